@@ -0,0 +1,89 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use mail_parser::*;
+
+// Counts allocations to compare `parse` with `parse_into`
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const ITERATIONS: usize = 10_000;
+
+fn main() {
+    let mut input = String::from("Subject: test\nContent-Type: multipart/mixed; boundary=a\n\n");
+    for i in 0..20 {
+        input.push_str(&format!("--a\nContent-Type: text/plain\n\npart {i}\n"));
+    }
+    input.push_str("--a--\n");
+    // Simulate reading each message into a new buffer
+    let buffers = (0..ITERATIONS).map(|_| input.clone()).collect::<Vec<_>>();
+    let parser = MessageParser::default();
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for buffer in &buffers {
+        let message = parser.parse(buffer).unwrap();
+        assert_eq!(message.parts.len(), 21);
+    }
+    let parse_allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS;
+    println!(
+        "parse:      {} allocations per message, {:?}",
+        parse_allocations,
+        start.elapsed()
+    );
+
+    let mut message = Message::default();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for buffer in &buffers {
+        let mut reused = message.recycle();
+        assert!(parser.parse_into(buffer, &mut reused));
+        assert_eq!(reused.parts.len(), 21);
+        message = reused.recycle();
+    }
+    let parse_into_allocations = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / ITERATIONS;
+    println!(
+        "parse_into: {} allocations per message, {:?}",
+        parse_into_allocations,
+        start.elapsed()
+    );
+
+    assert!(
+        parse_into_allocations < parse_allocations,
+        "parse_into should allocate less than parse"
+    );
+}
@@ -9,7 +9,9 @@
  * except according to those terms.
  */
 
-use crate::{Addr, Address, Group};
+use std::borrow::Cow;
+
+use crate::{Addr, Address, AddressRawText, Group};
 
 impl<'x> Address<'x> {
     /// Returns the first address in the list, or the first address in the first group.
@@ -88,13 +90,13 @@ impl<'x> Address<'x> {
             Address::List(list) => list.iter().any(|a| {
                 a.address
                     .as_ref()
-                    .map_or(false, |a| a.eq_ignore_ascii_case(addr))
+                    .is_some_and(|a| a.eq_ignore_ascii_case(addr))
             }),
             Address::Group(group) => group.iter().any(|group| {
                 group.addresses.iter().any(|a| {
                     a.address
                         .as_ref()
-                        .map_or(false, |a| a.eq_ignore_ascii_case(addr))
+                        .is_some_and(|a| a.eq_ignore_ascii_case(addr))
                 })
             }),
         }
@@ -150,4 +152,142 @@ impl<'x> Addr<'x> {
     pub fn address(&self) -> Option<&str> {
         self.address.as_deref()
     }
+
+    /// Returns the address with its domain part lowercased, suitable for
+    /// comparing addresses. The local part is kept as is since it may be
+    /// case-sensitive (RFC 5321, Section 2.4).
+    pub fn normalized_address(&self) -> Option<Cow<'_, str>> {
+        let address = self.trimmed_address()?;
+        match address.rsplit_once('@') {
+            Some((local_part, domain)) if domain.chars().any(char::is_uppercase) => {
+                Some(format!("{local_part}@{}", domain.to_lowercase()).into())
+            }
+            _ => Some(address.into()),
+        }
+    }
+
+    /// Returns the local part of the address, before the last `@`.
+    pub fn local_part(&self) -> Option<&str> {
+        let address = self.trimmed_address()?;
+        Some(
+            address
+                .rsplit_once('@')
+                .map_or(address, |(local_part, _)| local_part),
+        )
+    }
+
+    /// Returns the domain part of the address, after the last `@`.
+    pub fn domain(&self) -> Option<&str> {
+        self.trimmed_address()?
+            .rsplit_once('@')
+            .map(|(_, domain)| domain)
+    }
+
+    fn trimmed_address(&self) -> Option<&str> {
+        let address = self.address.as_deref()?.trim();
+        let address = address
+            .strip_prefix('<')
+            .and_then(|address| address.strip_suffix('>'))
+            .map_or(address, |address| address.trim());
+        (!address.is_empty()).then_some(address)
+    }
+}
+
+impl<'x> AddressRawText<'x> {
+    /// Returns the original text of the address at `index`, in the order
+    /// returned by [`Address::iter`].
+    pub fn address(&self, index: usize) -> Option<&str> {
+        self.groups
+            .iter()
+            .flat_map(|(_, addresses)| addresses)
+            .nth(index)
+            .map(|address| address.as_ref())
+    }
+
+    /// Returns the original text of the group at `index`, from its name to
+    /// its closing `;`, or the text of its addresses when they are not part
+    /// of a named group. Only available for [`Address::Group`] values.
+    pub fn group(&self, index: usize) -> Option<&str> {
+        self.groups.get(index)?.0.as_deref()
+    }
+
+    /// Returns the original text of the address at `index` of the group at
+    /// `group`.
+    pub fn group_address(&self, group: usize, index: usize) -> Option<&str> {
+        self.groups
+            .get(group)?
+            .1
+            .get(index)
+            .map(|address| address.as_ref())
+    }
+
+    pub fn into_owned(self) -> AddressRawText<'static> {
+        AddressRawText {
+            groups: self
+                .groups
+                .into_iter()
+                .map(|(group, addresses)| {
+                    (
+                        group.map(|s| s.into_owned().into()),
+                        addresses
+                            .into_iter()
+                            .map(|s| s.into_owned().into())
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Addr, MessageParser};
+
+    #[test]
+    fn normalized_address() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "From: John Doe <JOHN@Example.COM>\n",
+                "To: jane@example.com, \"Weird\" <\"a@b\"@EXAMPLE.org>, <postmaster>\n",
+                "\n",
+                "Hello",
+            ))
+            .unwrap();
+
+        let from = message.from().unwrap().first().unwrap();
+        assert_eq!(from.normalized_address().unwrap(), "JOHN@example.com");
+        assert_eq!(from.local_part(), Some("JOHN"));
+        assert_eq!(from.domain(), Some("Example.COM"));
+        assert!(from
+            .normalized_address()
+            .unwrap()
+            .eq_ignore_ascii_case("john@example.com"));
+
+        assert_eq!(
+            message
+                .to()
+                .unwrap()
+                .iter()
+                .map(|addr| (
+                    addr.normalized_address().unwrap(),
+                    addr.local_part(),
+                    addr.domain()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("jane@example.com".into(), Some("jane"), Some("example.com")),
+                (
+                    "\"a@b\"@example.org".into(),
+                    Some("\"a@b\""),
+                    Some("EXAMPLE.org")
+                ),
+                ("postmaster".into(), Some("postmaster"), None),
+            ]
+        );
+
+        let addr = Addr::new(None, " <Info@Example.com> ");
+        assert_eq!(addr.normalized_address().unwrap(), "Info@example.com");
+        assert_eq!(Addr::new(Some("Empty"), " ").normalized_address(), None);
+    }
 }
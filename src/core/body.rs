@@ -9,7 +9,12 @@
  * except according to those terms.
  */
 
-use crate::{AttachmentIterator, BodyPartIterator, Message, MessagePart, MessagePartId, PartType};
+use std::collections::HashSet;
+
+use crate::{
+    core::message::content_id_key, AttachmentIterator, BodyPartIterator, Message, MessagePart,
+    MessagePartId, MimeHeaders, PartType, PartWalker,
+};
 
 impl PartType<'_> {
     #[allow(clippy::len_without_is_empty)]
@@ -56,3 +61,59 @@ impl<'x> Iterator for AttachmentIterator<'x> {
         self.message.attachment(self.pos as usize)
     }
 }
+
+impl MessagePart<'_> {
+    // Inline parts are displayed within the body, they have a Content-ID and
+    // either an `inline` disposition or a `cid:` reference among `cids`
+    pub(crate) fn is_inline_part(&self, cids: &HashSet<String>) -> bool {
+        self.content_id().is_some_and(|id| {
+            self.content_disposition().is_some_and(|cd| cd.is_inline())
+                || cids.contains(content_id_key(id).as_ref())
+        })
+    }
+}
+
+impl<'x> PartWalker<'x> {
+    pub(crate) fn new(message: &'x Message<'x>) -> PartWalker<'x> {
+        PartWalker {
+            stack: if !message.parts.is_empty() {
+                vec![(0, message, 0)]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+impl<'x> Iterator for PartWalker<'x> {
+    type Item = (usize, &'x MessagePart<'x>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (depth, message, part_id) = self.stack.pop()?;
+            let part = if let Some(part) = message.parts.get(part_id) {
+                part
+            } else {
+                continue;
+            };
+
+            // Children are pushed in reverse so they are visited in document order
+            match &part.body {
+                PartType::Multipart(sub_parts) => {
+                    self.stack.extend(
+                        sub_parts
+                            .iter()
+                            .rev()
+                            .map(|&sub_part_id| (depth + 1, message, sub_part_id)),
+                    );
+                }
+                PartType::Message(nested) if !nested.parts.is_empty() => {
+                    self.stack.push((depth + 1, nested, 0));
+                }
+                _ => (),
+            }
+
+            return Some((depth, part));
+        }
+    }
+}
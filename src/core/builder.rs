@@ -9,7 +9,10 @@
  * except according to those terms.
  */
 
-use crate::{HeaderName, HeaderValue, MessageParser};
+use crate::{
+    parsers::fields::content_type::MAX_ATTRIBUTE_CONTINUATIONS, ContentTypePolicy, HeaderName,
+    HeaderValue, MessageParser, ParserLimits, TransferEncodingPolicy, UnknownHeaderFnc,
+};
 
 impl MessageParser {
     /// Create a new builder for a message parser using the default settings.
@@ -23,6 +26,16 @@ impl MessageParser {
         Self {
             header_map: Default::default(),
             def_hdr_parse_fnc: |s| s.parse_raw(),
+            comment_display_names: false,
+            lenient_address_names: false,
+            lenient_attribute_values: false,
+            max_attribute_name_len: usize::MAX,
+            max_attribute_value_len: usize::MAX,
+            max_attribute_continuations: MAX_ATTRIBUTE_CONTINUATIONS,
+            transfer_encoding_policy: TransferEncodingPolicy::First,
+            content_type_policy: ContentTypePolicy::Explicit,
+            limits: ParserLimits::default(),
+            unknown_header_fnc: None,
         }
     }
 
@@ -39,7 +52,7 @@ impl MessageParser {
     ///
     pub fn with_mime_headers(self) -> Self {
         self.header_content_type(HeaderName::ContentType)
-            .header_content_type(HeaderName::ContentDisposition)
+            .header_content_disposition(HeaderName::ContentDisposition)
             .header_id(HeaderName::ContentId)
             .header_text(HeaderName::ContentDescription)
             .header_text(HeaderName::ContentLocation)
@@ -149,13 +162,28 @@ impl MessageParser {
         self
     }
 
-    /// Parse a header as a MIME `Content-Type` or `Content-Disposition` type.
+    /// Parse a header as a MIME `Content-Type` type.
     pub fn header_content_type(mut self, header: impl Into<HeaderName<'static>>) -> Self {
         self.header_map
             .insert(header.into(), |s| s.parse_content_type());
         self
     }
 
+    /// Parse a header as a MIME `Content-Type` type, rejecting values without a
+    /// subtype, see [`MessageStream::parse_content_type_strict`](crate::parsers::MessageStream::parse_content_type_strict).
+    pub fn header_content_type_strict(mut self, header: impl Into<HeaderName<'static>>) -> Self {
+        self.header_map
+            .insert(header.into(), |s| s.parse_content_type_strict());
+        self
+    }
+
+    /// Parse a header as a MIME `Content-Disposition` type, which has no subtype.
+    pub fn header_content_disposition(mut self, header: impl Into<HeaderName<'static>>) -> Self {
+        self.header_map
+            .insert(header.into(), |s| s.parse_content_disposition());
+        self
+    }
+
     /// Parse a header as a comma-separated list of values.
     pub fn header_comma_separated(mut self, header: impl Into<HeaderName<'static>>) -> Self {
         self.header_map
@@ -205,6 +233,126 @@ impl MessageParser {
         };
         self
     }
+
+    /// Use comments as display names. By default, comments are not display
+    /// names (RFC 5322, Section 3.4) and an address such as
+    /// `jdoe@example.com (John Doe)` has no name. With this option, an address
+    /// without a display name takes it from its comment instead.
+    pub fn with_comment_display_names(mut self) -> Self {
+        self.comment_display_names = true;
+        self
+    }
+
+    /// Accept unquoted display names containing commas, as sent by some broken
+    /// clients. When enabled, `Doe, John <jdoe@example.com>` is parsed as a single
+    /// address named `Doe, John` rather than as two addresses, provided that
+    /// nothing but the rest of the name follows the comma up to the `<`.
+    pub fn with_lenient_address_names(mut self) -> Self {
+        self.lenient_address_names = true;
+        self
+    }
+
+    /// Accept `;`, line folds and parentheses in unquoted `Content-Type` and
+    /// `Content-Disposition` attribute values, as sent by some broken clients.
+    /// When enabled, a parameter with no `=` following an unquoted value is
+    /// appended to that value, so `filename=foo;bar.txt` is parsed as the file
+    /// name `foo;bar.txt`. Likewise, a folded line that does not start with a
+    /// new `name=value` parameter continues the unquoted value it interrupts,
+    /// and parentheses attached to an unquoted value are kept as part of it
+    /// instead of starting a comment when they are balanced and contain no
+    /// whitespace, as in `filename=report(final).pdf`.
+    pub fn with_lenient_attribute_values(mut self) -> Self {
+        self.lenient_attribute_values = true;
+        self
+    }
+
+    /// Set how parts with more than one `Content-Transfer-Encoding` header field
+    /// are decoded. Such parts can be detected with
+    /// [`MessagePart::has_duplicate_transfer_encoding`](crate::MessagePart::has_duplicate_transfer_encoding).
+    pub fn with_transfer_encoding_policy(mut self, policy: TransferEncodingPolicy) -> Self {
+        self.transfer_encoding_policy = policy;
+        self
+    }
+
+    /// Set whether the explicit `Content-Type` header field of a part or its
+    /// default type within the message structure is used when the two
+    /// conflict. See [`ContentTypePolicy`] for the malformed cases affected.
+    pub fn with_content_type_policy(mut self, policy: ContentTypePolicy) -> Self {
+        self.content_type_policy = policy;
+        self
+    }
+
+    /// Limit the number of MIME parts created while parsing a message,
+    /// including the parts of nested messages. Once the limit is reached, the
+    /// remaining contents are kept undecoded in one additional binary part and
+    /// the message is flagged as truncated, see [`Message::is_truncated`](crate::Message::is_truncated).
+    pub fn with_max_parts(mut self, max_parts: usize) -> Self {
+        self.limits.max_parts = std::cmp::max(max_parts, 1);
+        self
+    }
+
+    /// Limit the nesting depth of `multipart/*` and `message/rfc822` parts,
+    /// where the root part has a depth of `0`. Container parts whose children
+    /// would exceed the limit are not expanded, their contents are kept
+    /// undecoded in a binary part and the message is flagged as truncated,
+    /// see [`Message::is_truncated`](crate::Message::is_truncated).
+    pub fn with_max_nesting_depth(mut self, max_nesting_depth: usize) -> Self {
+        self.limits.max_nesting_depth = max_nesting_depth;
+        self
+    }
+
+    /// Limit the size in bytes of the decoded contents of each MIME part.
+    /// Base64 and quoted-printable decoding stops storing output once the
+    /// limit is reached, longer contents are cut at the limit and the message
+    /// is flagged as truncated, see [`Message::is_truncated`](crate::Message::is_truncated).
+    pub fn with_max_part_size(mut self, max_part_size: usize) -> Self {
+        self.limits.max_part_size = max_part_size;
+        self
+    }
+
+    /// Set all the limits on the parts of a message at once, equivalent to
+    /// calling [`Self::with_max_parts`], [`Self::with_max_nesting_depth`] and
+    /// [`Self::with_max_part_size`].
+    pub fn with_limits(self, limits: ParserLimits) -> Self {
+        self.with_max_parts(limits.max_parts)
+            .with_max_nesting_depth(limits.max_nesting_depth)
+            .with_max_part_size(limits.max_part_size)
+    }
+
+    /// Limit the length in bytes of `Content-Type` and `Content-Disposition`
+    /// attribute names. Attributes with longer names are dropped, which is
+    /// reported by [`MessageParser::content_type_details`].
+    pub fn with_max_attribute_name_len(mut self, max_len: usize) -> Self {
+        self.max_attribute_name_len = max_len;
+        self
+    }
+
+    /// Limit the length in bytes of `Content-Type` and `Content-Disposition`
+    /// attribute values, after merging RFC 2231 continuations. Longer values are
+    /// truncated, which is reported by [`MessageParser::content_type_details`].
+    pub fn with_max_attribute_value_len(mut self, max_len: usize) -> Self {
+        self.max_attribute_value_len = max_len;
+        self
+    }
+
+    /// Limit the number of RFC 2231 continuation segments (`name*1`, `name*2`,
+    /// ...) collected per `Content-Type` or `Content-Disposition` header, 1000 by
+    /// default. Additional segments are ignored, which is reported by
+    /// [`MessageParser::content_type_details`].
+    pub fn with_max_attribute_continuations(mut self, max_continuations: usize) -> Self {
+        self.max_attribute_continuations = max_continuations;
+        self
+    }
+
+    /// Call `fnc` for every header field not recognized by the parser (those
+    /// named [`HeaderName::Other`]), in the message and in all its parts. The
+    /// callback receives the header name as found in the message and its raw
+    /// value, excluding the trailing line break. Unknown headers are still
+    /// parsed and stored as usual.
+    pub fn on_unknown_header(mut self, fnc: UnknownHeaderFnc) -> Self {
+        self.unknown_header_fnc = Some(fnc);
+        self
+    }
 }
 
 impl Default for MessageParser {
@@ -212,3 +360,13 @@ impl Default for MessageParser {
         Self::new()
     }
 }
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        ParserLimits {
+            max_parts: usize::MAX,
+            max_nesting_depth: usize::MAX,
+            max_part_size: usize::MAX,
+        }
+    }
+}
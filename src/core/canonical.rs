@@ -0,0 +1,316 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use crate::{HeaderName, Message, MessagePartId, PartType};
+
+static BASE64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl<'x> Message<'x> {
+    /// Returns a normalized, deterministic serialization of the message that
+    /// is suitable for hashing and deduplication.
+    ///
+    /// Unlike [`Message::raw_message`], the output is not byte-identical to the
+    /// original message. Two messages that only differ in transfer encoding,
+    /// header order, header folding or line endings produce the same output.
+    /// The following rules are applied to every MIME part, starting from the root:
+    ///
+    /// - `Content-Transfer-Encoding` headers are dropped.
+    /// - Header names are lowercased and their raw values are unfolded, runs of
+    ///   whitespace are collapsed into a single space and leading/trailing whitespace
+    ///   is removed. Encoded-words are left untouched.
+    /// - Headers are sorted by name; headers sharing a name keep their original
+    ///   relative order. Each header is written as `name:value\r\n` and the header
+    ///   block is terminated by `\r\n`.
+    /// - Text and HTML bodies are written as UTF-8 after charset and transfer decoding,
+    ///   with all line endings converted to `\r\n`.
+    /// - Text, HTML and binary bodies are base64 encoded in lines of 76 characters,
+    ///   each followed by `\r\n`, and are preceded by a `content-transfer-encoding:base64`
+    ///   header.
+    /// - Multipart children are separated by `--boundary\r\n` and closed with
+    ///   `--boundary--\r\n`, using the boundary from the `Content-Type` header.
+    ///   Preambles and epilogues are dropped.
+    /// - Nested `message/rfc822` parts are canonicalized recursively using these rules.
+    ///
+    /// These rules will not change within a major version of this crate.
+    pub fn canonicalize(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.raw_message.len());
+        if !self.parts.is_empty() {
+            self.canonicalize_part(0, &mut buf);
+        }
+        buf
+    }
+
+    /// Returns an iterator over all occurrences of a top-level header, in the
+    /// order they appear in the message, yielding for each field both its
+    /// "simple" and "relaxed" canonical forms as defined by RFC 6376 (DKIM).
+    ///
+    /// The simple form is the verbatim header field, including its name and the
+    /// trailing line break. The relaxed form has its name lowercased, its value
+    /// unfolded, whitespace runs collapsed into a single space, whitespace
+    /// around the colon and at the end of the value removed, and is terminated
+    /// by `\r\n`.
+    pub fn header_canonicalized(
+        &self,
+        name: impl Into<HeaderName<'x>>,
+    ) -> impl DoubleEndedIterator<Item = (&[u8], Vec<u8>)> {
+        let name = name.into();
+        self.parts
+            .first()
+            .map(|part| part.headers.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter(move |header| header.name == name)
+            .filter_map(|header| {
+                let simple = self
+                    .raw_message
+                    .get(header.offset_field..header.offset_end)?;
+                let name = self
+                    .raw_message
+                    .get(header.offset_field..header.offset_start.checked_sub(1)?)?;
+                let value = self
+                    .raw_message
+                    .get(header.offset_start..header.offset_end)?;
+
+                let mut relaxed = Vec::with_capacity(simple.len());
+                relaxed.extend(name.trim_ascii().iter().map(|ch| ch.to_ascii_lowercase()));
+                relaxed.push(b':');
+                relaxed.extend_from_slice(&normalize_header_value(value));
+                relaxed.extend_from_slice(b"\r\n");
+
+                Some((simple, relaxed))
+            })
+    }
+
+    fn canonicalize_part(&self, part_id: MessagePartId, buf: &mut Vec<u8>) {
+        let part = if let Some(part) = self.parts.get(part_id) {
+            part
+        } else {
+            return;
+        };
+
+        // Normalize and sort headers
+        let mut headers = part
+            .headers
+            .iter()
+            .filter(|h| h.name != HeaderName::ContentTransferEncoding)
+            .map(|h| {
+                (
+                    h.name.as_str().to_ascii_lowercase(),
+                    normalize_header_value(
+                        self.raw_message
+                            .get(h.offset_start..h.offset_end)
+                            .unwrap_or_default(),
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+        if matches!(
+            part.body,
+            PartType::Text(_) | PartType::Html(_) | PartType::Binary(_) | PartType::InlineBinary(_)
+        ) {
+            headers.push(("content-transfer-encoding".to_string(), b"base64".to_vec()));
+        }
+        headers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (name, value) in headers {
+            buf.extend_from_slice(name.as_bytes());
+            buf.push(b':');
+            buf.extend_from_slice(&value);
+            buf.extend_from_slice(b"\r\n");
+        }
+        buf.extend_from_slice(b"\r\n");
+
+        match &part.body {
+            PartType::Text(text) | PartType::Html(text) => {
+                base64_encode(&normalize_line_endings(text.as_bytes()), buf);
+            }
+            PartType::Binary(bytes) | PartType::InlineBinary(bytes) => {
+                base64_encode(bytes, buf);
+            }
+            PartType::Message(message) => {
+                buf.extend_from_slice(&message.canonicalize());
+            }
+            PartType::Multipart(sub_parts) => {
+                let boundary = part
+                    .headers
+                    .iter()
+                    .find(|h| h.name == HeaderName::ContentType)
+                    .and_then(|h| h.value.as_content_type())
+                    .and_then(|ct| ct.attribute("boundary"))
+                    .unwrap_or_default();
+                for &sub_part_id in sub_parts {
+                    buf.extend_from_slice(b"--");
+                    buf.extend_from_slice(boundary.as_bytes());
+                    buf.extend_from_slice(b"\r\n");
+                    self.canonicalize_part(sub_part_id, buf);
+                }
+                buf.extend_from_slice(b"--");
+                buf.extend_from_slice(boundary.as_bytes());
+                buf.extend_from_slice(b"--\r\n");
+            }
+        }
+    }
+}
+
+fn normalize_header_value(value: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(value.len());
+    let mut last_is_space = false;
+
+    for &ch in value {
+        match ch {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                last_is_space = true;
+            }
+            _ => {
+                if last_is_space && !result.is_empty() {
+                    result.push(b' ');
+                }
+                last_is_space = false;
+                result.push(ch);
+            }
+        }
+    }
+
+    result
+}
+
+fn normalize_line_endings(text: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(text.len() + (text.len() / 40));
+    let mut iter = text.iter().peekable();
+
+    while let Some(&ch) = iter.next() {
+        match ch {
+            b'\r' => {
+                if iter.peek() == Some(&&b'\n') {
+                    iter.next();
+                }
+                result.extend_from_slice(b"\r\n");
+            }
+            b'\n' => result.extend_from_slice(b"\r\n"),
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+fn base64_encode(bytes: &[u8], buf: &mut Vec<u8>) {
+    for line in bytes.chunks(57) {
+        for chunk in line.chunks(3) {
+            let b0 = chunk[0] as usize;
+            let b1 = chunk.get(1).copied().unwrap_or(0) as usize;
+            let b2 = chunk.get(2).copied().unwrap_or(0) as usize;
+
+            buf.push(BASE64_CHARS[b0 >> 2]);
+            buf.push(BASE64_CHARS[((b0 & 0x03) << 4) | (b1 >> 4)]);
+            buf.push(if chunk.len() > 1 {
+                BASE64_CHARS[((b1 & 0x0f) << 2) | (b2 >> 6)]
+            } else {
+                b'='
+            });
+            buf.push(if chunk.len() > 2 {
+                BASE64_CHARS[b2 & 0x3f]
+            } else {
+                b'='
+            });
+        }
+        buf.extend_from_slice(b"\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MessageParser;
+
+    #[test]
+    fn canonicalize() {
+        let message_qp = concat!(
+            "From: Art Vandelay <art@vandelay.com>\n",
+            "Subject: Why not both\n  importing AND exporting?\n",
+            "Content-Type: multipart/mixed; boundary=\"festivus\"\n",
+            "\n",
+            "--festivus\n",
+            "Content-Type: text/plain; charset=\"us-ascii\"\n",
+            "Content-Transfer-Encoding: quoted-printable\n",
+            "\n",
+            "Hello=2C world!\n",
+            "--festivus--\n"
+        );
+        let message_b64 = concat!(
+            "subject:   Why not both importing AND exporting?\r\n",
+            "FROM: Art Vandelay <art@vandelay.com>\r\n",
+            "Content-Type: multipart/mixed; boundary=\"festivus\"\r\n",
+            "\r\n",
+            "This is a preamble.\r\n",
+            "--festivus\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "Content-Type: text/plain; charset=\"us-ascii\"\r\n",
+            "\r\n",
+            "SGVsbG8sIHdvcmxkIQ==\r\n",
+            "--festivus--\r\n"
+        );
+
+        let parser = MessageParser::default();
+        let canonical = parser.parse(message_qp).unwrap().canonicalize();
+
+        assert_eq!(canonical, parser.parse(message_b64).unwrap().canonicalize());
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            concat!(
+                "content-type:multipart/mixed; boundary=\"festivus\"\r\n",
+                "from:Art Vandelay <art@vandelay.com>\r\n",
+                "subject:Why not both importing AND exporting?\r\n",
+                "\r\n",
+                "--festivus\r\n",
+                "content-transfer-encoding:base64\r\n",
+                "content-type:text/plain; charset=\"us-ascii\"\r\n",
+                "\r\n",
+                "SGVsbG8sIHdvcmxkIQ==\r\n",
+                "--festivus--\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn header_canonicalized() {
+        // Example from RFC 6376, section 3.4.5
+        let input = "A: X\r\nB : Y\t\r\n\tZ  \r\nSubject: one\r\nsubject:  two \r\n\r\nbody\r\n";
+        let message = MessageParser::default().parse(input).unwrap();
+
+        for (name, expected) in [
+            ("A", vec![("A: X\r\n", "a:X\r\n")]),
+            ("b", vec![("B : Y\t\r\n\tZ  \r\n", "b:Y Z\r\n")]),
+            (
+                "Subject",
+                vec![
+                    ("Subject: one\r\n", "subject:one\r\n"),
+                    ("subject:  two \r\n", "subject:two\r\n"),
+                ],
+            ),
+            ("X-Missing", vec![]),
+        ] {
+            assert_eq!(
+                message
+                    .header_canonicalized(name)
+                    .map(|(simple, relaxed)| (
+                        std::str::from_utf8(simple).unwrap(),
+                        String::from_utf8(relaxed).unwrap()
+                    ))
+                    .collect::<Vec<_>>(),
+                expected
+                    .into_iter()
+                    .map(|(simple, relaxed)| (simple, relaxed.to_string()))
+                    .collect::<Vec<_>>(),
+                "failed for {name}"
+            );
+        }
+    }
+}
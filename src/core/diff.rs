@@ -0,0 +1,217 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use crate::{HeaderDiff, Message, MessageDiff, MessagePart, PartDiff, PartType};
+
+impl MessageDiff {
+    /// Returns `true` if no differences were found.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.parts.is_empty()
+    }
+}
+
+impl Message<'_> {
+    /// Compares this message with `other`, which is considered the newer
+    /// version, and returns the header fields and parts that differ. Header
+    /// values are compared as found in the raw message, after unfolding.
+    pub fn diff(&self, other: &Message<'_>) -> MessageDiff {
+        self.diff_(other, false)
+    }
+
+    /// Same as [`Message::diff`], but ignores differences in whitespace and
+    /// letter case in header values and text bodies, as well as line endings.
+    pub fn diff_normalized(&self, other: &Message<'_>) -> MessageDiff {
+        self.diff_(other, true)
+    }
+
+    fn diff_(&self, other: &Message<'_>, normalize: bool) -> MessageDiff {
+        let mut diff = MessageDiff::default();
+
+        for part_id in 0..std::cmp::max(self.parts.len(), other.parts.len()) {
+            let (old_part, new_part) = match (self.parts.get(part_id), other.parts.get(part_id)) {
+                (Some(old_part), Some(new_part)) => (old_part, new_part),
+                (Some(_), None) => {
+                    diff.parts.push(PartDiff::Removed(part_id));
+                    continue;
+                }
+                (None, Some(_)) => {
+                    diff.parts.push(PartDiff::Added(part_id));
+                    continue;
+                }
+                (None, None) => unreachable!(),
+            };
+
+            let old_headers = self.part_headers(old_part);
+            let mut new_headers = other.part_headers(new_part);
+            for (name, old_value) in old_headers {
+                // Match the next unused header field with the same name
+                if let Some(pos) = new_headers.iter().position(|(n, _)| n == &name) {
+                    let (_, new_value) = new_headers.remove(pos);
+                    if !values_eq(&old_value, &new_value, normalize) {
+                        diff.headers.push(HeaderDiff::Changed {
+                            part_id,
+                            name,
+                            old_value,
+                            new_value,
+                        });
+                    }
+                } else {
+                    diff.headers.push(HeaderDiff::Removed {
+                        part_id,
+                        name,
+                        value: old_value,
+                    });
+                }
+            }
+            for (name, value) in new_headers {
+                diff.headers.push(HeaderDiff::Added {
+                    part_id,
+                    name,
+                    value,
+                });
+            }
+
+            if !bodies_eq(old_part, new_part, normalize) {
+                diff.parts.push(PartDiff::BodyChanged(part_id));
+            }
+        }
+
+        diff
+    }
+
+    fn part_headers(&self, part: &MessagePart<'_>) -> Vec<(String, String)> {
+        part.headers
+            .iter()
+            .map(|header| {
+                let value = self
+                    .raw_message
+                    .get(header.offset_start..header.offset_end)
+                    .map(String::from_utf8_lossy)
+                    .unwrap_or_default();
+                (
+                    header.name.as_str().to_ascii_lowercase(),
+                    value.replace(['\r', '\n'], "").trim().to_string(),
+                )
+            })
+            .collect()
+    }
+}
+
+fn values_eq(a: &str, b: &str, normalize: bool) -> bool {
+    if normalize {
+        a.split_whitespace()
+            .map(|word| word.to_lowercase())
+            .eq(b.split_whitespace().map(|word| word.to_lowercase()))
+    } else {
+        a == b
+    }
+}
+
+fn bodies_eq(a: &MessagePart<'_>, b: &MessagePart<'_>, normalize: bool) -> bool {
+    match (&a.body, &b.body) {
+        // Added or removed children are reported separately
+        (PartType::Multipart(_), PartType::Multipart(_)) => true,
+        (PartType::Text(a), PartType::Text(b)) | (PartType::Html(a), PartType::Html(b)) => {
+            values_eq(a, b, normalize)
+        }
+        (PartType::Message(a), PartType::Message(b)) => (if normalize {
+            a.diff_normalized(b)
+        } else {
+            a.diff(b)
+        })
+        .is_empty(),
+        (
+            PartType::Binary(a) | PartType::InlineBinary(a),
+            PartType::Binary(b) | PartType::InlineBinary(b),
+        ) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HeaderDiff, MessageDiff, MessageParser, PartDiff};
+
+    #[test]
+    fn message_diff() {
+        let original = MessageParser::default()
+            .parse(concat!(
+                "From: john@example.org\r\n",
+                "Subject: Quarterly\r\n report\r\n",
+                "X-Tracking: 1234\r\n",
+                "Content-Type: multipart/mixed; boundary=\"a\"\r\n\r\n",
+                "--a\r\n",
+                "Content-Type: text/plain\r\n\r\n",
+                "Hello  world\r\n",
+                "--a\r\n",
+                "Content-Type: application/octet-stream\r\n\r\n",
+                "binary\r\n",
+                "--a--\r\n",
+            ))
+            .unwrap();
+        let transformed = MessageParser::default()
+            .parse(concat!(
+                "From: john@example.org\n",
+                "Subject: QUARTERLY report\n",
+                "X-Scanned: yes\n",
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: text/plain\n\n",
+                "Hello world\n",
+                "--a--\n",
+            ))
+            .unwrap();
+
+        assert!(original.diff(&original).is_empty());
+        assert_eq!(
+            original.diff(&transformed),
+            MessageDiff {
+                headers: vec![
+                    HeaderDiff::Changed {
+                        part_id: 0,
+                        name: "subject".to_string(),
+                        old_value: "Quarterly report".to_string(),
+                        new_value: "QUARTERLY report".to_string(),
+                    },
+                    HeaderDiff::Removed {
+                        part_id: 0,
+                        name: "x-tracking".to_string(),
+                        value: "1234".to_string(),
+                    },
+                    HeaderDiff::Added {
+                        part_id: 0,
+                        name: "x-scanned".to_string(),
+                        value: "yes".to_string(),
+                    },
+                ],
+                parts: vec![PartDiff::BodyChanged(1), PartDiff::Removed(2)],
+            }
+        );
+        assert_eq!(
+            original.diff_normalized(&transformed),
+            MessageDiff {
+                headers: vec![
+                    HeaderDiff::Removed {
+                        part_id: 0,
+                        name: "x-tracking".to_string(),
+                        value: "1234".to_string(),
+                    },
+                    HeaderDiff::Added {
+                        part_id: 0,
+                        name: "x-scanned".to_string(),
+                        value: "yes".to_string(),
+                    },
+                ],
+                parts: vec![PartDiff::Removed(2)],
+            }
+        );
+    }
+}
@@ -11,14 +11,32 @@
 
 use core::fmt;
 use std::hash::Hash;
+use std::io::{self, Write};
 use std::net::IpAddr;
 use std::{borrow::Cow, fmt::Display};
 
 use crate::{
-    Address, ContentType, DateTime, GetHeader, Greeting, Header, HeaderName, HeaderValue, Host,
-    Message, MessagePart, MessagePartId, MimeHeaders, PartType, Protocol, Received, TlsVersion,
+    decoders::{
+        base64::Base64Decoder,
+        charsets::map::{canonical_charset_name, charset_decoder},
+        quoted_printable::QuotedPrintableDecoder,
+    },
+    Address, AutoSubmitted, ContentType, ContentTypeAttribute, ContentTypeDetails,
+    ContentTypeWarning, ContentTypeWarningReason, DateTime, EffectiveEncoding, EncodedWord,
+    Encoding, GetHeader, Greeting, Header, HeaderName, HeaderValue, Host, Message, MessagePart,
+    MessagePartId, MimeHeaders, PartHeaders, PartType, Protocol, Received, TlsVersion,
+    TransferEncoding,
 };
 
+// Number of raw bytes transfer-decoded at a time by `MessagePart::decode_to`
+const DECODE_CHUNK_LEN: usize = 8192;
+
+static TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "text", "log", "csv", "tsv", "md", "json", "xml", "yaml", "yml", "ini", "cfg", "conf",
+    "htm", "html", "c", "h", "cpp", "rs", "py", "js", "ts", "java", "sh", "sql", "diff", "patch",
+    "ics", "vcf",
+];
+
 impl<'x> Header<'x> {
     /// Returns the header name
     pub fn name(&self) -> &str {
@@ -57,6 +75,38 @@ impl<'x> Header<'x> {
     }
 }
 
+impl<'x> EncodedWord<'x> {
+    /// Returns the charset name, as declared in the encoded-word
+    pub fn charset(&self) -> &str {
+        &self.charset
+    }
+
+    /// Returns the encoding used, either `Base64` or `QuotedPrintable`
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the raw offset where the encoded-word starts
+    pub fn offset_start(&self) -> usize {
+        self.offset_start
+    }
+
+    /// Returns the raw offset where the encoded-word ends
+    pub fn offset_end(&self) -> usize {
+        self.offset_end
+    }
+
+    /// Returns an owned version of the encoded-word
+    pub fn into_owned(self) -> EncodedWord<'static> {
+        EncodedWord {
+            charset: self.charset.into_owned().into(),
+            encoding: self.encoding,
+            offset_start: self.offset_start,
+            offset_end: self.offset_end,
+        }
+    }
+}
+
 impl<'x> HeaderValue<'x> {
     pub fn is_empty(&self) -> bool {
         *self == HeaderValue::Empty
@@ -586,6 +636,12 @@ impl<'x> MimeHeaders<'x> for Message<'x> {
 }
 
 impl<'x> MessagePart<'x> {
+    /// Returns the Content-Description header field of the part, unfolded and
+    /// with RFC 2047 encoded-words decoded.
+    pub fn description(&self) -> Option<&str> {
+        self.content_description()
+    }
+
     /// Returns the body part's contents as a `u8` slice
     pub fn contents(&self) -> &[u8] {
         match &self.body {
@@ -596,6 +652,60 @@ impl<'x> MessagePart<'x> {
         }
     }
 
+    /// Writes the body part's contents to `w`, producing the same bytes as
+    /// [`MessagePart::contents`]. Binary and `message/rfc822` bodies are read
+    /// from `raw_message`, the data the offsets of the part refer to, and
+    /// transfer-decoded in chunks of a fixed size. Text bodies are written as
+    /// converted to UTF-8 while parsing.
+    ///
+    /// Contents cut by [`MessageParser::with_max_part_size`](crate::MessageParser::with_max_part_size)
+    /// are written in full.
+    pub fn decode_to<W: Write>(&self, raw_message: &[u8], w: &mut W) -> io::Result<()> {
+        let raw = match (
+            &self.body,
+            raw_message.get(self.offset_body..self.offset_end),
+        ) {
+            (PartType::Binary(_) | PartType::InlineBinary(_) | PartType::Message(_), Some(raw)) => {
+                raw
+            }
+            _ => return w.write_all(self.contents()),
+        };
+
+        if self.encoding == Encoding::None {
+            return w.write_all(raw);
+        }
+
+        let mut base64 = Base64Decoder::default();
+        let mut quoted_printable = QuotedPrintableDecoder::new();
+        let mut buf = Vec::with_capacity(DECODE_CHUNK_LEN);
+        for chunk in raw.chunks(DECODE_CHUNK_LEN) {
+            buf.clear();
+            let is_valid = if self.encoding == Encoding::Base64 {
+                base64.decode(chunk, &mut buf)
+            } else {
+                quoted_printable.decode(chunk, &mut buf)
+            };
+            if !is_valid {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "part body does not match its transfer encoding",
+                ));
+            }
+            w.write_all(&buf)?;
+        }
+
+        if self.encoding == Encoding::QuotedPrintable {
+            buf.clear();
+            quoted_printable.finish(
+                matches!(raw_message.get(self.offset_end), Some(b'\r' | b'\n')),
+                &mut buf,
+            );
+            w.write_all(&buf)?;
+        }
+
+        Ok(())
+    }
+
     /// Returns the body part's contents as a `str`
     pub fn text_contents(&self) -> Option<&str> {
         match &self.body {
@@ -608,6 +718,66 @@ impl<'x> MessagePart<'x> {
         }
     }
 
+    /// Returns a text view of the part when its contents are plausibly text.
+    ///
+    /// Text parts are always returned. Binary parts are only considered when
+    /// their type is `application/octet-stream` (or missing) and their contents
+    /// contain no NUL bytes and less than 1% of control characters. The contents
+    /// are then returned if they are valid UTF-8, decodable with the declared
+    /// `charset` or, for files with a text extension such as `.txt` or `.csv`,
+    /// decoded as Windows-1252. Otherwise `None` is returned.
+    pub fn as_text_if_textual(&self) -> Option<Cow<'_, str>> {
+        let bytes = match &self.body {
+            PartType::Text(text) | PartType::Html(text) => return Some(text.as_ref().into()),
+            PartType::Binary(bytes) | PartType::InlineBinary(bytes)
+                if self.content_type().is_none_or(|ct| {
+                    ct.c_type.eq_ignore_ascii_case("application")
+                        && ct
+                            .c_subtype
+                            .as_ref()
+                            .is_some_and(|st| st.eq_ignore_ascii_case("octet-stream"))
+                }) =>
+            {
+                bytes.as_ref()
+            }
+            _ => return None,
+        };
+
+        if bytes.is_empty()
+            || bytes.contains(&0)
+            || bytes
+                .iter()
+                .filter(|&&ch| {
+                    (ch < 0x20 && !matches!(ch, b'\t' | b'\n' | b'\r' | b'\x0c')) || ch == 0x7f
+                })
+                .count()
+                * 100
+                >= bytes.len()
+        {
+            return None;
+        }
+
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            Some(text.into())
+        } else if let Some(decoder) = self
+            .content_type()
+            .and_then(|ct| ct.attribute("charset"))
+            .and_then(|charset| charset_decoder(charset.as_bytes()))
+        {
+            Some(decoder(bytes).into())
+        } else if self.attachment_name().is_some_and(|name| {
+            name.rsplit_once('.').is_some_and(|(_, ext)| {
+                TEXT_EXTENSIONS
+                    .iter()
+                    .any(|text_ext| ext.eq_ignore_ascii_case(text_ext))
+            })
+        }) {
+            charset_decoder(b"windows-1252").map(|decoder| decoder(bytes).into())
+        } else {
+            None
+        }
+    }
+
     /// Returns the nested message
     pub fn message(&self) -> Option<&Message<'x>> {
         if let PartType::Message(message) = &self.body {
@@ -661,6 +831,75 @@ impl<'x> MessagePart<'x> {
         matches!(self.body, PartType::Message(_))
     }
 
+    /// Returns `true` when the part wraps sub-structure, that is, when it is
+    /// a multipart or a nested message
+    pub fn is_container(&self) -> bool {
+        matches!(self.body, PartType::Multipart(_) | PartType::Message(_))
+    }
+
+    /// Returns `true` when the part holds content rather than other parts
+    pub fn is_leaf(&self) -> bool {
+        !self.is_container()
+    }
+
+    /// Returns the Content-Transfer-Encoding of the part, `7bit` when the
+    /// header field is missing. The encoding of a part is independent of the
+    /// encoding of its parent. Since `multipart/*` and `message/rfc822`
+    /// parts may only use identity encodings, any other encoding declared by
+    /// them is flagged as invalid. Such parts are still decoded by the
+    /// parser, as some clients wrap `message/rfc822` attachments in base64.
+    pub fn effective_encoding(&self) -> EffectiveEncoding {
+        let encoding = match self.encoding {
+            Encoding::QuotedPrintable => TransferEncoding::QuotedPrintable,
+            Encoding::Base64 => TransferEncoding::Base64,
+            Encoding::None => match self.content_transfer_encoding().map(str::trim) {
+                None => TransferEncoding::SevenBit,
+                Some(value) if value.eq_ignore_ascii_case("7bit") => TransferEncoding::SevenBit,
+                Some(value) if value.eq_ignore_ascii_case("8bit") => TransferEncoding::EightBit,
+                Some(value) if value.eq_ignore_ascii_case("binary") => TransferEncoding::Binary,
+                Some(value) if value.eq_ignore_ascii_case("quoted-printable") => {
+                    TransferEncoding::QuotedPrintable
+                }
+                Some(value) if value.eq_ignore_ascii_case("base64") => TransferEncoding::Base64,
+                Some(_) => TransferEncoding::Unknown,
+            },
+        };
+        let is_container = self.is_container()
+            || self.content_type().is_some_and(|ct| {
+                ct.ctype().eq_ignore_ascii_case("multipart")
+                    || (ct.ctype().eq_ignore_ascii_case("message")
+                        && ct.subtype().is_some_and(|subtype| {
+                            ["rfc822", "partial", "external-body"]
+                                .iter()
+                                .any(|st| subtype.eq_ignore_ascii_case(st))
+                        }))
+            });
+
+        EffectiveEncoding {
+            encoding,
+            is_invalid: is_container
+                && !matches!(
+                    encoding,
+                    TransferEncoding::SevenBit
+                        | TransferEncoding::EightBit
+                        | TransferEncoding::Binary
+                ),
+        }
+    }
+
+    /// Returns `true` when the part has more than one
+    /// `Content-Transfer-Encoding` header field, which RFC 2045 does not
+    /// allow even when their values are identical. The encoding used to
+    /// decode the part is selected by the
+    /// [`TransferEncodingPolicy`](crate::TransferEncodingPolicy) of the parser.
+    pub fn has_duplicate_transfer_encoding(&self) -> bool {
+        self.headers
+            .iter()
+            .filter(|header| header.name == HeaderName::ContentTransferEncoding)
+            .nth(1)
+            .is_some()
+    }
+
     /// Returns `true` when the body part is empty
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -762,6 +1001,50 @@ impl<'x> MimeHeaders<'x> for MessagePart<'x> {
     }
 }
 
+impl<'x> MimeHeaders<'x> for PartHeaders<'x> {
+    fn content_description(&self) -> Option<&str> {
+        self.headers
+            .header_value(&HeaderName::ContentDescription)
+            .and_then(|header| header.as_text())
+    }
+
+    fn content_disposition(&self) -> Option<&ContentType<'x>> {
+        self.headers
+            .header_value(&HeaderName::ContentDisposition)
+            .and_then(|header| header.as_content_type())
+    }
+
+    fn content_id(&self) -> Option<&str> {
+        self.headers
+            .header_value(&HeaderName::ContentId)
+            .and_then(|header| header.as_text())
+    }
+
+    fn content_transfer_encoding(&self) -> Option<&str> {
+        self.headers
+            .header_value(&HeaderName::ContentTransferEncoding)
+            .and_then(|header| header.as_text())
+    }
+
+    fn content_type(&self) -> Option<&ContentType<'x>> {
+        self.headers
+            .header_value(&HeaderName::ContentType)
+            .and_then(|header| header.as_content_type())
+    }
+
+    fn content_language(&self) -> &HeaderValue<'x> {
+        self.headers
+            .header_value(&HeaderName::ContentLanguage)
+            .unwrap_or(&HeaderValue::Empty)
+    }
+
+    fn content_location(&self) -> Option<&str> {
+        self.headers
+            .header_value(&HeaderName::ContentLocation)
+            .and_then(|header| header.as_text())
+    }
+}
+
 /// An RFC2047 Content-Type or RFC2183 Content-Disposition MIME header field.
 impl<'x> ContentType<'x> {
     /// Returns the type
@@ -774,37 +1057,98 @@ impl<'x> ContentType<'x> {
         self.c_subtype.as_ref()?.as_ref().into()
     }
 
-    /// Returns an attribute by name
+    /// Returns the sub-type, or `default` if there is none
+    pub fn subtype_or<'y>(&'y self, default: &'y str) -> &'y str {
+        self.c_subtype.as_deref().unwrap_or(default)
+    }
+
+    /// Returns the type and sub-type separated by a slash, as in `text/plain`,
+    /// or only the type when there is no sub-type, as in `inline`
+    pub fn full_type(&self) -> Cow<'_, str> {
+        match &self.c_subtype {
+            Some(subtype) => format!("{}/{}", self.c_type, subtype).into(),
+            None => self.c_type.as_ref().into(),
+        }
+    }
+
+    /// Returns an attribute by name, compared case-insensitively
     pub fn attribute(&self, name: &str) -> Option<&str> {
         self.attributes
             .as_ref()?
             .iter()
-            .find(|(key, _)| key == name)?
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))?
             .1
             .as_ref()
             .into()
     }
 
-    /// Removes an attribute by name
+    /// Removes an attribute by name, compared case-insensitively
     pub fn remove_attribute(&mut self, name: &str) -> Option<Cow<'x, str>> {
         let attributes = self.attributes.as_mut()?;
 
         attributes
             .iter()
-            .position(|(key, _)| key == name)
-            .map(|pos| attributes.swap_remove(pos).1)
+            .position(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|pos| attributes.remove(pos).1)
     }
 
-    /// Returns all attributes
+    /// Returns all attributes, in the order they were declared. Attributes
+    /// split into RFC 2231 continuations are merged and placed where their
+    /// first segment appeared.
     pub fn attributes(&self) -> Option<&[(Cow<'x, str>, Cow<'x, str>)]> {
         self.attributes.as_deref()
     }
 
-    /// Returns `true` when the provided attribute name is present
+    /// Returns `true` when the provided attribute name is present, compared
+    /// case-insensitively
     pub fn has_attribute(&self, name: &str) -> bool {
         self.attributes
             .as_ref()
-            .map_or(false, |attr| attr.iter().any(|(key, _)| key == name))
+            .is_some_and(|attr| attr.iter().any(|(key, _)| key.eq_ignore_ascii_case(name)))
+    }
+
+    /// Returns the `boundary` attribute
+    pub fn boundary(&self) -> Option<&str> {
+        self.attribute("boundary")
+    }
+
+    /// Returns the `charset` attribute
+    pub fn charset(&self) -> Option<&str> {
+        self.attribute("charset")
+    }
+
+    /// Returns ```true``` if the type is "multipart"
+    pub fn is_multipart(&self) -> bool {
+        self.c_type.eq_ignore_ascii_case("multipart")
+    }
+
+    /// Returns ```true``` if the type is "message"
+    pub fn is_message(&self) -> bool {
+        self.c_type.eq_ignore_ascii_case("message")
+    }
+
+    /// Returns the names of attributes that appear more than once with
+    /// different values, such as `boundary=a; boundary=b`. Repeated attributes
+    /// with identical values are collapsed while parsing and are not reported.
+    pub fn conflicting_attributes(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        if let Some(attributes) = &self.attributes {
+            for (pos, (name, value)) in attributes.iter().enumerate() {
+                if !names.contains(&name.as_ref())
+                    && attributes[pos + 1..]
+                        .iter()
+                        .any(|(n, v)| n == name && v != value)
+                {
+                    names.push(name.as_ref());
+                }
+            }
+        }
+        names
+    }
+
+    /// Returns the canonical name of the `charset` attribute, see [`canonical_charset_name`].
+    pub fn charset_canonical(&self) -> Option<&'static str> {
+        canonical_charset_name(self.attribute("charset")?)
     }
 
     /// Returns ```true``` if the Content-Disposition type is "attachment"
@@ -818,6 +1162,128 @@ impl<'x> ContentType<'x> {
     }
 }
 
+impl<'x> ContentTypeDetails<'x> {
+    /// Returns the value of an attribute along with the charset and language
+    /// declared using RFC 2231 extended notation, compared case-insensitively.
+    /// Returns `None` for attributes declared without extended notation.
+    pub fn attribute_details(&self, name: &str) -> Option<&ContentTypeAttribute<'x>> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, details)| details)
+    }
+
+    /// Returns the name of an attribute as written in the header field, before
+    /// being lowercased, or `None` if the attribute does not exist. The name
+    /// is compared case-insensitively.
+    pub fn attribute_original_name(&self, name: &str) -> Option<&str> {
+        self.original_names
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, original)| original.as_ref())
+    }
+
+    /// Returns the malformed tokens skipped and the attributes dropped or
+    /// truncated while parsing the header field, in the order they were found.
+    pub fn warnings(&self) -> &[ContentTypeWarning] {
+        &self.warnings
+    }
+
+    /// Returns `true` if attributes were dropped or truncated because they
+    /// exceeded the limits set with [`MessageParser::with_max_attribute_name_len`](crate::MessageParser::with_max_attribute_name_len),
+    /// [`MessageParser::with_max_attribute_value_len`](crate::MessageParser::with_max_attribute_value_len)
+    /// or [`MessageParser::with_max_attribute_continuations`](crate::MessageParser::with_max_attribute_continuations).
+    pub fn is_truncated(&self) -> bool {
+        self.warnings.iter().any(|warning| {
+            matches!(
+                warning.reason,
+                ContentTypeWarningReason::NameTooLong
+                    | ContentTypeWarningReason::ValueTooLong
+                    | ContentTypeWarningReason::TooManyContinuations
+            )
+        })
+    }
+
+    /// Returns an owned version of the details
+    pub fn into_owned(self) -> ContentTypeDetails<'static> {
+        ContentTypeDetails {
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(|(name, details)| (name.into_owned().into(), details.into_owned()))
+                .collect(),
+            original_names: self
+                .original_names
+                .into_iter()
+                .map(|(name, original)| (name.into_owned().into(), original.into_owned().into()))
+                .collect(),
+            warnings: self.warnings,
+        }
+    }
+}
+
+impl ContentTypeAttribute<'_> {
+    /// Returns an owned version of the attribute
+    pub fn into_owned(self) -> ContentTypeAttribute<'static> {
+        ContentTypeAttribute {
+            value: self.value.into_owned().into(),
+            charset: self.charset.map(|c| c.into_owned().into()),
+            language: self.language.map(|l| l.into_owned().into()),
+        }
+    }
+}
+
+impl<'x> From<ContentType<'x>> for AutoSubmitted<'x> {
+    fn from(value: ContentType<'x>) -> Self {
+        let attributes = value.attributes.unwrap_or_default();
+        if value.c_type.eq_ignore_ascii_case("no") {
+            AutoSubmitted::No
+        } else if value.c_type.eq_ignore_ascii_case("auto-generated") {
+            AutoSubmitted::AutoGenerated(attributes)
+        } else if value.c_type.eq_ignore_ascii_case("auto-replied") {
+            AutoSubmitted::AutoReplied(attributes)
+        } else {
+            AutoSubmitted::Other(value.c_type, attributes)
+        }
+    }
+}
+
+impl<'x> AutoSubmitted<'x> {
+    /// Returns `true` when the message was automatically generated or replied,
+    /// which means that no automatic responses should be sent back.
+    pub fn is_auto_submitted(&self) -> bool {
+        !matches!(self, AutoSubmitted::No)
+    }
+
+    /// Returns the Auto-Submitted keyword
+    pub fn keyword(&self) -> &str {
+        match self {
+            AutoSubmitted::No => "no",
+            AutoSubmitted::AutoGenerated(_) => "auto-generated",
+            AutoSubmitted::AutoReplied(_) => "auto-replied",
+            AutoSubmitted::Other(keyword, _) => keyword,
+        }
+    }
+
+    /// Returns all optional parameters
+    pub fn attributes(&self) -> &[(Cow<'x, str>, Cow<'x, str>)] {
+        match self {
+            AutoSubmitted::No => &[],
+            AutoSubmitted::AutoGenerated(attributes)
+            | AutoSubmitted::AutoReplied(attributes)
+            | AutoSubmitted::Other(_, attributes) => attributes,
+        }
+    }
+
+    /// Returns an optional parameter by name
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes()
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref())
+    }
+}
+
 /// A Received header
 impl<'x> Received<'x> {
     pub fn into_owned(self) -> Received<'static> {
@@ -1101,8 +1567,375 @@ impl Display for TlsVersion {
     }
 }
 
+/// Writes the header value, quoting attribute values that are empty or
+/// contain whitespace, control characters or tspecials. RFC 2231 charsets
+/// and languages are not written.
+impl Display for ContentType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.full_type())?;
+
+        for (name, value) in self.attributes().unwrap_or_default() {
+            write!(f, "; {name}=")?;
+            if !value.is_empty()
+                && value
+                    .bytes()
+                    .all(|ch| ch.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&ch))
+            {
+                f.write_str(value)?;
+            } else {
+                f.write_str("\"")?;
+                for ch in value.chars() {
+                    if matches!(ch, '"' | '\\') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{ch}")?;
+                }
+                f.write_str("\"")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Display for HeaderName<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{EffectiveEncoding, MessageParser, MimeHeaders, TransferEncoding};
+
+    #[test]
+    fn as_text_if_textual() {
+        let input = concat!(
+            "Content-Type: multipart/mixed; boundary=\"b\"\n",
+            "\n",
+            "--b\n",
+            "Content-Type: text/plain\n",
+            "\n",
+            "Body\n",
+            "--b\n",
+            "Content-Type: application/octet-stream\n",
+            "Content-Disposition: attachment; filename=\"report.csv\"\n",
+            "Content-Transfer-Encoding: base64\n",
+            "\n",
+            "bmFtZSx2YWx1ZQpjYWbpLDEK\n",
+            "--b\n",
+            "Content-Type: application/octet-stream\n",
+            "Content-Transfer-Encoding: base64\n",
+            "\n",
+            "aGVsbG8gd29ybGQK\n",
+            "--b\n",
+            "Content-Type: application/octet-stream\n",
+            "Content-Transfer-Encoding: base64\n",
+            "\n",
+            "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJ\n",
+            "--b\n",
+            "Content-Type: application/octet-stream; name=\"data.bin\"\n",
+            "Content-Transfer-Encoding: base64\n",
+            "\n",
+            "Y2Fm6Q==\n",
+            "--b\n",
+            "Content-Type: image/gif\n",
+            "Content-Transfer-Encoding: base64\n",
+            "\n",
+            "R0lGODlhAQABAAAAACw=\n",
+            "--b--\n"
+        );
+
+        let message = MessageParser::default().parse(input).unwrap();
+        assert_eq!(
+            message
+                .parts
+                .iter()
+                .skip(1)
+                .map(|p| p.as_text_if_textual())
+                .collect::<Vec<_>>(),
+            vec![
+                Some("Body".into()),
+                Some("name,value\ncafé,1\n".into()),
+                Some("hello world\n".into()),
+                None,
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_attributes() {
+        for (input, expected) in [
+            (
+                "Content-Type: multipart/mixed; boundary=a; boundary=b\n\n",
+                vec!["boundary"],
+            ),
+            (
+                "Content-Type: text/plain; charset=utf-8; charset=utf-8\n\n",
+                vec![],
+            ),
+            (
+                "Content-Type: text/plain; a=1; b=2; a=3; b=2; a=1\n\n",
+                vec!["a"],
+            ),
+        ] {
+            let message = MessageParser::default().parse(input).unwrap();
+            assert_eq!(
+                message.content_type().unwrap().conflicting_attributes(),
+                expected,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn part_is_container() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: text/plain\n\n",
+                "Hello\n",
+                "--a\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: nested\n\n",
+                "Nested body\n",
+                "--a\n",
+                "Content-Type: application/octet-stream\n\n",
+                "data\n",
+                "--a--\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            message
+                .parts
+                .iter()
+                .map(|part| (part.is_container(), part.is_leaf()))
+                .collect::<Vec<_>>(),
+            vec![(true, false), (false, true), (true, false), (false, true)]
+        );
+    }
+
+    #[test]
+    fn part_description() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: application/pdf\n",
+                "Content-Description: =?utf-8?q?Quarterly_r=C3=A9sum=C3=A9?=\n",
+                " of =?iso-8859-1?q?r=E9sultats?=\n\n",
+                "%PDF\n",
+                "--a\n",
+                "Content-Type: image/png\n",
+                "Content-Description: Company logo\n\n",
+                "png\n",
+                "--a\n",
+                "Content-Type: text/plain\n\n",
+                "text\n",
+                "--a--\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            message.parts[1].description(),
+            Some("Quarterly résumé of résultats")
+        );
+        assert_eq!(message.parts[2].description(), Some("Company logo"));
+        assert_eq!(message.parts[3].description(), None);
+    }
+
+    #[test]
+    fn attachment_name_precedence() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: application/pdf;\n",
+                " name=\"=?iso-8859-1?Q?Rechnung_M=E4rz.pdf?=\"\n",
+                "Content-Disposition: attachment;\n",
+                " filename=\"=?utf-8?Q?Invoice.pdf?=\";\n",
+                " filename*=utf-8''Factura%20ma%C3%B1ana.pdf\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "JVBERi0=\n",
+                "--b\n",
+                "Content-Type: application/pdf;\n",
+                " name=\"=?iso-8859-1?Q?Rechnung_M=E4rz.pdf?=\"\n",
+                "Content-Disposition: attachment\n\n",
+                "%PDF-\n",
+                "--b--\n",
+            ))
+            .unwrap();
+
+        let attachment = message.attachment(0).unwrap();
+        assert_eq!(attachment.attachment_name(), Some("Factura mañana.pdf"));
+        assert_eq!(
+            attachment.content_type().unwrap().attribute("name"),
+            Some("Rechnung März.pdf")
+        );
+        assert_eq!(
+            message.attachment(1).unwrap().attachment_name(),
+            Some("Rechnung März.pdf")
+        );
+    }
+
+    #[test]
+    fn effective_encoding() {
+        let input = concat!(
+            "Content-Type: multipart/mixed; boundary=\"mixed\"\n",
+            "Content-Transfer-Encoding: 8bit\n\n",
+            "--mixed\n",
+            "Content-Type: text/plain\n\n",
+            "Plain\n",
+            "--mixed\n",
+            "Content-Type: text/plain\n",
+            "Content-Transfer-Encoding: QUOTED-PRINTABLE\n\n",
+            "Caf=C3=A9\n",
+            "--mixed\n",
+            "Content-Type: application/octet-stream\n",
+            "Content-Transfer-Encoding: x-uuencode\n\n",
+            "begin 644 file\n",
+            "--mixed\n",
+            "Content-Type: multipart/alternative; boundary=\"alt\"\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "--alt\n",
+            "Content-Type: text/plain\n",
+            "Content-Transfer-Encoding: binary\n\n",
+            "Alternative\n",
+            "--alt--\n",
+            "--mixed\n",
+            "Content-Type: message/rfc822\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "U3ViamVjdDogRW5jb2RlZAoKRW5jb2RlZCBib2R5Cg==\n",
+            "--mixed\n",
+            "Content-Type: message/rfc822\n",
+            "Content-Transfer-Encoding: 7bit\n\n",
+            "Subject: Plain\n\n",
+            "Plain body\n",
+            "--mixed--\n",
+        );
+        let message = MessageParser::default().parse(input).unwrap();
+
+        assert_eq!(
+            message
+                .parts
+                .iter()
+                .map(|part| {
+                    let effective = part.effective_encoding();
+                    (effective.encoding, effective.is_invalid)
+                })
+                .collect::<Vec<_>>(),
+            [
+                (TransferEncoding::EightBit, false),
+                (TransferEncoding::SevenBit, false),
+                (TransferEncoding::QuotedPrintable, false),
+                (TransferEncoding::Unknown, false),
+                (TransferEncoding::Base64, true),
+                (TransferEncoding::Binary, false),
+                (TransferEncoding::Base64, true),
+                (TransferEncoding::SevenBit, false),
+            ]
+        );
+        assert_eq!(
+            message.parts[6].message().unwrap().subject(),
+            Some("Encoded")
+        );
+
+        // Nested parts do not inherit the encoding of their parent
+        let nested = message.parts[7].message().unwrap();
+        assert_eq!(
+            nested.parts[0].effective_encoding(),
+            EffectiveEncoding {
+                encoding: TransferEncoding::SevenBit,
+                is_invalid: false
+            }
+        );
+    }
+
+    #[test]
+    fn decode_to() {
+        let input = format!(
+            concat!(
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: text/plain; charset=iso-8859-1\n",
+                "Content-Transfer-Encoding: quoted-printable\n\n",
+                "Caf=E9 au lait=\n",
+                " avec sucre\n",
+                "--mixed\n",
+                "Content-Type: application/octet-stream\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=\n",
+                "--mixed\n",
+                "Content-Type: application/octet-stream\n",
+                "Content-Transfer-Encoding: quoted-printable\n\n",
+                "{}",
+                "trailing  \t\n",
+                "--mixed\n",
+                "Content-Type: application/octet-stream\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "{}",
+                "--mixed\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: Nested\n\n",
+                "Nested body\n",
+                "--mixed--\n",
+            ),
+            "soft =\nbreak=20 \t\nline  \n=E9=\n".repeat(1000),
+            "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=\n".repeat(1000)
+        );
+        let message = MessageParser::default().parse(&input).unwrap();
+        assert_eq!(message.parts.len(), 6);
+
+        for part in &message.parts {
+            let mut buf = Vec::new();
+            part.decode_to(&message.raw_message, &mut buf).unwrap();
+            assert_eq!(buf, part.contents());
+        }
+
+        let mut buf = Vec::new();
+        message.parts[2]
+            .decode_to(&message.raw_message, &mut buf)
+            .unwrap();
+        assert_eq!(buf, (0u8..32).collect::<Vec<_>>());
+
+        // Contents cut by the part size limit are written in full
+        let message = MessageParser::default()
+            .with_max_part_size(10)
+            .parse(&input)
+            .unwrap();
+        let mut buf = Vec::new();
+        message.parts[4]
+            .decode_to(&message.raw_message, &mut buf)
+            .unwrap();
+        assert_eq!(message.parts[4].len(), 10);
+        assert_eq!(buf.len(), 32_000);
+
+        let part = MessageParser::default().parse_part(&input, "1").unwrap();
+        let mut buf = Vec::new();
+        part.decode_to(input.as_bytes(), &mut buf).unwrap();
+        assert_eq!(buf, "Café au lait avec sucre".as_bytes());
+    }
+
+    #[test]
+    fn remove_attribute() {
+        let message = MessageParser::default()
+            .parse("Content-Type: text/plain; a=1; B=2; c=3; d=4\n\nBody\n")
+            .unwrap();
+        let mut content_type = message.parts[0].content_type().unwrap().clone();
+
+        assert_eq!(content_type.remove_attribute("b").as_deref(), Some("2"));
+        assert_eq!(content_type.remove_attribute("b"), None);
+        assert_eq!(
+            content_type.attributes().unwrap(),
+            [
+                ("a".into(), "1".into()),
+                ("c".into(), "3".into()),
+                ("d".into(), "4".into())
+            ]
+        );
+    }
+}
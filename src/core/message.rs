@@ -9,17 +9,28 @@
  * except according to those terms.
  */
 
-use std::{borrow::Cow, convert::TryInto};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+};
 
 use crate::{
-    decoders::html::{html_to_text, text_to_html},
+    decoders::{
+        base64::base64_decode,
+        hex::decode_hex,
+        html::{html_snippet, html_to_text, text_to_html},
+    },
+    mailbox::mbox::parse_from_line,
     parsers::{
-        fields::thread::thread_name,
-        preview::{preview_html, preview_text},
+        fields::{spam::parse_spam_score, thread::thread_name},
+        preview::{preview_html, preview_text, preview_words, unflow_text},
         MessageStream,
     },
-    Address, AttachmentIterator, BodyPartIterator, DateTime, GetHeader, Header, HeaderForm,
-    HeaderName, HeaderValue, Message, MessageParser, MessagePart, PartType, Received,
+    Addr, Address, AttachmentIterator, AutoSubmitted, BodyPartIterator, ChildCountViolation,
+    DateTime, GetHeader, Header, HeaderForm, HeaderName, HeaderPool, HeaderValue, Message,
+    MessageParser, MessagePart, MessagePartId, MimeHeaders, PartRole, PartType, PartWalker,
+    Priority, Received, SpamStatus, ThreadIndex,
 };
 
 impl<'x> Message<'x> {
@@ -51,6 +62,67 @@ impl<'x> Message<'x> {
             .and_then(|h| std::str::from_utf8(&self.raw_message[h.offset_start..h.offset_end]).ok())
     }
 
+    /// Returns the undecoded value of the first header matching the given name,
+    /// from right after the colon up to the line terminator. Folded lines are
+    /// included, the final line break is not.
+    pub fn get_header_raw(&self, header: impl Into<HeaderName<'x>>) -> Option<&[u8]> {
+        let header = header.into();
+        let header = self
+            .parts
+            .first()?
+            .headers
+            .iter()
+            .find(|h| h.name == header)?;
+        let value = self
+            .raw_message
+            .get(header.offset_start..header.offset_end)?;
+        Some(
+            value
+                .strip_suffix(b"\r\n")
+                .or_else(|| value.strip_suffix(b"\n"))
+                .unwrap_or(value),
+        )
+    }
+
+    /// Returns the headers of the message and of all nested `message/rfc822`
+    /// parts, at any depth, each with a path identifying where it was found.
+    ///
+    /// The path of a header is its lowercased name, prefixed with
+    /// `embedded[n].` for each nested message it is contained in, where `n` is
+    /// the position of the nested message among the nested messages of its
+    /// parent, in order of appearance. For example, `subject` is the subject of
+    /// the message and `embedded[0].embedded[1].from` is the sender of the second
+    /// message attached to the first attached message. Headers of MIME parts
+    /// other than messages are not included.
+    pub fn flatten_headers(&self) -> Vec<(String, &Header<'x>)> {
+        let mut headers = Vec::new();
+        self.flatten_headers_(String::new(), &mut headers);
+        headers
+    }
+
+    fn flatten_headers_<'y>(&'y self, prefix: String, headers: &mut Vec<(String, &'y Header<'x>)>) {
+        if let Some(part) = self.parts.first() {
+            for header in &part.headers {
+                headers.push((
+                    format!("{prefix}{}", header.name.as_str().to_ascii_lowercase()),
+                    header,
+                ));
+            }
+        }
+
+        for (pos, message) in self
+            .parts
+            .iter()
+            .filter_map(|part| match &part.body {
+                PartType::Message(message) => Some(message),
+                _ => None,
+            })
+            .enumerate()
+        {
+            message.flatten_headers_(format!("{prefix}embedded[{pos}]."), headers);
+        }
+    }
+
     // Parse a header as a specific type.
     pub fn header_as(
         &self,
@@ -155,6 +227,302 @@ impl<'x> Message<'x> {
             .and_then(|header| header.as_datetime())
     }
 
+    /// Returns the Delivery-Date header field, added by some MTAs when the
+    /// message is delivered to the final mailbox.
+    pub fn delivery_date(&self) -> Option<DateTime> {
+        self.header_datetime("Delivery-Date")
+    }
+
+    /// Returns the Auto-Submitted header field (RFC 3834)
+    pub fn auto_submitted(&self) -> Option<AutoSubmitted<'_>> {
+        let header = self.parts[0].headers.header("Auto-Submitted")?;
+        Some(
+            match &header.value {
+                HeaderValue::ContentType(content_type) => content_type.clone(),
+                _ => MessageStream::new(
+                    self.raw_message
+                        .get(header.offset_start..header.offset_end)?,
+                )
+                .parse_content_type()
+                .into_content_type()?,
+            }
+            .into(),
+        )
+    }
+
+    /// Returns `true` if the message looks like a non-delivery report. This is
+    /// the case for `multipart/report` messages with a `delivery-status` report
+    /// type or containing a `message/delivery-status` part, and for messages
+    /// sent by `mailer-daemon` or `postmaster` that either have a null
+    /// Return-Path or are marked as automatically submitted.
+    pub fn is_bounce(&self) -> bool {
+        if self.parts.iter().any(|part| {
+            part.content_type().is_some_and(|ct| {
+                (ct.ctype().eq_ignore_ascii_case("multipart")
+                    && ct
+                        .subtype()
+                        .is_some_and(|st| st.eq_ignore_ascii_case("report"))
+                    && ct
+                        .attribute("report-type")
+                        .is_some_and(|rt| rt.eq_ignore_ascii_case("delivery-status")))
+                    || (ct.ctype().eq_ignore_ascii_case("message")
+                        && ct
+                            .subtype()
+                            .is_some_and(|st| st.eq_ignore_ascii_case("delivery-status")))
+            })
+        }) {
+            return true;
+        }
+
+        self.from()
+            .and_then(|from| from.first())
+            .and_then(|addr| addr.address())
+            .and_then(|addr| addr.split_once('@'))
+            .is_some_and(|(local, _)| {
+                local.eq_ignore_ascii_case("mailer-daemon")
+                    || local.eq_ignore_ascii_case("postmaster")
+            })
+            && (self.has_null_return_path()
+                || self
+                    .auto_submitted()
+                    .is_some_and(|auto| auto.is_auto_submitted()))
+    }
+
+    /// Returns `true` if the message looks like an automatic reply, such as a
+    /// vacation notice. This is the case when the Auto-Submitted header field is
+    /// `auto-replied`, when Precedence is `auto_reply`, or when one of the
+    /// X-Autoreply or X-Autorespond header fields is present. Non-delivery
+    /// reports are not considered automatic replies.
+    pub fn is_auto_reply(&self) -> bool {
+        !self.is_bounce()
+            && (matches!(self.auto_submitted(), Some(AutoSubmitted::AutoReplied(_)))
+                || self
+                    .header_values("Precedence")
+                    .filter_map(|value| value.as_text())
+                    .any(|value| value.trim().eq_ignore_ascii_case("auto_reply"))
+                || self.header("X-Autoreply").is_some()
+                || self.header("X-Autorespond").is_some())
+    }
+
+    fn has_null_return_path(&self) -> bool {
+        self.parts[0]
+            .headers
+            .iter()
+            .filter(|h| h.name == HeaderName::ReturnPath)
+            .any(|h| {
+                self.raw_message
+                    .get(h.offset_start..h.offset_end)
+                    .is_some_and(|value| {
+                        value
+                            .iter()
+                            .filter(|ch| !ch.is_ascii_whitespace())
+                            .eq(b"<>".iter())
+                    })
+            })
+    }
+
+    /// Returns the Expires header field (RFC 4021)
+    pub fn expires(&self) -> Option<DateTime> {
+        self.header_datetime("Expires")
+    }
+
+    /// Returns the Reply-By header field (RFC 4021)
+    pub fn reply_by(&self) -> Option<DateTime> {
+        self.header_datetime("Reply-By")
+    }
+
+    // Returns a header as a date, parsing the raw value if needed
+    fn header_datetime(&self, name: &'static str) -> Option<DateTime> {
+        let header = self.parts[0].headers.header(name)?;
+        match &header.value {
+            HeaderValue::DateTime(datetime) => Some(*datetime),
+            _ => MessageStream::new(
+                self.raw_message
+                    .get(header.offset_start..header.offset_end)?,
+            )
+            .parse_date()
+            .into_datetime(),
+        }
+    }
+
+    /// Returns the first Organization header field, unfolded and with
+    /// RFC 2047 encoded-words decoded. Other occurrences can be obtained
+    /// with [`Message::header_values`].
+    pub fn organization(&self) -> Option<Cow<'_, str>> {
+        self.header_unstructured("Organization")
+    }
+
+    /// Returns the message priority from the first recognized value of the
+    /// following header fields, in order of precedence:
+    ///
+    /// - `X-Priority`: `1` and `2` are high, `3` is normal, `4` and `5` are low.
+    /// - `X-MSMail-Priority`: `High`, `Normal` or `Low`.
+    /// - `Importance`: `high`, `normal` or `low`.
+    /// - `Priority`: `urgent`, `normal` or `non-urgent`.
+    pub fn priority(&self) -> Option<Priority> {
+        self.header_priority("X-Priority", |value| {
+            match value.bytes().next()? {
+                b'1' | b'2' => Priority::High,
+                b'3' => Priority::Normal,
+                b'4' | b'5' => Priority::Low,
+                _ => return None,
+            }
+            .into()
+        })
+        .or_else(|| self.header_priority("X-MSMail-Priority", priority_keyword))
+        .or_else(|| self.header_priority("Importance", priority_keyword))
+        .or_else(|| self.header_priority("Priority", priority_keyword))
+    }
+
+    fn header_priority(
+        &self,
+        name: &'static str,
+        parse: impl Fn(&str) -> Option<Priority>,
+    ) -> Option<Priority> {
+        let name = HeaderName::from(name);
+        self.parts[0]
+            .headers
+            .iter()
+            .filter(|h| h.name == name)
+            .find_map(|h| parse(h.value.as_text()?.trim()))
+    }
+
+    /// Returns the first Thread-Topic header field used by Microsoft Exchange,
+    /// with RFC 2047 encoded-words decoded.
+    pub fn thread_topic(&self) -> Option<Cow<'_, str>> {
+        self.header_unstructured("Thread-Topic")
+    }
+
+    /// Returns the first Thread-Index header field used by Microsoft Exchange.
+    pub fn thread_index(&self) -> Option<ThreadIndex> {
+        let name = HeaderName::from("Thread-Index");
+        let header = self.parts[0].headers.iter().find(|h| h.name == name)?;
+        ThreadIndex::parse(
+            self.raw_message
+                .get(header.offset_start..header.offset_end)?,
+        )
+    }
+
+    /// Returns the first X-Spam-Status header field added by SpamAssassin,
+    /// see [`SpamStatus::parse`].
+    pub fn spam_status(&self) -> Option<SpamStatus> {
+        SpamStatus::parse(&self.header_unstructured("X-Spam-Status")?)
+    }
+
+    /// Returns the score of the first X-Spam-Score header field.
+    pub fn spam_score(&self) -> Option<f64> {
+        parse_spam_score(&self.header_unstructured("X-Spam-Score")?)
+    }
+
+    fn header_unstructured(&self, name: &'static str) -> Option<Cow<'_, str>> {
+        let name = HeaderName::from(name);
+        let header = self.parts[0].headers.iter().find(|h| h.name == name)?;
+        // Other headers are parsed as raw text by default, decode them here
+        MessageStream::new(
+            self.raw_message
+                .get(header.offset_start..header.offset_end)?,
+        )
+        .parse_unstructured()
+        .into_text()
+    }
+
+    /// Returns whether SPF passed according to the topmost Authentication-Results
+    /// header field reporting an `spf` result, falling back to the first
+    /// Received-SPF header field. Returns `None` if neither is present.
+    pub fn spf_passed(&self) -> Option<bool> {
+        self.auth_result_passed("spf").or_else(|| {
+            let name = HeaderName::from("Received-SPF");
+            self.parts[0]
+                .headers
+                .iter()
+                .filter(|h| h.name == name)
+                .find_map(|h| {
+                    let value = strip_comments(&String::from_utf8_lossy(
+                        self.raw_message.get(h.offset_start..h.offset_end)?,
+                    ));
+                    Some(
+                        value
+                            .split_whitespace()
+                            .next()?
+                            .eq_ignore_ascii_case("pass"),
+                    )
+                })
+        })
+    }
+
+    /// Returns whether DKIM passed according to the topmost Authentication-Results
+    /// header field reporting a `dkim` result, which is the case when at least
+    /// one signature passed. Returns `None` if no such header field is present.
+    pub fn dkim_passed(&self) -> Option<bool> {
+        self.auth_result_passed("dkim")
+    }
+
+    /// Returns whether DMARC passed according to the topmost Authentication-Results
+    /// header field reporting a `dmarc` result. Returns `None` if no such header
+    /// field is present.
+    pub fn dmarc_passed(&self) -> Option<bool> {
+        self.auth_result_passed("dmarc")
+    }
+
+    fn auth_result_passed(&self, method: &str) -> Option<bool> {
+        let name = HeaderName::from("Authentication-Results");
+        self.parts[0]
+            .headers
+            .iter()
+            .filter(|h| h.name == name)
+            .find_map(|h| {
+                let value = strip_comments(&String::from_utf8_lossy(
+                    self.raw_message.get(h.offset_start..h.offset_end)?,
+                ));
+                // The first element is the authserv-id
+                let mut results = value
+                    .split(';')
+                    .skip(1)
+                    .filter_map(|result| {
+                        let (result_method, result) = result.split_once('=')?;
+                        let result_method = result_method.trim();
+                        (result_method.eq_ignore_ascii_case(method)
+                            || result_method
+                                .split_once('/')
+                                .is_some_and(|(m, _)| m.trim().eq_ignore_ascii_case(method)))
+                        .then(|| result.split_whitespace().next())?
+                    })
+                    .peekable();
+                results.peek()?;
+                Some(results.any(|result| result.eq_ignore_ascii_case("pass")))
+            })
+    }
+
+    /// Returns the image carried by the first Face header field, which is a
+    /// base64 encoded PNG image of 48x48 pixels. Returns `None` if the header
+    /// is missing or does not contain a PNG image.
+    pub fn face(&self) -> Option<Vec<u8>> {
+        base64_decode(&self.header_unfolded("Face")?)
+            .filter(|image| image.starts_with(b"\x89PNG\r\n\x1a\n"))
+    }
+
+    /// Returns the first X-Face header field with all whitespace removed. The
+    /// value is a 48x48 monochrome bitmap compressed with the `compface`
+    /// algorithm, which is not decoded by this crate.
+    pub fn x_face(&self) -> Option<String> {
+        self.header_unfolded("X-Face")
+            .and_then(|value| String::from_utf8(value).ok())
+    }
+
+    // Returns the raw value of the first header field named `name`, without whitespace
+    fn header_unfolded(&self, name: &'static str) -> Option<Vec<u8>> {
+        let name = HeaderName::from(name);
+        let header = self.parts[0].headers.iter().find(|h| h.name == name)?;
+        let value = self
+            .raw_message
+            .get(header.offset_start..header.offset_end)?
+            .iter()
+            .filter(|ch| !ch.is_ascii_whitespace())
+            .copied()
+            .collect::<Vec<_>>();
+        (!value.is_empty()).then_some(value)
+    }
+
     /// Returns the From header field
     pub fn from(&self) -> Option<&Address<'x>> {
         self.parts[0]
@@ -275,6 +643,98 @@ impl<'x> Message<'x> {
             .and_then(|a| a.as_address())
     }
 
+    /// Returns the addresses found in the From, Sender, Reply-To, To and Cc
+    /// header fields, without duplicates and in order of appearance. Addresses
+    /// are compared case-insensitively.
+    ///
+    /// When `scan_bodies` is `true`, the text bodies are also scanned for
+    /// addresses. The scan is conservative and only matches plain
+    /// `local@domain.tld` addresses.
+    pub fn all_addresses(&self, scan_bodies: bool) -> Vec<Cow<'_, str>> {
+        fn add<'y>(addresses: &mut Vec<Cow<'y, str>>, addr: Cow<'y, str>) {
+            if !addresses.iter().any(|a| a.eq_ignore_ascii_case(&addr)) {
+                addresses.push(addr);
+            }
+        }
+
+        let mut addresses = Vec::new();
+
+        for header in [
+            HeaderName::From,
+            HeaderName::Sender,
+            HeaderName::ReplyTo,
+            HeaderName::To,
+            HeaderName::Cc,
+        ] {
+            for value in self.header_values(header) {
+                if let Some(address) = value.as_address() {
+                    for addr in address.iter() {
+                        if let Some(addr) = addr.address() {
+                            add(&mut addresses, addr.into());
+                        }
+                    }
+                }
+            }
+        }
+
+        if scan_bodies {
+            for part in self.text_body.iter().filter_map(|&pos| self.parts.get(pos)) {
+                let text = match &part.body {
+                    PartType::Text(text) => text.as_ref().into(),
+                    PartType::Html(html) => Cow::from(html_to_text(html.as_ref())),
+                    _ => continue,
+                };
+                for addr in scan_addresses(&text) {
+                    add(&mut addresses, addr.to_string().into());
+                }
+            }
+        }
+
+        addresses
+    }
+
+    /// Returns the number of parts of each content type, keyed by lowercase
+    /// `type/subtype` and including the parts of nested messages. Parts
+    /// without a Content-Type header field are counted as `text/plain`.
+    /// Multipart containers and `message/rfc822` parts are only counted when
+    /// `include_containers` is `true`.
+    pub fn content_type_histogram(&self, include_containers: bool) -> HashMap<String, usize> {
+        let mut histogram = HashMap::new();
+        self.content_type_histogram_(include_containers, &mut histogram);
+        histogram
+    }
+
+    fn content_type_histogram_(
+        &self,
+        include_containers: bool,
+        histogram: &mut HashMap<String, usize>,
+    ) {
+        for part in &self.parts {
+            let is_container = match &part.body {
+                PartType::Multipart(_) => true,
+                PartType::Message(message) => {
+                    message.content_type_histogram_(include_containers, histogram);
+                    true
+                }
+                _ => false,
+            };
+            if is_container && !include_containers {
+                continue;
+            }
+
+            let content_type = match (part.content_type(), &part.body) {
+                (Some(ct), _) => match ct.subtype() {
+                    Some(subtype) => format!("{}/{subtype}", ct.ctype()),
+                    None => ct.ctype().to_string(),
+                }
+                .to_ascii_lowercase(),
+                (None, PartType::Message(_)) => "message/rfc822".to_string(),
+                (None, _) => "text/plain".to_string(),
+            };
+            *histogram.entry(content_type).or_insert(0) += 1;
+        }
+    }
+
     /// Returns the Resent-BCC header field
     pub fn resent_bcc(&self) -> Option<&Address<'x>> {
         self.parts[0]
@@ -299,6 +759,28 @@ impl<'x> Message<'x> {
             .unwrap_or(&HeaderValue::Empty)
     }
 
+    /// Returns the sender address of the mbox `From ` envelope line preceding
+    /// the headers, which is skipped when parsing messages extracted from an
+    /// mbox file.
+    pub fn envelope_from(&self) -> Option<&str> {
+        parse_from_line(self.envelope_line()?).map(|(from, _)| from)
+    }
+
+    /// Returns the date of the mbox `From ` envelope line preceding the
+    /// headers.
+    pub fn envelope_date(&self) -> Option<DateTime> {
+        parse_from_line(self.envelope_line()?)
+            .map(|(_, date)| date)
+            .filter(|date| date.is_valid())
+    }
+
+    fn envelope_line(&self) -> Option<&str> {
+        let line = self.raw_message.get(..self.parts.first()?.offset_header)?;
+        std::str::from_utf8(line)
+            .ok()
+            .filter(|line| line.starts_with("From "))
+    }
+
     /// Returns the Resent-From header field
     pub fn resent_from(&self) -> Option<&Address<'x>> {
         self.parts[0]
@@ -374,6 +856,47 @@ impl<'x> Message<'x> {
         thread_name(self.subject()?).into()
     }
 
+    /// Returns a one-line summary of the message for terminal listings:
+    ///
+    /// ```text
+    /// 2024-03-01 09:15  Jane Doe                  Quarterly report (2 attachments)
+    /// ```
+    ///
+    /// The date is shown in the time zone of the `Date` header field. The
+    /// sender is the display name of the first `From` address, or its address,
+    /// padded or truncated to 24 characters. The subject is the base subject
+    /// returned by [`Message::thread_name`] truncated to 60 characters.
+    /// Truncated fields end with `…`, missing fields are shown as `-` and the
+    /// attachment count is omitted when the message has no attachments.
+    pub fn summary(&self) -> String {
+        let date = self.date().map_or_else(
+            || format!("{:<16}", "-"),
+            |d| {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}",
+                    d.year, d.month, d.day, d.hour, d.minute
+                )
+            },
+        );
+        let from = self
+            .from()
+            .and_then(|from| from.first())
+            .and_then(|addr| addr.name().or_else(|| addr.address()))
+            .map_or_else(|| "-".to_string(), |from| summary_field(from, 24));
+        let subject = self
+            .thread_name()
+            .filter(|subject| !subject.trim().is_empty())
+            .map_or_else(|| "-".to_string(), |subject| summary_field(subject, 60));
+
+        let mut summary = format!("{date}  {from:<24}  {subject}");
+        match self.attachment_count() {
+            0 => (),
+            1 => summary.push_str(" (1 attachment)"),
+            count => summary.push_str(&format!(" ({count} attachments)")),
+        }
+        summary
+    }
+
     /// Returns the To header field
     pub fn to(&self) -> Option<&Address<'x>> {
         self.parts[0]
@@ -382,6 +905,33 @@ impl<'x> Message<'x> {
             .and_then(|a| a.as_address())
     }
 
+    /// Returns the recipients in the To, Cc and Bcc header fields without
+    /// duplicates, in order of first appearance. Addresses are compared
+    /// case-insensitively and entries without an address are skipped. The
+    /// [`Message::to`], [`Message::cc`] and [`Message::bcc`] accessors return
+    /// every address as it appears in the message, including duplicates.
+    pub fn unique_recipients(&self) -> Vec<&Addr<'x>> {
+        let mut recipients: Vec<&Addr<'x>> = Vec::new();
+
+        for addr in [self.to(), self.cc(), self.bcc()]
+            .into_iter()
+            .flatten()
+            .flat_map(|address| address.iter())
+        {
+            if let Some(address) = addr.address() {
+                if !recipients.iter().any(|recipient| {
+                    recipient
+                        .address()
+                        .is_some_and(|r| r.eq_ignore_ascii_case(address))
+                }) {
+                    recipients.push(addr);
+                }
+            }
+        }
+
+        recipients
+    }
+
     /// Returns a preview of the message body
     pub fn body_preview(&self, preview_len: usize) -> Option<Cow<'x, str>> {
         if !self.text_body.is_empty() {
@@ -393,6 +943,41 @@ impl<'x> Message<'x> {
         }
     }
 
+    /// Returns a plain text preview of the message body of at most `max_chars`
+    /// characters, as displayed by mailbox list views. The first text body is
+    /// used, converting it from HTML when the message has no text alternative.
+    /// `format=flowed` text is reflowed, whitespace is collapsed and the text
+    /// is truncated at a word boundary, ending with `...` when shortened.
+    pub fn preview(&self, max_chars: usize) -> String {
+        let Some(part) = self
+            .text_body
+            .first()
+            .or_else(|| self.html_body.first())
+            .and_then(|part_id| self.parts.get(*part_id))
+        else {
+            return String::new();
+        };
+
+        match &part.body {
+            PartType::Text(text) => {
+                let content_type = part.content_type();
+                if content_type
+                    .and_then(|ct| ct.attribute("format"))
+                    .is_some_and(|format| format.eq_ignore_ascii_case("flowed"))
+                {
+                    let delsp = content_type
+                        .and_then(|ct| ct.attribute("delsp"))
+                        .is_some_and(|delsp| delsp.eq_ignore_ascii_case("yes"));
+                    preview_words(&unflow_text(text, delsp), max_chars)
+                } else {
+                    preview_words(text, max_chars)
+                }
+            }
+            PartType::Html(html) => preview_words(&html_to_text(html), max_chars),
+            _ => String::new(),
+        }
+    }
+
     /// Returns a message body part as text/plain
     pub fn body_html(&'x self, pos: usize) -> Option<Cow<'x, str>> {
         let part = self.parts.get(*self.html_body.get(pos)?)?;
@@ -403,6 +988,17 @@ impl<'x> Message<'x> {
         }
     }
 
+    /// Returns the first `max_chars` visible characters of an HTML body part
+    /// as plain text, without converting the entire document.
+    pub fn body_html_snippet(&self, pos: usize, max_chars: usize) -> Option<String> {
+        let part = self.parts.get(*self.html_body.get(pos)?)?;
+        match &part.body {
+            PartType::Html(html) => Some(html_snippet(html.as_ref(), max_chars)),
+            PartType::Text(text) => Some(text.chars().take(max_chars).collect()),
+            _ => None,
+        }
+    }
+
     /// Returns a message body part as text/plain
     pub fn body_text(&'x self, pos: usize) -> Option<Cow<'x, str>> {
         let part = self.parts.get(*self.text_body.get(pos)?)?;
@@ -418,6 +1014,81 @@ impl<'x> Message<'x> {
         self.parts.get(pos)
     }
 
+    /// Returns the part addressed by an IMAP body part specifier such as
+    /// `1`, `1.2` or `2.1.3`, following the numbering rules of
+    /// [RFC 3501 (Section 6.4.5)](https://datatracker.ietf.org/doc/html/rfc3501#section-6.4.5).
+    ///
+    /// The body of a non-multipart message is part `1`. The numbering of a
+    /// `message/rfc822` part continues into the encapsulated message, so the
+    /// returned part may belong to a nested [`Message`].
+    pub fn part_by_imap_path(&self, path: &str) -> Option<&MessagePart<'x>> {
+        let mut message = self;
+        let mut part: Option<&MessagePart<'x>> = None;
+
+        for num in path.split('.') {
+            let num = num.parse::<usize>().ok()?.checked_sub(1)?;
+
+            if let Some(MessagePart {
+                body: PartType::Message(nested),
+                ..
+            }) = part
+            {
+                message = nested;
+                part = None;
+            }
+
+            part = Some(match part {
+                Some(part) => message.parts.get(*part.sub_parts()?.get(num)?)?,
+                None => {
+                    let root = message.parts.first()?;
+                    match &root.body {
+                        PartType::Multipart(sub_parts) => {
+                            message.parts.get(*sub_parts.get(num)?)?
+                        }
+                        _ if num == 0 => root,
+                        _ => return None,
+                    }
+                }
+            });
+        }
+
+        part
+    }
+
+    /// Returns the IMAP body part specifier of the part at `index`, as
+    /// understood by [`Message::part_by_imap_path`]. The root part of a
+    /// multipart message has no part number and is returned as an empty
+    /// string. Only parts of this message are addressed, parts of
+    /// encapsulated messages have their own indexes.
+    pub fn imap_path_of(&self, index: MessagePartId) -> Option<String> {
+        let root = self.parts.first()?;
+        self.parts.get(index)?;
+
+        if index == 0 {
+            return Some(if root.is_multipart() {
+                String::new()
+            } else {
+                "1".to_string()
+            });
+        }
+
+        let mut path = Vec::new();
+        let mut current = index;
+        while current != 0 {
+            let (parent, pos) = self.parts.iter().enumerate().find_map(|(parent, part)| {
+                part.sub_parts()?
+                    .iter()
+                    .position(|&sub_part| sub_part == current)
+                    .map(|pos| (parent, pos))
+            })?;
+            path.push((pos + 1).to_string());
+            current = parent;
+        }
+        path.reverse();
+
+        Some(path.join("."))
+    }
+
     /// Returns an inline HTML body part by position
     pub fn html_part(&self, pos: usize) -> Option<&MessagePart<'x>> {
         self.parts.get(*self.html_body.get(pos)?)
@@ -428,7 +1099,7 @@ impl<'x> Message<'x> {
         self.parts.get(*self.text_body.get(pos)?)
     }
 
-    /// Returns an attacment by position
+    /// Returns an attacment by position, see [`Message::attachments`]
     pub fn attachment(&self, pos: usize) -> Option<&MessagePart<'x>> {
         self.parts.get(*self.attachments.get(pos)?)
     }
@@ -443,7 +1114,7 @@ impl<'x> Message<'x> {
         self.html_body.len()
     }
 
-    /// Returns the number of attachments
+    /// Returns the number of attachments, see [`Message::attachments`]
     pub fn attachment_count(&self) -> usize {
         self.attachments.len()
     }
@@ -458,19 +1129,189 @@ impl<'x> Message<'x> {
         BodyPartIterator::new(self, &self.html_body)
     }
 
-    /// Returns an Interator over the attachments
+    /// Returns an Interator over the attachments, that is, the parts that are
+    /// not part of the message body, excluding the inline parts returned by
+    /// [`Message::inline_parts`].
     pub fn attachments(&'x self) -> AttachmentIterator<'x> {
         AttachmentIterator::new(self)
     }
 
+    /// Returns an Iterator over the inline parts, that is, the parts that are
+    /// not part of the message body but are displayed within it. These parts
+    /// have a Content-ID and either an `inline` Content-Disposition or a
+    /// reference from an HTML body part by a `cid:` URL, such as the images
+    /// of a `multipart/related` message. Their positions are stored in
+    /// [`Message::inline_parts`](struct.Message.html#structfield.inline_parts).
+    pub fn inline_parts(&self) -> impl Iterator<Item = &MessagePart<'x>> {
+        self.inline_parts
+            .iter()
+            .filter_map(|&part_id| self.parts.get(part_id))
+    }
+
+    /// Returns an Iterator over all the parts of the message in document
+    /// order, paired with their nesting depth. The root part has a depth of
+    /// `0`, and the parts of `message/rfc822` attachments follow the
+    /// attachment itself, one level deeper.
+    pub fn walk_parts(&'x self) -> PartWalker<'x> {
+        PartWalker::new(self)
+    }
+
+    /// Returns the multipart parts of the message whose number of children
+    /// does not match the requirements of their subtype:
+    ///
+    /// * `multipart/signed` and `multipart/encrypted` (RFC 1847): exactly 2.
+    /// * `multipart/alternative` (RFC 2046): at least 2.
+    /// * `multipart/report` (RFC 6522): 2 or 3.
+    /// * Any other subtype (RFC 2046): at least 1.
+    ///
+    /// Parts of nested messages are not included.
+    pub fn child_count_violations(&self) -> Vec<ChildCountViolation> {
+        self.parts
+            .iter()
+            .enumerate()
+            .filter_map(|(part_id, part)| {
+                let PartType::Multipart(children) = &part.body else {
+                    return None;
+                };
+                let subtype = part
+                    .content_type()
+                    .and_then(|ct| ct.subtype())
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                let (min_children, max_children) = match subtype.as_str() {
+                    "signed" | "encrypted" => (2, Some(2)),
+                    "alternative" => (2, None),
+                    "report" => (2, Some(3)),
+                    _ => (1, None),
+                };
+                (children.len() < min_children
+                    || max_children.is_some_and(|max| children.len() > max))
+                .then_some(ChildCountViolation {
+                    part_id,
+                    children: children.len(),
+                    min_children,
+                    max_children,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the positions of the inline image parts of the message, which
+    /// are the `image/*` parts among the inline parts returned by
+    /// [`Message::inline_parts`]. Parts of nested messages are not included.
+    pub fn inline_images(&self) -> Vec<MessagePartId> {
+        self.inline_parts
+            .iter()
+            .copied()
+            .filter(|&part_id| {
+                self.parts.get(part_id).is_some_and(|part| {
+                    part.content_type()
+                        .is_some_and(|ct| ct.ctype().eq_ignore_ascii_case("image"))
+                })
+            })
+            .collect()
+    }
+
+    // Moves the inline parts out of the attachment list, once all the HTML
+    // parts that may reference them have been parsed
+    pub(crate) fn split_inline_parts(&mut self) {
+        let cids = self
+            .parts
+            .iter()
+            .filter_map(|part| match &part.body {
+                PartType::Html(html) => Some(html.as_ref()),
+                _ => None,
+            })
+            .flat_map(scan_cids)
+            .map(|cid| content_id_key(&cid).into_owned())
+            .collect::<HashSet<_>>();
+
+        let parts = &self.parts;
+        let inline_parts = &mut self.inline_parts;
+        self.attachments.retain(|&part_id| {
+            if parts
+                .get(part_id)
+                .is_some_and(|part| part.is_inline_part(&cids))
+            {
+                inline_parts.push(part_id);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Returns the part whose Content-ID matches `cid`, which may be enclosed
+    /// in angle brackets or given as a `cid:` URL (RFC 2392). The domain is
+    /// compared case-insensitively. Parts of nested messages are not searched.
+    pub fn part_by_content_id(&self, cid: &str) -> Option<&MessagePart<'x>> {
+        let cid = normalize_cid(cid)?;
+
+        self.parts.iter().find(|part| {
+            part.content_id()
+                .is_some_and(|id| content_id_matches(id, &cid))
+        })
+    }
+
+    /// Returns the decoded contents of the part whose Content-ID matches `cid`,
+    /// see [`Message::part_by_content_id`].
+    pub fn resolve_cid(&self, cid: &str) -> Option<&[u8]> {
+        self.part_by_content_id(cid).map(|part| part.contents())
+    }
+
+    /// Returns `true` if the message contains inline images, see [`Message::inline_images`].
+    pub fn has_inline_images(&self) -> bool {
+        !self.inline_images().is_empty()
+    }
+
+    /// Returns the parts of the message in the order a mail client should
+    /// present them, along with their role. The HTML body parts come first,
+    /// which for `multipart/alternative` contain only the preferred
+    /// alternative and fall back to plain text bodies when there is no HTML.
+    /// They are followed by the remaining inline images, see
+    /// [`Message::inline_images`], and by the attachments. Each part is
+    /// returned at most once.
+    pub fn render_order(&self) -> Vec<(MessagePartId, PartRole)> {
+        let mut parts: Vec<(MessagePartId, PartRole)> = Vec::new();
+
+        for &part_id in &self.html_body {
+            let role = if self.parts.get(part_id).is_some_and(|part| {
+                part.content_type()
+                    .is_some_and(|ct| ct.ctype().eq_ignore_ascii_case("image"))
+            }) {
+                PartRole::InlineImage
+            } else {
+                PartRole::Body
+            };
+            parts.push((part_id, role));
+        }
+
+        for part_id in self.inline_images() {
+            if !parts.iter().any(|(id, _)| *id == part_id) {
+                parts.push((part_id, PartRole::InlineImage));
+            }
+        }
+
+        for &part_id in &self.attachments {
+            if !parts.iter().any(|(id, _)| *id == part_id) {
+                parts.push((part_id, PartRole::Attachment));
+            }
+        }
+
+        parts
+    }
+
     /// Returns an owned version of the message
     pub fn into_owned(self) -> Message<'static> {
         Message {
             html_body: self.html_body,
             text_body: self.text_body,
             attachments: self.attachments,
+            inline_parts: self.inline_parts,
             parts: self.parts.into_iter().map(|p| p.into_owned()).collect(),
             raw_message: self.raw_message.into_owned().into(),
+            is_truncated: self.is_truncated,
+            header_pool: HeaderPool::default(),
         }
     }
 }
@@ -482,3 +1323,1462 @@ impl<'x> TryInto<Message<'x>> for &'x [u8] {
         MessageParser::default().parse(self).ok_or(())
     }
 }
+
+fn scan_addresses(text: &str) -> impl Iterator<Item = &str> {
+    let bytes = text.as_bytes();
+    text.match_indices('@').filter_map(move |(pos, _)| {
+        let is_local = |ch: u8| ch.is_ascii_alphanumeric() || b".!#$%&'*+/=?^_`{|}~-".contains(&ch);
+        let is_domain = |ch: u8| ch.is_ascii_alphanumeric() || ch == b'.' || ch == b'-';
+
+        let mut start = pos;
+        while start > 0 && is_local(bytes[start - 1]) {
+            start -= 1;
+        }
+        while start < pos && bytes[start] == b'.' {
+            start += 1;
+        }
+        let mut end = pos + 1;
+        while end < bytes.len() && is_domain(bytes[end]) {
+            end += 1;
+        }
+        while end > pos + 1 && matches!(bytes[end - 1], b'.' | b'-') {
+            end -= 1;
+        }
+
+        let local = &text[start..pos];
+        let domain = &text[pos + 1..end];
+        let mut labels = domain.split('.');
+        let tld = labels.next_back()?;
+        (!local.is_empty()
+            && !local.ends_with('.')
+            && domain.contains('.')
+            && domain
+                .split('.')
+                .all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+            && tld.len() >= 2
+            && tld.bytes().all(|ch| ch.is_ascii_alphabetic()))
+        .then(|| &text[start..end])
+    })
+}
+
+// Returns the Content-ID referenced by `cid`, which may be enclosed in angle
+// brackets or given as a `cid:` URL (RFC 2392)
+fn normalize_cid(cid: &str) -> Option<String> {
+    let cid = cid.trim();
+    let cid = match cid.get(..4) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("cid:") => {
+            match decode_hex(&cid.as_bytes()[4..]) {
+                (true, cid) => String::from_utf8(cid).ok()?,
+                _ => return None,
+            }
+        }
+        _ => cid.to_string(),
+    };
+
+    match cid
+        .strip_prefix('<')
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        Some(inner) => Some(inner.to_string()),
+        None => Some(cid),
+    }
+}
+
+// Compares the local parts of two Content-IDs exactly and their domains
+// case-insensitively
+// Returns a Content-ID with its domain lowercased, keys are equal if and only
+// if `content_id_matches` is true
+pub(crate) fn content_id_key(id: &str) -> Cow<'_, str> {
+    match id.rsplit_once('@') {
+        Some((local, domain)) if domain.bytes().any(|ch| ch.is_ascii_uppercase()) => {
+            format!("{local}@{}", domain.to_ascii_lowercase()).into()
+        }
+        _ => id.into(),
+    }
+}
+
+fn content_id_matches(a: &str, b: &str) -> bool {
+    match (a.rsplit_once('@'), b.rsplit_once('@')) {
+        (Some((a_local, a_domain)), Some((b_local, b_domain))) => {
+            a_local == b_local && a_domain.eq_ignore_ascii_case(b_domain)
+        }
+        _ => a == b,
+    }
+}
+
+// Returns the Content-IDs referenced by the `cid:` URLs of an HTML body
+fn scan_cids(html: &str) -> impl Iterator<Item = String> + '_ {
+    let bytes = html.as_bytes();
+    (0..bytes.len().saturating_sub(4)).filter_map(move |pos| {
+        if !bytes[pos..pos + 4].eq_ignore_ascii_case(b"cid:") {
+            return None;
+        }
+        let len = bytes[pos + 4..]
+            .iter()
+            .position(|ch| matches!(ch, b'"' | b'\'' | b')' | b'>') || ch.is_ascii_whitespace())
+            .unwrap_or(bytes.len() - pos - 4);
+        normalize_cid(html.get(pos..pos + 4 + len)?).filter(|cid| !cid.is_empty())
+    })
+}
+
+fn summary_field(text: &str, max_chars: usize) -> String {
+    let mut field = String::with_capacity(text.len().min(max_chars * 4));
+    let mut chars = 0;
+
+    for word in text.split_whitespace() {
+        for ch in (chars > 0).then_some(' ').into_iter().chain(word.chars()) {
+            if chars == max_chars {
+                field.pop();
+                field.push('…');
+                return field;
+            }
+            field.push(ch);
+            chars += 1;
+        }
+    }
+
+    field
+}
+
+// Removes RFC 5322 comments, unfolding the value
+fn strip_comments(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut depth = 0;
+    let mut in_quote = false;
+    let mut is_escaped = false;
+    for ch in value.chars() {
+        match ch {
+            _ if is_escaped => is_escaped = false,
+            '\\' => is_escaped = true,
+            '"' if depth == 0 => in_quote = !in_quote,
+            '(' if !in_quote => {
+                depth += 1;
+                continue;
+            }
+            ')' if !in_quote && depth > 0 => {
+                depth -= 1;
+                continue;
+            }
+            '\r' | '\n' => continue,
+            _ => (),
+        }
+        if depth == 0 {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+fn priority_keyword(value: &str) -> Option<Priority> {
+    let value = value.split([' ', '\t', '(']).next()?;
+    if value.eq_ignore_ascii_case("high") || value.eq_ignore_ascii_case("urgent") {
+        Some(Priority::High)
+    } else if value.eq_ignore_ascii_case("normal") {
+        Some(Priority::Normal)
+    } else if value.eq_ignore_ascii_case("low") || value.eq_ignore_ascii_case("non-urgent") {
+        Some(Priority::Low)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        AutoSubmitted, ChildCountViolation, HeaderName, Message, MessageParser, MimeHeaders,
+        PartRole, Priority, SpamStatus,
+    };
+
+    #[test]
+    fn expires_reply_by() {
+        let input = concat!(
+            "From: art@vandelay.com\n",
+            "Expires: Sat, 20 Nov 2021 14:22:01 -0800\n",
+            "Reply-By: 21 Nov 2021 09:00:00 GMT\n",
+            "Subject: Deadline\n",
+            "\n",
+            "Hello\n"
+        );
+
+        for parser in [
+            MessageParser::default(),
+            MessageParser::new()
+                .with_date_headers()
+                .header_date(HeaderName::Other("Expires".into()))
+                .header_date(HeaderName::Other("Reply-By".into())),
+        ] {
+            let message = parser.parse(input).unwrap();
+            assert_eq!(
+                message.expires().unwrap().to_rfc3339(),
+                "2021-11-20T14:22:01-08:00"
+            );
+            assert_eq!(
+                message.reply_by().unwrap().to_rfc3339(),
+                "2021-11-21T09:00:00Z"
+            );
+            assert_eq!(message.date(), None);
+        }
+
+        assert_eq!(
+            MessageParser::default()
+                .parse("Subject: test\n\nbody")
+                .unwrap()
+                .expires(),
+            None
+        );
+    }
+
+    #[test]
+    fn delivery_date() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Delivery-Date: Tue, 1 Jul 2003 10:52:37 +0200\n",
+                "Date: Mon, 30 Jun 2003 22:01:12 -0700\n",
+                "Subject: test\n\nbody",
+            ))
+            .unwrap();
+        assert_eq!(
+            message.delivery_date().unwrap().to_rfc3339(),
+            "2003-07-01T10:52:37+02:00"
+        );
+        assert_eq!(
+            message.date().unwrap().to_rfc3339(),
+            "2003-06-30T22:01:12-07:00"
+        );
+
+        let message = MessageParser::default()
+            .header_date("Delivery-Date")
+            .parse("Delivery-Date: Tue, 1 Jul 2003 10:52:37 +0200\n\nbody")
+            .unwrap();
+        assert_eq!(message.delivery_date().unwrap().year, 2003);
+
+        for input in [
+            "Subject: test\n\nbody",
+            "Delivery-Date: yesterday\nSubject: test\n\nbody",
+        ] {
+            assert_eq!(
+                MessageParser::default()
+                    .parse(input)
+                    .unwrap()
+                    .delivery_date(),
+                None,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn auto_submitted() {
+        for (header, expected) in [
+            ("no", Some(AutoSubmitted::No)),
+            ("No (manually sent)", Some(AutoSubmitted::No)),
+            ("auto-generated", Some(AutoSubmitted::AutoGenerated(vec![]))),
+            (
+                "auto-replied (vacation); owner-email=\"art@vandelay.com\"",
+                Some(AutoSubmitted::AutoReplied(vec![(
+                    "owner-email".into(),
+                    "art@vandelay.com".into(),
+                )])),
+            ),
+            (
+                "auto-notified",
+                Some(AutoSubmitted::Other("auto-notified".into(), vec![])),
+            ),
+        ] {
+            let input = format!("Auto-Submitted: {header}\nSubject: test\n\nbody");
+            let message = MessageParser::default().parse(input.as_bytes()).unwrap();
+            let auto_submitted = message.auto_submitted();
+            assert_eq!(auto_submitted, expected, "failed for {header:?}");
+            assert_eq!(
+                auto_submitted.unwrap().is_auto_submitted(),
+                !header.to_lowercase().starts_with("no"),
+            );
+        }
+
+        assert_eq!(
+            MessageParser::default()
+                .parse("Subject: test\n\nbody")
+                .unwrap()
+                .auto_submitted(),
+            None
+        );
+    }
+
+    #[test]
+    fn get_header_raw() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: =?utf-8?q?hello?=\r\n",
+                "x-custom: first\r\n",
+                "\tline\r\n",
+                "X-Custom: second\r\n",
+                "\r\n",
+                "body"
+            ))
+            .unwrap();
+
+        assert_eq!(
+            message.get_header_raw("subject"),
+            Some(&b" =?utf-8?q?hello?="[..])
+        );
+        assert_eq!(
+            message.get_header_raw("X-CUSTOM"),
+            Some(&b" first\r\n\tline"[..])
+        );
+        assert_eq!(message.get_header_raw("X-Missing"), None);
+    }
+
+    #[test]
+    fn organization() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Organization: =?iso-8859-1?q?Caf=E9?=\n",
+                " =?utf-8?q?M=C3=BCller?= GmbH\n",
+                "organization: Second\n",
+                "\n",
+                "body"
+            ))
+            .unwrap();
+
+        assert_eq!(message.organization().unwrap(), "CaféMüller GmbH");
+        assert_eq!(message.header_values("Organization").count(), 2);
+
+        let message = MessageParser::default()
+            .parse("Subject: test\n\nbody")
+            .unwrap();
+        assert_eq!(message.organization(), None);
+    }
+
+    #[test]
+    fn all_addresses() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "From: John <john@example.org>\n",
+                "Sender: JOHN@example.org\n",
+                "To: Team: jane@example.org, bob@example.net;, carol@example.com\n",
+                "Cc: jane@Example.org\n",
+                "\n",
+                "Please write to support@help.example.com. or to bob@example.net,\n",
+                "not to @handle, user@localhost or a.@b.c.\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            message.all_addresses(false),
+            vec![
+                "john@example.org",
+                "jane@example.org",
+                "bob@example.net",
+                "carol@example.com"
+            ]
+        );
+        assert_eq!(
+            message.all_addresses(true),
+            vec![
+                "john@example.org",
+                "jane@example.org",
+                "bob@example.net",
+                "carol@example.com",
+                "support@help.example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn thread_topic_index() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Thread-Topic: =?utf-8?q?Caf=C3=A9?= meeting\n",
+                "Thread-Index: Adl+cg2tAQIDBAUGBwgJCgsMDQ4P\n",
+                " EAAAAQID\n",
+                "\n",
+                "body"
+            ))
+            .unwrap();
+
+        assert_eq!(message.thread_topic().unwrap(), "Café meeting");
+        let thread_index = message.thread_index().unwrap();
+        assert_eq!(thread_index.as_bytes().len(), 27);
+        assert_eq!(thread_index.guid(), (1..=16).collect::<Vec<u8>>());
+        assert_eq!(thread_index.date().to_rfc3339(), "2023-05-04T10:20:30Z");
+        assert_eq!(thread_index.child_count(), 1);
+
+        let message = MessageParser::default()
+            .parse("Thread-Index: AAAA\n\nbody")
+            .unwrap();
+        assert_eq!(message.thread_topic(), None);
+        assert_eq!(message.thread_index(), None);
+    }
+
+    #[test]
+    fn bounce_auto_reply() {
+        for (input, is_bounce, is_auto_reply) in [
+            (
+                concat!(
+                    "From: Mail Delivery System <MAILER-DAEMON@example.org>\n",
+                    "Content-Type: multipart/report; report-type=delivery-status; boundary=\"a\"\n\n",
+                    "--a\n",
+                    "Content-Type: text/plain\n\n",
+                    "Delivery failed\n",
+                    "--a\n",
+                    "Content-Type: message/delivery-status\n\n",
+                    "Reporting-MTA: dns; example.org\n",
+                    "--a--\n",
+                ),
+                true,
+                false,
+            ),
+            (
+                concat!(
+                    "Return-Path: < >\n",
+                    "From: postmaster@example.org\n\n",
+                    "Undeliverable\n",
+                ),
+                true,
+                false,
+            ),
+            ("From: postmaster@example.org\n\nHello\n", false, false),
+            (
+                "Return-Path: <>\nFrom: john@example.org\nAuto-Submitted: auto-replied\n\nAway\n",
+                false,
+                true,
+            ),
+            (
+                "From: john@example.org\nPrecedence: auto_reply\n\nAway\n",
+                false,
+                true,
+            ),
+            (
+                "From: john@example.org\nX-Autoreply: yes\n\nAway\n",
+                false,
+                true,
+            ),
+            (
+                "From: john@example.org\nAuto-Submitted: auto-generated\n\nReport\n",
+                false,
+                false,
+            ),
+        ] {
+            let message = MessageParser::default().parse(input).unwrap();
+            assert_eq!(message.is_bounce(), is_bounce, "failed for {input:?}");
+            assert_eq!(
+                message.is_auto_reply(),
+                is_auto_reply,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn priority() {
+        for (headers, expected) in [
+            (
+                "X-Priority: 1 (Highest)\nX-MSMail-Priority: High\nImportance: High\n",
+                Some(Priority::High),
+            ),
+            ("X-MSMail-Priority: High\n", Some(Priority::High)),
+            (
+                "X-MSMail-Priority: Low\nImportance: high\n",
+                Some(Priority::Low),
+            ),
+            (
+                "X-Priority: 5\nX-MSMail-Priority: High\n",
+                Some(Priority::Low),
+            ),
+            (
+                "X-Priority: x\nX-MSMail-Priority: Normal\n",
+                Some(Priority::Normal),
+            ),
+            ("Importance: low\n", Some(Priority::Low)),
+            ("Priority: urgent\n", Some(Priority::High)),
+            ("Priority: non-urgent\n", Some(Priority::Low)),
+            ("Subject: test\n", None),
+        ] {
+            let input = format!("{headers}\nbody");
+            let message = MessageParser::default().parse(&input).unwrap();
+            assert_eq!(message.priority(), expected, "failed for {headers:?}");
+        }
+    }
+
+    #[test]
+    fn flatten_headers() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "From: a@example.org\n",
+                "Subject: Fwd\n",
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: text/plain\n\n",
+                "See attached\n",
+                "--a\n",
+                "Content-Type: message/rfc822\n\n",
+                "From: b@example.org\n",
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: Innermost\n\n",
+                "Hi\n",
+                "--b--\n",
+                "--a\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: Second\n\n",
+                "Hello\n",
+                "--a--\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            message
+                .flatten_headers()
+                .into_iter()
+                .map(|(path, header)| format!("{path}: {}", header.value().as_text().unwrap_or("")))
+                .collect::<Vec<_>>(),
+            vec![
+                "from: ",
+                "subject: Fwd",
+                "content-type: ",
+                "embedded[0].from: ",
+                "embedded[0].content-type: ",
+                "embedded[0].embedded[0].subject: Innermost",
+                "embedded[1].subject: Second",
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_images() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/related; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: text/html\n\n",
+                "<p><img src=\"cid:logo@Example.ORG\"><img src='CID:chart%40example.org'>",
+                "<img src=\"https://example.org/remote.png\"></p>\n",
+                "--a\n",
+                "Content-Type: image/png\n",
+                "Content-ID: <logo@example.org>\n\n",
+                "logo\n",
+                "--a\n",
+                "Content-Type: image/gif\n",
+                "Content-ID: <chart@example.org>\n",
+                "Content-Disposition: attachment\n\n",
+                "chart\n",
+                "--a\n",
+                "Content-Type: image/jpeg\n",
+                "Content-Disposition: inline; filename=\"photo.jpg\"\n\n",
+                "photo\n",
+                "--a\n",
+                "Content-Type: image/jpeg\n",
+                "Content-ID: <unreferenced@example.org>\n",
+                "Content-Disposition: attachment; filename=\"scan.jpg\"\n\n",
+                "scan\n",
+                "--a\n",
+                "Content-Type: application/pdf\n",
+                "Content-Disposition: inline\n\n",
+                "pdf\n",
+                "--a--\n",
+            ))
+            .unwrap();
+
+        assert!(message.has_inline_images());
+        assert_eq!(message.inline_images(), vec![2, 3]);
+        assert_eq!(message.attachments, vec![4, 5, 6]);
+        assert_eq!(
+            message
+                .inline_images()
+                .into_iter()
+                .map(|part_id| message.parts[part_id].content_id())
+                .collect::<Vec<_>>(),
+            vec![Some("logo@example.org"), Some("chart@example.org")]
+        );
+
+        let message = MessageParser::default()
+            .parse("Content-Type: text/html\n\n<img src=\"https://example.org/a.png\">")
+            .unwrap();
+        assert!(!message.has_inline_images());
+    }
+
+    #[test]
+    fn auth_results() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Authentication-Results: mx.example.org;\n",
+                "  spf=pass (sender IP is 192.0.2.1) smtp.mailfrom=example.com;\n",
+                "  dkim=fail (bad signature) header.d=example.com;\n",
+                "  dkim=pass header.d=mailer.example.net;\n",
+                "  DMARC=fail (p=reject) header.from=example.com\n",
+                "Authentication-Results: relay.example.net; spf=fail; dmarc=pass\n",
+                "Received-SPF: fail (example.org: domain does not designate 192.0.2.1)\n",
+                "Subject: test\n\n",
+                "body",
+            ))
+            .unwrap();
+        assert_eq!(message.spf_passed(), Some(true));
+        assert_eq!(message.dkim_passed(), Some(true));
+        assert_eq!(message.dmarc_passed(), Some(false));
+
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Authentication-Results: mx.example.org (comment; with=semicolon); none\n",
+                "Authentication-Results: mx.example.org; dkim/1=neutral (no key)\n",
+                "Received-SPF: Pass (mailfrom) identity=mailfrom;\n",
+                "Subject: test\n\n",
+                "body",
+            ))
+            .unwrap();
+        assert_eq!(message.spf_passed(), Some(true));
+        assert_eq!(message.dkim_passed(), Some(false));
+        assert_eq!(message.dmarc_passed(), None);
+
+        let message = MessageParser::default()
+            .parse("Subject: test\n\nbody")
+            .unwrap();
+        assert_eq!(message.spf_passed(), None);
+        assert_eq!(message.dkim_passed(), None);
+        assert_eq!(message.dmarc_passed(), None);
+    }
+
+    #[test]
+    fn in_reply_to_multiple_ids() {
+        for (input, expected) in [
+            ("<a@x> <b@y> (was: something)", vec!["a@x", "b@y"]),
+            (
+                "Your message of \"Mon, 1 Jan 2024\"\n <a@x>, <b@y>",
+                vec!["a@x", "b@y"],
+            ),
+            ("(comment) <a@x> (another comment)", vec!["a@x"]),
+            ("<a@x>", vec!["a@x"]),
+        ] {
+            let input = format!("In-Reply-To: {input}\n\nbody");
+            let message = MessageParser::default().parse(&input).unwrap();
+            assert_eq!(
+                message.in_reply_to().as_text_list().unwrap_or_default(),
+                expected,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn child_count_violations() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: multipart/alternative; boundary=\"alt\"\n\n",
+                "--alt\n",
+                "Content-Type: text/plain\n\n",
+                "only alternative\n",
+                "--alt--\n",
+                "--mixed\n",
+                "Content-Type: multipart/signed; boundary=\"signed\"\n\n",
+                "--signed\n",
+                "Content-Type: text/plain\n\n",
+                "signed text\n",
+                "--signed\n",
+                "Content-Type: application/pgp-signature\n\n",
+                "signature\n",
+                "--signed\n",
+                "Content-Type: text/plain\n\n",
+                "injected\n",
+                "--signed--\n",
+                "--mixed\n",
+                "Content-Type: multipart/encrypted; boundary=\"enc\"\n\n",
+                "--enc\n",
+                "Content-Type: application/pgp-encrypted\n\n",
+                "Version: 1\n",
+                "--enc\n",
+                "Content-Type: application/octet-stream\n\n",
+                "data\n",
+                "--enc--\n",
+                "--mixed--\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            message.child_count_violations(),
+            vec![
+                ChildCountViolation {
+                    part_id: 1,
+                    children: 1,
+                    min_children: 2,
+                    max_children: None,
+                },
+                ChildCountViolation {
+                    part_id: 3,
+                    children: 3,
+                    min_children: 2,
+                    max_children: Some(2),
+                },
+            ]
+        );
+
+        let message = MessageParser::default()
+            .parse("Content-Type: text/plain\n\nbody")
+            .unwrap();
+        assert!(message.child_count_violations().is_empty());
+    }
+
+    #[test]
+    fn face_headers() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Face: iVBORw0KGgoAAAANSUhEUgAAADAAAAAwAQAAAAB/ecQqAAAAC0lEQVR4nG\n",
+                " NgAAMAAAcAAbKGrPQAAAAASUVORK5CYII=\n",
+                "X-Face: \"8Hq}7$f(Gg@ZCy^1Mk|WT3|#lEhHJ/(KaQ,wo;i8=Vd%w_XVMUx\n",
+                "\t!!F(>@9HWd%C.yM)0\n",
+                "Subject: avatar\n\n",
+                "body",
+            ))
+            .unwrap();
+
+        let face = message.face().unwrap();
+        assert_eq!(face.len(), 68);
+        assert!(face.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(
+            message.x_face().as_deref(),
+            Some("\"8Hq}7$f(Gg@ZCy^1Mk|WT3|#lEhHJ/(KaQ,wo;i8=Vd%w_XVMUx!!F(>@9HWd%C.yM)0")
+        );
+
+        for input in [
+            "Face: R0lGODlhAQABAAAAACw=\n\nbody",
+            "Face: not base64!\n\nbody",
+            "Subject: no face\n\nbody",
+        ] {
+            let message = MessageParser::default().parse(input).unwrap();
+            assert_eq!(message.face(), None, "failed for {input:?}");
+            assert_eq!(message.x_face(), None, "failed for {input:?}");
+        }
+    }
+
+    #[test]
+    fn message_summary() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "From: \"Jane Doe\" <jane@example.com>\n",
+                "Date: Fri, 1 Mar 2024 09:15:00 +0100\n",
+                "Subject: Re: [list] Quarterly\n report\n",
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: text/plain\n\n",
+                "See attached.\n",
+                "--b\n",
+                "Content-Type: application/pdf\n\n",
+                "%PDF\n",
+                "--b\n",
+                "Content-Type: image/png; name=\"chart.png\"\n",
+                "Content-Disposition: attachment\n\n",
+                "PNG\n",
+                "--b--\n",
+            ))
+            .unwrap();
+        assert_eq!(
+            message.summary(),
+            "2024-03-01 09:15  Jane Doe                  Quarterly report (2 attachments)"
+        );
+
+        let message = MessageParser::default()
+            .parse(concat!(
+                "From: an-extremely-long-mailbox-name@example.com\n",
+                "Subject: ",
+                "A subject line that goes on and on well past the width of a terminal column\n\n",
+                "body",
+            ))
+            .unwrap();
+        assert_eq!(
+            message.summary(),
+            concat!(
+                "-                 an-extremely-long-mailb…  ",
+                "A subject line that goes on and on well past the width of a…"
+            )
+        );
+
+        let message = MessageParser::default()
+            .parse("X-Empty: yes\n\nbody")
+            .unwrap();
+        assert_eq!(
+            message.summary(),
+            "-                 -                         -"
+        );
+    }
+
+    #[test]
+    fn imap_paths() {
+        // Body structure from RFC 3501, Section 6.4.5
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: RFC 3501 example\n",
+                "Content-Type: multipart/mixed; boundary=\"outer\"\n\n",
+                "--outer\n",
+                "Content-Type: text/plain\n\n",
+                "part 1\n",
+                "--outer\n",
+                "Content-Type: application/octet-stream\n\n",
+                "part 2\n",
+                "--outer\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: part 3\n",
+                "Content-Type: multipart/mixed; boundary=\"three\"\n\n",
+                "--three\n",
+                "Content-Type: text/plain\n\n",
+                "part 3.1\n",
+                "--three\n",
+                "Content-Type: application/octet-stream\n\n",
+                "part 3.2\n",
+                "--three--\n",
+                "--outer\n",
+                "Content-Type: multipart/mixed; boundary=\"four\"\n\n",
+                "--four\n",
+                "Content-Type: image/gif\n\n",
+                "part 4.1\n",
+                "--four\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: part 4.2\n",
+                "Content-Type: multipart/mixed; boundary=\"fourtwo\"\n\n",
+                "--fourtwo\n",
+                "Content-Type: text/plain\n\n",
+                "part 4.2.1\n",
+                "--fourtwo\n",
+                "Content-Type: multipart/alternative; boundary=\"alt\"\n\n",
+                "--alt\n",
+                "Content-Type: text/plain\n\n",
+                "part 4.2.2.1\n",
+                "--alt\n",
+                "Content-Type: text/richtext\n\n",
+                "part 4.2.2.2\n",
+                "--alt--\n",
+                "--fourtwo--\n",
+                "--four--\n",
+                "--outer--\n",
+            ))
+            .unwrap();
+
+        for path in ["1", "2", "3.1", "3.2", "4.1", "4.2.1", "4.2.2.1", "4.2.2.2"] {
+            let part = message.part_by_imap_path(path).unwrap();
+            assert_eq!(
+                part.contents(),
+                format!("part {path}").as_bytes(),
+                "failed for {path:?}"
+            );
+        }
+        assert_eq!(
+            message
+                .part_by_imap_path("3")
+                .unwrap()
+                .message()
+                .unwrap()
+                .subject(),
+            Some("part 3")
+        );
+        assert_eq!(
+            message
+                .part_by_imap_path("4.2")
+                .unwrap()
+                .message()
+                .unwrap()
+                .subject(),
+            Some("part 4.2")
+        );
+        assert!(message.part_by_imap_path("4").unwrap().is_multipart());
+        assert!(message.part_by_imap_path("4.2.2").unwrap().is_multipart());
+        for path in ["", "0", "5", "1.1", "3.3", "4.2.3", "x", "1.", "-1"] {
+            assert!(
+                message.part_by_imap_path(path).is_none(),
+                "failed for {path:?}"
+            );
+        }
+
+        for (index, part) in message.parts.iter().enumerate() {
+            let path = message.imap_path_of(index).unwrap();
+            if index == 0 {
+                assert_eq!(path, "");
+            } else {
+                assert!(
+                    std::ptr::eq(message.part_by_imap_path(&path).unwrap(), part),
+                    "failed for {path:?}"
+                );
+            }
+        }
+        assert_eq!(message.imap_path_of(message.parts.len()), None);
+
+        let message = MessageParser::default()
+            .parse("Subject: single\n\nbody")
+            .unwrap();
+        assert_eq!(message.part_by_imap_path("1").unwrap().contents(), b"body");
+        assert!(message.part_by_imap_path("2").is_none());
+        assert!(message.part_by_imap_path("1.1").is_none());
+        assert_eq!(message.imap_path_of(0).as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn part_headers() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: parts\n",
+                "X-Top: message\n",
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: text/plain\n",
+                "X-Part-Id: first\n",
+                "X-Scanner: clean\n\n",
+                "body\n",
+                "--b--\n",
+            ))
+            .unwrap();
+
+        let part = message.part(1).unwrap();
+        let custom = part
+            .headers()
+            .iter()
+            .filter(|h| h.name().starts_with("X-"))
+            .map(|h| (h.name(), h.value().as_text().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(custom, [("X-Part-Id", "first"), ("X-Scanner", "clean")]);
+        assert_eq!(part.headers().len(), 3);
+
+        let raw = part
+            .headers()
+            .iter()
+            .map(|h| &message.raw_message()[h.offset_field..h.offset_end])
+            .collect::<Vec<_>>();
+        assert_eq!(raw[1], b"X-Part-Id: first\n");
+    }
+
+    #[test]
+    fn mbox_envelope_line() {
+        let input = concat!(
+            "From alice@example.com Sat Jan  3 01:05:34 1996\n",
+            "From: Alice <alice@example.com>\n",
+            "Subject: hello\n\n",
+            "body\n",
+        );
+        for message in [
+            MessageParser::default().parse(input).unwrap(),
+            MessageParser::default().parse_headers(input).unwrap(),
+        ] {
+            assert_eq!(message.headers().len(), 2);
+            assert_eq!(
+                message.from().unwrap().first().unwrap().address(),
+                Some("alice@example.com")
+            );
+            assert_eq!(message.subject(), Some("hello"));
+            assert_eq!(message.envelope_from(), Some("alice@example.com"));
+            assert_eq!(
+                message.envelope_date().unwrap().to_rfc3339(),
+                "1996-01-03T01:05:34Z"
+            );
+        }
+        assert_eq!(
+            MessageParser::default()
+                .parse(input)
+                .unwrap()
+                .body_text(0)
+                .as_deref(),
+            Some("body\n")
+        );
+
+        let message = MessageParser::default()
+            .parse("From: bob@example.com\nSubject: hi\n\nbody")
+            .unwrap();
+        assert_eq!(message.headers().len(), 2);
+        assert_eq!(message.envelope_from(), None);
+        assert_eq!(message.envelope_date(), None);
+
+        // Obsolete header syntax is not mistaken for an envelope line
+        let input = "From : John <john@example.com>\nSubject: hi\n\nbody\n";
+        let parser = MessageParser::default();
+        let message = parser.parse(input).unwrap();
+        assert_eq!(
+            message.from().unwrap().first().unwrap().address(),
+            Some("john@example.com")
+        );
+        assert_eq!(message.subject(), Some("hi"));
+        assert_eq!(message.envelope_from(), None);
+        assert_eq!(
+            parser.parse_part(input, "1").unwrap().text_contents(),
+            Some("body\n")
+        );
+    }
+
+    #[test]
+    fn unique_recipients() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "To: jane@example.com, \"Jane\" <jane@example.com>, john@example.com\n",
+                "Cc: Team: JOHN@example.com, ops@example.com;, jane@example.com\n",
+                "Bcc: audit@example.com, undisclosed-recipients:;\n",
+                "Subject: padding\n\n",
+                "body",
+            ))
+            .unwrap();
+
+        let to = message.to().unwrap();
+        assert_eq!(
+            to.iter().map(|a| a.address().unwrap()).collect::<Vec<_>>(),
+            ["jane@example.com", "jane@example.com", "john@example.com"]
+        );
+        assert_eq!(message.cc().unwrap().iter().count(), 3);
+
+        assert_eq!(
+            message
+                .unique_recipients()
+                .into_iter()
+                .map(|a| a.address().unwrap())
+                .collect::<Vec<_>>(),
+            [
+                "jane@example.com",
+                "john@example.com",
+                "ops@example.com",
+                "audit@example.com"
+            ]
+        );
+
+        let message = MessageParser::default()
+            .parse("Subject: none\n\nbody")
+            .unwrap();
+        assert!(message.unique_recipients().is_empty());
+    }
+
+    #[test]
+    fn render_order() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: newsletter\n",
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: multipart/alternative; boundary=\"alt\"\n\n",
+                "--alt\n",
+                "Content-Type: text/plain\n\n",
+                "plain version\n",
+                "--alt\n",
+                "Content-Type: multipart/related; boundary=\"rel\"\n\n",
+                "--rel\n",
+                "Content-Type: text/html\n\n",
+                "<p>html version <img src=\"cid:logo@example\"></p>\n",
+                "--rel\n",
+                "Content-Type: image/png\n",
+                "Content-ID: <logo@example>\n\n",
+                "logo\n",
+                "--rel--\n",
+                "--alt--\n",
+                "--mixed\n",
+                "Content-Type: image/jpeg\n",
+                "Content-Disposition: inline\n\n",
+                "photo\n",
+                "--mixed\n",
+                "Content-Type: application/pdf\n",
+                "Content-Disposition: attachment; filename=\"report.pdf\"\n\n",
+                "pdf\n",
+                "--mixed--\n",
+            ))
+            .unwrap();
+
+        let order = message
+            .render_order()
+            .into_iter()
+            .map(|(part_id, role)| {
+                (
+                    message.parts[part_id].content_type().unwrap().full_type(),
+                    role,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            [
+                ("text/html".into(), PartRole::Body),
+                ("image/jpeg".into(), PartRole::InlineImage),
+                ("image/png".into(), PartRole::InlineImage),
+                ("application/pdf".into(), PartRole::Attachment),
+            ]
+        );
+
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: plain\n",
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: text/plain\n\n",
+                "hello\n",
+                "--b\n",
+                "Content-Type: text/plain; name=\"notes.txt\"\n",
+                "Content-Disposition: attachment\n\n",
+                "notes\n",
+                "--b--\n",
+            ))
+            .unwrap();
+        assert_eq!(
+            message.render_order(),
+            [(1, PartRole::Body), (2, PartRole::Attachment)]
+        );
+    }
+
+    #[test]
+    fn message_preview() {
+        for (input, max_chars, expected) in [
+            (
+                concat!(
+                    "Content-Type: text/plain; format=flowed; delsp=yes\n\n",
+                    "Lunch is on the ter \n",
+                    "race   today, don't  \n",
+                    "be late!\n",
+                ),
+                100,
+                "Lunch is on the terrace today, don't be late!",
+            ),
+            (
+                concat!(
+                    "Content-Type: text/plain; format=flowed; delsp=yes\n\n",
+                    "Lunch is on the ter \n",
+                    "race   today, don't  \n",
+                    "be late!\n",
+                ),
+                30,
+                "Lunch is on the terrace...",
+            ),
+            (
+                concat!(
+                    "Content-Type: multipart/alternative; boundary=\"alt\"\n\n",
+                    "--alt\n",
+                    "Content-Type: text/html\n\n",
+                    "<p>html version</p>\n",
+                    "--alt\n",
+                    "Content-Type: text/plain\n\n",
+                    "text  version\n",
+                    "--alt--\n",
+                ),
+                100,
+                "text version",
+            ),
+            (
+                concat!(
+                    "Content-Type: text/html; charset=utf-8\n\n",
+                    "<html><body><p>¡Hola,</p>\n<p>señor Núñez!</p></body></html>\n",
+                ),
+                15,
+                "¡Hola, señor...",
+            ),
+            (
+                concat!(
+                    "Content-Type: image/png\n",
+                    "Content-Disposition: attachment\n\n",
+                    "png\n",
+                ),
+                100,
+                "",
+            ),
+        ] {
+            let message = MessageParser::default().parse(input).unwrap();
+            let preview = message.preview(max_chars);
+            assert_eq!(preview, expected, "failed for {input:?}");
+            assert!(preview.chars().count() <= max_chars);
+        }
+    }
+
+    #[test]
+    fn content_type_histogram() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: multipart/alternative; boundary=\"alt\"\n\n",
+                "--alt\n",
+                "Content-Type: text/plain\n\n",
+                "Plain body\n",
+                "--alt\n",
+                "Content-Type: TEXT/HTML\n\n",
+                "<p>HTML body</p>\n",
+                "--alt--\n",
+                "--mixed\n",
+                "Content-Type: image/png\n\n",
+                "png\n",
+                "--mixed\n",
+                "Content-Type: image/png\n\n",
+                "png\n",
+                "--mixed\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: Attached\n",
+                "Content-Type: multipart/mixed; boundary=\"nested\"\n\n",
+                "--nested\n\n",
+                "Nested body\n",
+                "--nested\n",
+                "Content-Type: image/png\n\n",
+                "png\n",
+                "--nested\n",
+                "Content-Type: application/pdf\n\n",
+                "%PDF-\n",
+                "--nested--\n",
+                "--mixed--\n",
+            ))
+            .unwrap();
+
+        let mut histogram = message
+            .content_type_histogram(false)
+            .into_iter()
+            .collect::<Vec<_>>();
+        histogram.sort();
+        assert_eq!(
+            histogram,
+            [
+                ("application/pdf".to_string(), 1),
+                ("image/png".to_string(), 3),
+                ("text/html".to_string(), 1),
+                ("text/plain".to_string(), 2),
+            ]
+        );
+
+        let histogram = message.content_type_histogram(true);
+        assert_eq!(histogram.len(), 7);
+        assert_eq!(histogram["multipart/mixed"], 2);
+        assert_eq!(histogram["multipart/alternative"], 1);
+        assert_eq!(histogram["message/rfc822"], 1);
+        assert_eq!(histogram["image/png"], 3);
+    }
+
+    #[test]
+    fn walk_parts() {
+        let input = concat!(
+            "Subject: Walk\n",
+            "Content-Type: multipart/mixed; boundary=\"level-1\"\n\n",
+            "--level-1\n",
+            "Content-Type: multipart/alternative; boundary=\"level-2\"\n\n",
+            "--level-2\n",
+            "Content-Type: text/plain\n\n",
+            "Plain\n",
+            "--level-2\n",
+            "Content-Type: multipart/related; boundary=\"level-3\"\n\n",
+            "--level-3\n",
+            "Content-Type: text/html\n\n",
+            "<p>Html</p>\n",
+            "--level-3\n",
+            "Content-Type: image/png\n\n",
+            "PNG\n",
+            "--level-3--\n",
+            "--level-2--\n",
+            "--level-1\n",
+            "Content-Type: message/rfc822\n\n",
+            "Subject: Nested\n",
+            "Content-Type: multipart/mixed; boundary=\"nested\"\n\n",
+            "--nested\n",
+            "Content-Type: text/plain\n\n",
+            "Nested plain\n",
+            "--nested--\n",
+            "--level-1\n",
+            "Content-Type: text/plain\n\n",
+            "Footer\n",
+            "--level-1--\n",
+        );
+
+        let message = MessageParser::default().parse(input).unwrap();
+        assert_eq!(
+            message
+                .walk_parts()
+                .map(|(depth, part)| {
+                    let ct = part.content_type().unwrap();
+                    (depth, format!("{}/{}", ct.ctype(), ct.subtype().unwrap()))
+                })
+                .collect::<Vec<_>>(),
+            [
+                (0, "multipart/mixed"),
+                (1, "multipart/alternative"),
+                (2, "text/plain"),
+                (2, "multipart/related"),
+                (3, "text/html"),
+                (3, "image/png"),
+                (1, "message/rfc822"),
+                (2, "multipart/mixed"),
+                (3, "text/plain"),
+                (1, "text/plain"),
+            ]
+            .map(|(depth, ct)| (depth, ct.to_string()))
+        );
+        assert_eq!(
+            message
+                .walk_parts()
+                .filter(|(_, part)| part.is_content_type("text", "plain"))
+                .filter_map(|(_, part)| part.text_contents())
+                .collect::<Vec<_>>(),
+            ["Plain", "Nested plain", "Footer"]
+        );
+
+        let message = MessageParser::default()
+            .parse("Subject: Single\n\nHello\n")
+            .unwrap();
+        assert_eq!(
+            message
+                .walk_parts()
+                .map(|(depth, part)| (depth, part.text_contents()))
+                .collect::<Vec<_>>(),
+            [(0, Some("Hello\n"))]
+        );
+        assert_eq!(Message::default().walk_parts().count(), 0);
+    }
+
+    #[test]
+    fn spam_headers() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "X-Spam-Score: 7.3\n",
+                "X-Spam-Status: Yes, score=7.3 required=5.0 tests=BAYES_99,\n",
+                "\tHTML_MESSAGE autolearn=no version=3.4.6\n",
+                "Subject: Spam\n\n",
+                "Body\n",
+            ))
+            .unwrap();
+        assert_eq!(message.spam_score(), Some(7.3));
+        assert_eq!(
+            message.spam_status(),
+            Some(SpamStatus {
+                is_spam: true,
+                score: 7.3,
+                required: Some(5.0),
+                tests: vec!["BAYES_99".to_string(), "HTML_MESSAGE".to_string()],
+            })
+        );
+
+        let message = MessageParser::default()
+            .parse("X-Spam-Status: Unknown\nX-Spam-Score: ***\n\nBody\n")
+            .unwrap();
+        assert_eq!(message.spam_status(), None);
+        assert_eq!(message.spam_score(), None);
+
+        let message = MessageParser::default()
+            .parse("Subject: Ham\n\nBody\n")
+            .unwrap();
+        assert_eq!(message.spam_status(), None);
+        assert_eq!(message.spam_score(), None);
+    }
+
+    #[test]
+    fn inline_parts() {
+        let input = concat!(
+            "Subject: Related\n",
+            "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+            "--mixed\n",
+            "Content-Type: multipart/related; boundary=\"related\"\n\n",
+            "--related\n",
+            "Content-Type: text/html\n\n",
+            "<p><img src=\"cid:logo@example.com\"></p>\n",
+            "--related\n",
+            "Content-Type: image/png\n",
+            "Content-Disposition: inline; filename=\"logo.png\"\n",
+            "Content-ID: <logo@example.com>\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "iVBORw0KGgo=\n",
+            "--related--\n",
+            "--mixed\n",
+            "Content-Type: application/pdf\n",
+            "Content-Disposition: attachment; filename=\"report.pdf\"\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "JVBERi0xLjcK\n",
+            "--mixed\n",
+            "Content-Type: image/jpeg; name=\"photo.jpg\"\n",
+            "Content-Disposition: inline\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "/9j/4AAQ\n",
+            "--mixed\n",
+            "Content-Type: application/octet-stream; name=\"data.bin\"\n\n",
+            "data\n",
+            "--mixed--\n",
+        );
+        let message = MessageParser::default().parse(input).unwrap();
+
+        assert_eq!(
+            message
+                .inline_parts()
+                .map(|part| (part.attachment_name(), part.content_id()))
+                .collect::<Vec<_>>(),
+            [(Some("logo.png"), Some("logo@example.com"))]
+        );
+        assert_eq!(
+            message
+                .inline_images()
+                .into_iter()
+                .map(|part_id| message.parts[part_id].attachment_name())
+                .collect::<Vec<_>>(),
+            [Some("logo.png")]
+        );
+        assert_eq!(
+            message
+                .attachments()
+                .map(|part| part.attachment_name())
+                .collect::<Vec<_>>(),
+            [Some("report.pdf"), Some("photo.jpg"), Some("data.bin")]
+        );
+        assert_eq!(message.attachment_count(), 3);
+        assert_eq!(message.attachment_count(), message.attachments.len());
+        assert_eq!(
+            (0..4)
+                .map(|pos| message
+                    .attachment(pos)
+                    .and_then(|part| part.attachment_name()))
+                .collect::<Vec<_>>(),
+            [
+                Some("report.pdf"),
+                Some("photo.jpg"),
+                Some("data.bin"),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn part_by_content_id() {
+        let input = concat!(
+            "Content-Type: multipart/related; boundary=\"related\"\n\n",
+            "--related\n",
+            "Content-Type: text/html\n\n",
+            "<img src=\"cid:logo@Example.COM\"><img src=\"cid:photo%20one@example.com\">\n",
+            "--related\n",
+            "Content-Type: image/png\n",
+            "Content-ID: <logo@example.com>\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "iVBORw0KGgo=\n",
+            "--related\n",
+            "Content-Type: image/jpeg\n",
+            "Content-ID: <photo one@example.com>\n\n",
+            "JPEG\n",
+            "--related--\n",
+        );
+        let message = MessageParser::default().parse(input).unwrap();
+
+        for cid in [
+            "logo@example.com",
+            "<logo@example.com>",
+            "logo@EXAMPLE.com",
+            " <logo@Example.Com> ",
+            "cid:logo@Example.COM",
+        ] {
+            assert_eq!(
+                message
+                    .part_by_content_id(cid)
+                    .map(|part| part.offset_header),
+                Some(message.parts[2].offset_header),
+                "failed for {cid:?}"
+            );
+            assert_eq!(
+                message.resolve_cid(cid),
+                Some(&b"\x89PNG\r\n\x1a\n"[..]),
+                "failed for {cid:?}"
+            );
+        }
+        assert_eq!(
+            message.resolve_cid("cid:photo%20one@example.com"),
+            Some(&b"JPEG"[..])
+        );
+
+        for cid in [
+            "LOGO@example.com",
+            "logo",
+            "<missing@example.com>",
+            "cid:%zz",
+            "",
+        ] {
+            assert!(
+                message.part_by_content_id(cid).is_none(),
+                "failed for {cid:?}"
+            );
+            assert_eq!(message.resolve_cid(cid), None, "failed for {cid:?}");
+        }
+    }
+}
@@ -12,5 +12,10 @@
 pub mod address;
 pub mod body;
 pub mod builder;
+pub mod canonical;
+pub mod diff;
 pub mod header;
 pub mod message;
+pub mod pgp;
+pub mod redact;
+pub mod sniff;
@@ -0,0 +1,153 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use crate::{HeaderName, Message, MessageParser, MessagePartId, MimeHeaders, PartType};
+
+impl Message<'_> {
+    /// Returns a copy of the message with the contents of every part replaced
+    /// by a `[redacted: N bytes]` placeholder, where `N` is the size of the
+    /// decoded contents. Header fields, including attachment file names, and
+    /// the part tree are preserved, while multipart preambles and epilogues are
+    /// dropped. Since placeholders are written as plain text, the
+    /// `Content-Transfer-Encoding` header fields of non-multipart parts are
+    /// removed. Nested `message/rfc822` parts are redacted recursively.
+    ///
+    /// The [`Message::raw_message`] of the result is a valid RFC 5322 message.
+    pub fn redact(&self) -> Message<'static> {
+        let raw_message = self.redacted_raw();
+        MessageParser::default()
+            .parse(&raw_message[..])
+            .map(|message| message.into_owned())
+            .unwrap_or_default()
+    }
+
+    fn redacted_raw(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.raw_message.len().min(4096));
+        if !self.parts.is_empty() {
+            self.redact_part(0, &mut buf);
+        }
+        buf
+    }
+
+    fn redact_part(&self, part_id: MessagePartId, buf: &mut Vec<u8>) {
+        let part = if let Some(part) = self.parts.get(part_id) {
+            part
+        } else {
+            return;
+        };
+
+        for header in &part.headers {
+            if header.name == HeaderName::ContentTransferEncoding && !part.is_multipart() {
+                continue;
+            }
+            let raw_header = self
+                .raw_message
+                .get(header.offset_field..header.offset_end)
+                .unwrap_or_default();
+            buf.extend_from_slice(raw_header);
+            if !raw_header.ends_with(b"\n") {
+                buf.extend_from_slice(b"\r\n");
+            }
+        }
+        buf.extend_from_slice(b"\r\n");
+
+        match &part.body {
+            PartType::Text(_)
+            | PartType::Html(_)
+            | PartType::Binary(_)
+            | PartType::InlineBinary(_) => {
+                buf.extend_from_slice(format!("[redacted: {} bytes]\r\n", part.len()).as_bytes());
+            }
+            PartType::Message(message) => {
+                buf.extend_from_slice(&message.redacted_raw());
+            }
+            PartType::Multipart(sub_parts) => {
+                let boundary = part
+                    .content_type()
+                    .and_then(|ct| ct.attribute("boundary"))
+                    .unwrap_or_default();
+                for &sub_part_id in sub_parts {
+                    buf.extend_from_slice(b"--");
+                    buf.extend_from_slice(boundary.as_bytes());
+                    buf.extend_from_slice(b"\r\n");
+                    self.redact_part(sub_part_id, buf);
+                }
+                buf.extend_from_slice(b"--");
+                buf.extend_from_slice(boundary.as_bytes());
+                buf.extend_from_slice(b"--\r\n");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MessageParser, MimeHeaders};
+
+    #[test]
+    fn redact() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "From: alice@example.com\n",
+                "Subject: secrets\n",
+                "Content-Type: multipart/mixed; boundary=\"outer\"\n\n",
+                "Preamble text\n",
+                "--outer\n",
+                "Content-Type: text/plain; charset=utf-8\n",
+                "Content-Transfer-Encoding: quoted-printable\n\n",
+                "The password is hunter2 =E2=9C=93\n",
+                "--outer\n",
+                "Content-Type: application/pdf; name=\"salary.pdf\"\n",
+                "Content-Disposition: attachment; filename=\"salary.pdf\"\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "JVBERi0xLjcKJeLjz9MK\n",
+                "--outer\n",
+                "Content-Type: message/rfc822\n\n",
+                "Subject: inner\n\n",
+                "Inner secret\n",
+                "--outer--\n",
+                "Epilogue text\n",
+            ))
+            .unwrap();
+
+        let redacted = message.redact();
+        let raw = std::str::from_utf8(redacted.raw_message()).unwrap();
+        for secret in ["hunter2", "JVBERi0", "Inner secret", "Preamble", "Epilogue"] {
+            assert!(!raw.contains(secret), "found {secret:?} in {raw}");
+        }
+
+        assert_eq!(redacted.parts.len(), message.parts.len());
+        assert_eq!(redacted.subject(), Some("secrets"));
+        assert_eq!(redacted.from(), message.from());
+        assert_eq!(
+            redacted.body_text(0).as_deref(),
+            Some("[redacted: 27 bytes]")
+        );
+
+        let attachment = redacted.attachment(0).unwrap();
+        assert_eq!(attachment.attachment_name(), Some("salary.pdf"));
+        assert_eq!(
+            attachment.content_type(),
+            message.attachment(0).unwrap().content_type()
+        );
+        assert_eq!(attachment.contents(), b"[redacted: 15 bytes]");
+        assert!(!attachment.is_encoding_problem);
+
+        let inner = redacted.attachment(1).unwrap().message().unwrap();
+        assert_eq!(inner.subject(), Some("inner"));
+        assert_eq!(inner.body_text(0).as_deref(), Some("[redacted: 12 bytes]"));
+
+        for (part, redacted_part) in message.parts.iter().zip(redacted.parts.iter()) {
+            assert_eq!(part.is_multipart(), redacted_part.is_multipart());
+            assert_eq!(part.sub_parts(), redacted_part.sub_parts());
+        }
+    }
+}
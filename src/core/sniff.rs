@@ -0,0 +1,158 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use crate::MessagePart;
+
+// Number of bytes inspected when looking for magic numbers
+const SNIFF_LEN: usize = 512;
+
+impl MessagePart<'_> {
+    /// Detects the content type of the decoded body from its magic bytes,
+    /// regardless of the declared `Content-Type`. See [`sniff_content_type`]
+    /// for the recognized types.
+    pub fn sniff_content_type(&self) -> Option<&'static str> {
+        sniff_content_type(self.contents())
+    }
+}
+
+/// Detects the content type of `bytes` from their magic numbers, looking at
+/// no more than the first 512 bytes. Recognizes PDF, ZIP and the Office Open
+/// XML formats stored in it, legacy Office documents, PNG, JPEG, GIF, gzip,
+/// Windows PE and ELF executables. Returns `None` for unknown content.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    let bytes = &bytes[..bytes.len().min(SNIFF_LEN)];
+
+    Some(if bytes.starts_with(b"%PDF-") {
+        "application/pdf"
+    } else if bytes.starts_with(b"PK\x03\x04") {
+        if contains(bytes, b"word/") {
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        } else if contains(bytes, b"xl/") {
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        } else if contains(bytes, b"ppt/") {
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+        } else {
+            "application/zip"
+        }
+    } else if bytes.starts_with(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1") {
+        "application/x-ole-storage"
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1A\n") {
+        "image/png"
+    } else if bytes.starts_with(b"\xFF\xD8\xFF") {
+        "image/jpeg"
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if bytes.starts_with(b"\x1F\x8B") {
+        "application/gzip"
+    } else if bytes.starts_with(b"\x7FELF") {
+        "application/x-elf"
+    } else if bytes.starts_with(b"MZ") && is_pe(bytes) {
+        "application/vnd.microsoft.portable-executable"
+    } else {
+        return None;
+    })
+}
+
+// DOS stubs point to the PE header at offset 0x3C
+fn is_pe(bytes: &[u8]) -> bool {
+    bytes
+        .get(0x3C..0x40)
+        .map(|offset| u32::from_le_bytes(offset.try_into().unwrap()) as usize)
+        .and_then(|offset| bytes.get(offset..offset.checked_add(4)?))
+        .is_some_and(|signature| signature == b"PE\0\0")
+}
+
+fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+    bytes.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sniff_content_type;
+    use crate::{MessageParser, MimeHeaders};
+
+    #[test]
+    fn sniff_magic_bytes() {
+        let mut pe = [0u8; 0x80];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3C] = 0x40;
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+
+        let mut docx = b"PK\x03\x04\x14\x00\x06\x00\x08\x00".to_vec();
+        docx.extend_from_slice(&[0; 16]);
+        docx.extend_from_slice(b"\x13\x00\x00\x00[Content_Types].xml");
+        docx.extend_from_slice(b"PK\x03\x04 word/document.xml");
+
+        let mut late_marker = b"PK\x03\x04".to_vec();
+        late_marker.extend_from_slice(&[0; 600]);
+        late_marker.extend_from_slice(b"word/");
+
+        for (bytes, expected) in [
+            (&b"%PDF-1.7\n%\xE2\xE3\xCF\xD3"[..], Some("application/pdf")),
+            (
+                &docx[..],
+                Some("application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+            ),
+            (
+                b"PK\x03\x04\x14\x00xl/workbook.xml",
+                Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+            ),
+            (
+                b"PK\x03\x04\x14\x00ppt/slides/slide1.xml",
+                Some("application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+            ),
+            (b"PK\x03\x04\x14\x00notes.txt", Some("application/zip")),
+            (&late_marker[..], Some("application/zip")),
+            (
+                b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1\x00\x00",
+                Some("application/x-ole-storage"),
+            ),
+            (b"\x89PNG\r\n\x1A\n\x00\x00\x00\x0DIHDR", Some("image/png")),
+            (b"\xFF\xD8\xFF\xE0\x00\x10JFIF", Some("image/jpeg")),
+            (b"GIF89a\x01\x00\x01\x00", Some("image/gif")),
+            (b"GIF87a", Some("image/gif")),
+            (b"\x1F\x8B\x08\x00", Some("application/gzip")),
+            (b"\x7FELF\x02\x01\x01", Some("application/x-elf")),
+            (
+                &pe[..],
+                Some("application/vnd.microsoft.portable-executable"),
+            ),
+            (b"MZ without a PE header", None),
+            (b"Hello, world!", None),
+            (b"", None),
+        ] {
+            assert_eq!(sniff_content_type(bytes), expected, "failed for {bytes:?}");
+        }
+    }
+
+    #[test]
+    fn sniff_attachment_content_type() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: invoice\n",
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: text/plain\n\n",
+                "See attached.\n",
+                "--b\n",
+                "Content-Type: application/octet-stream; name=\"invoice.pdf\"\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "JVBERi0xLjcKJeLjz9MK\n",
+                "--b--\n",
+            ))
+            .unwrap();
+
+        let part = message.attachment(0).unwrap();
+        assert_eq!(part.content_type().unwrap().ctype(), "application");
+        assert_eq!(part.sniff_content_type(), Some("application/pdf"));
+        assert_eq!(message.text_part(0).unwrap().sniff_content_type(), None);
+    }
+}
@@ -13,6 +13,8 @@ use std::borrow::Cow;
 
 use crate::parsers::MessageStream;
 
+use super::DecodeBuf;
+
 #[inline(always)]
 pub fn base64_decode(bytes: &[u8]) -> Option<Vec<u8>> {
     base64_decode_stream(bytes.iter(), bytes.len(), u8::MAX)
@@ -95,7 +97,7 @@ impl<'x> MessageStream<'x> {
         let mut chunk: u32 = 0;
         let mut byte_count: u8 = 0;
 
-        let mut buf = Vec::with_capacity(self.remaining() / 4 * 3);
+        let mut buf = DecodeBuf::with_capacity(self.remaining() / 4 * 3, self.max_decoded_len);
         let mut last_ch = b'\n';
         let mut before_last_ch = 0;
         let mut end_pos = self.offset();
@@ -122,9 +124,7 @@ impl<'x> MessageStream<'x> {
 
                     if byte_count == 0 {
                         #[cfg(feature = "ludicrous_mode")]
-                        unsafe {
-                            buf.extend_from_slice(chunk.to_le_bytes().get_unchecked(0..3));
-                        }
+                        buf.extend_from_slice(unsafe { chunk.to_le_bytes().get_unchecked(0..3) });
                         #[cfg(not(feature = "ludicrous_mode"))]
                         buf.extend_from_slice(&chunk.to_le_bytes()[0..3]);
                     }
@@ -134,18 +134,16 @@ impl<'x> MessageStream<'x> {
                     b'=' => match byte_count {
                         1 | 2 => {
                             #[cfg(feature = "ludicrous_mode")]
-                            unsafe {
-                                buf.push(*chunk.to_le_bytes().get_unchecked(0));
-                            }
+                            buf.push(unsafe { *chunk.to_le_bytes().get_unchecked(0) });
                             #[cfg(not(feature = "ludicrous_mode"))]
                             buf.push(chunk.to_le_bytes()[0]);
                             byte_count = 0;
                         }
                         3 => {
                             #[cfg(feature = "ludicrous_mode")]
-                            unsafe {
-                                buf.extend_from_slice(chunk.to_le_bytes().get_unchecked(0..2));
-                            }
+                            buf.extend_from_slice(unsafe {
+                                chunk.to_le_bytes().get_unchecked(0..2)
+                            });
                             #[cfg(not(feature = "ludicrous_mode"))]
                             buf.extend_from_slice(&chunk.to_le_bytes()[0..2]);
                             byte_count = 0;
@@ -167,6 +165,8 @@ impl<'x> MessageStream<'x> {
                     b'-' => {
                         if last_ch == b'-' {
                             return if !boundary.is_empty() && self.try_skip(boundary) {
+                                self.is_decoded_truncated = buf.is_truncated();
+                                let mut buf = buf.into_vec();
                                 buf.shrink_to_fit();
                                 (
                                     if before_last_ch == b'\n' {
@@ -193,6 +193,8 @@ impl<'x> MessageStream<'x> {
             last_ch = ch;
         }
 
+        self.is_decoded_truncated = buf.is_truncated();
+        let mut buf = buf.into_vec();
         buf.shrink_to_fit();
         (
             if boundary.is_empty() {
@@ -290,6 +292,60 @@ impl<'x> MessageStream<'x> {
     }
 }
 
+// Decodes a base64 MIME part body received in chunks, producing the same
+// output as `MessageStream::decode_base64_mime` for the body without its
+// closing boundary
+#[derive(Debug, Default)]
+pub(crate) struct Base64Decoder {
+    chunk: u32,
+    byte_count: u8,
+    last_ch: u8,
+}
+
+impl Base64Decoder {
+    // Appends the decoded bytes to `buf`, returns `false` on invalid input
+    pub(crate) fn decode(&mut self, bytes: &[u8], buf: &mut Vec<u8>) -> bool {
+        for &ch in bytes {
+            let val = BASE64_MAP[self.byte_count as usize][ch as usize];
+
+            if val < 0x01ffffff {
+                self.byte_count = (self.byte_count + 1) & 3;
+
+                if self.byte_count == 1 {
+                    self.chunk = val;
+                } else {
+                    self.chunk |= val;
+
+                    if self.byte_count == 0 {
+                        buf.extend_from_slice(&self.chunk.to_le_bytes()[0..3]);
+                    }
+                }
+            } else {
+                match ch {
+                    b'=' => match self.byte_count {
+                        1 | 2 => {
+                            buf.push(self.chunk.to_le_bytes()[0]);
+                            self.byte_count = 0;
+                        }
+                        3 => {
+                            buf.extend_from_slice(&self.chunk.to_le_bytes()[0..2]);
+                            self.byte_count = 0;
+                        }
+                        0 => (),
+                        _ => return false,
+                    },
+                    b' ' | b'\t' | b'\r' | b'\n' => (),
+                    b'-' if self.last_ch != b'-' => (),
+                    _ => return false,
+                }
+            }
+            self.last_ch = ch;
+        }
+
+        true
+    }
+}
+
 /*
  * Table adapted from Nick Galbreath's "High performance base64 encoder / decoder"
  *
@@ -522,13 +578,25 @@ mod tests {
             ),
         ] {
             let mut s = MessageStream::new(encoded_str.as_bytes());
-            let (_, result) = s.decode_base64_mime(b"boundary");
+            let (bytes_read, result) = s.decode_base64_mime(b"boundary");
 
             assert_eq!(
                 result,
                 expected_result.as_bytes(),
                 "Failed for {encoded_str:?}",
             );
+
+            // Decoding stops at the limit and still finds the boundary
+            for max_len in 0..=result.len() + 1 {
+                let mut s = MessageStream::new(encoded_str.as_bytes());
+                s.max_decoded_len = max_len;
+                assert_eq!(
+                    s.decode_base64_mime(b"boundary"),
+                    (bytes_read, result[..max_len.min(result.len())].into()),
+                    "Failed for {encoded_str:?} limited to {max_len}",
+                );
+                assert_eq!(s.is_decoded_truncated, max_len < result.len());
+            }
         }
     }
 
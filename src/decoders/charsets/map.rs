@@ -17,6 +17,81 @@ use super::{
 };
 
 pub fn charset_decoder(charset: &[u8]) -> Option<DecoderFnc> {
+    charset_index(charset)
+        .or_else(|| charset_index(charset_alias(charset)?.as_bytes()))
+        .map(|hash| {
+            #[cfg(feature = "ludicrous_mode")]
+            unsafe {
+                *FNC_MAP.get_unchecked(hash)
+            }
+            #[cfg(not(feature = "ludicrous_mode"))]
+            FNC_MAP[hash]
+        })
+}
+
+/// Returns the canonical name of a charset label, for example
+/// `"windows-1252"` for `"cp1252"` or `"iso-8859-1"` for `"Latin1"`.
+/// Returns `None` if the charset is not recognized.
+pub fn canonical_charset_name(charset: &str) -> Option<&'static str> {
+    let charset = charset.trim().as_bytes();
+    if let Some(hash) = charset_index(charset) {
+        return Some(NAME_MAP[hash]);
+    }
+
+    match charset_alias(charset)?.as_str() {
+        "utf_8" => Some("utf-8"),
+        "us_ascii" => Some("us-ascii"),
+        alias => charset_index(alias.as_bytes()).map(|hash| NAME_MAP[hash]),
+    }
+}
+
+// Rewrites common charset spellings that are not part of the hash table
+fn charset_alias(charset: &[u8]) -> Option<String> {
+    if !(2..=45).contains(&charset.len()) {
+        return None;
+    }
+    let mut l_charset = [0u8; 45];
+    for (pos, ch) in charset.iter().enumerate() {
+        l_charset[pos] = match ch {
+            b'-' => b'_',
+            _ => ch.to_ascii_lowercase(),
+        };
+    }
+    let charset = std::str::from_utf8(&l_charset[..charset.len()]).ok()?;
+    let (charset, has_prefix) = charset
+        .strip_prefix("x_")
+        .map_or((charset, false), |charset| (charset, true));
+
+    match charset {
+        "utf8" | "utf_8" | "csutf8" | "unicode_1_1_utf_8" => "utf_8".to_string(),
+        "ascii" | "us_ascii" | "us" | "csascii" | "iso646_us" | "ansi_x3.4_1968" => {
+            "us_ascii".to_string()
+        }
+        "utf16" => "utf_16".to_string(),
+        "utf16le" => "utf_16le".to_string(),
+        "utf16be" => "utf_16be".to_string(),
+        "utf7" => "utf_7".to_string(),
+        "sjis" | "windows_31j" | "cswindows31j" => "shift_jis".to_string(),
+        "hz" | "hz_gb2312" | "hzgb2312" => "hz_gb_2312".to_string(),
+        _ => {
+            if let Some(code_page) = charset.strip_prefix("cp") {
+                format!("windows_{code_page}")
+            } else if let Some(part) = charset
+                .strip_prefix("iso8859")
+                .or_else(|| charset.strip_prefix("iso_8859"))
+            {
+                format!("iso_8859_{}", part.trim_start_matches('_'))
+            } else if has_prefix {
+                charset.to_string()
+            } else {
+                return None;
+            }
+        }
+    }
+    .into()
+}
+
+fn charset_index(charset: &[u8]) -> Option<usize> {
     if (2..=45).contains(&charset.len()) {
         let mut l_charset = [0u8; 45];
         let mut hash = charset.len();
@@ -71,14 +146,7 @@ pub fn charset_decoder(charset: &[u8]) -> Option<DecoderFnc> {
             };
 
             if l_charset[..charset.len()].eq(ch_map) {
-                return Some({
-                    #[cfg(feature = "ludicrous_mode")]
-                    unsafe {
-                        *FNC_MAP.get_unchecked(hash)
-                    }
-                    #[cfg(not(feature = "ludicrous_mode"))]
-                    FNC_MAP[hash]
-                });
+                return Some(hash);
             }
         }
     }
@@ -479,7 +547,7 @@ static CH_MAP: &[&[u8]; 545] = &[
     b"",
     b"",
     b"",
-    b"",
+    b"hz_gb_2312",
     b"",
     b"",
     b"",
@@ -1027,7 +1095,7 @@ static FNC_MAP: &[for<'x> fn(&'x [u8]) -> String; 545] = &[
     no_op,
     no_op,
     no_op,
-    no_op,
+    decoder_hz,
     no_op,
     no_op,
     no_op,
@@ -1204,9 +1272,558 @@ static FNC_MAP: &[for<'x> fn(&'x [u8]) -> String; 545] = &[
     decoder_ibm_850,
 ];
 
+// Canonical charset names, indexed by the same hash as CH_MAP
+static NAME_MAP: &[&str; 545] = &[
+    "",
+    "",
+    "iso-8859-1",
+    "",
+    "",
+    "",
+    "iso-8859-1",
+    "iso-8859-14",
+    "",
+    "",
+    "",
+    "iso-8859-14",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-4",
+    "",
+    "",
+    "gbk",
+    "iso-8859-4",
+    "",
+    "gbk",
+    "",
+    "",
+    "",
+    "iso-8859-9",
+    "",
+    "",
+    "",
+    "iso-8859-9",
+    "",
+    "",
+    "",
+    "iso-8859-7",
+    "iso-8859-7",
+    "",
+    "",
+    "",
+    "",
+    "big5",
+    "iso-8859-10",
+    "",
+    "",
+    "iso-8859-1",
+    "iso-8859-10",
+    "",
+    "",
+    "big5",
+    "ibm866",
+    "",
+    "",
+    "",
+    "",
+    "shift_jis",
+    "",
+    "iso-8859-3",
+    "",
+    "",
+    "ibm850",
+    "iso-8859-3",
+    "",
+    "",
+    "",
+    "iso-8859-9",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-14",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "euc-kr",
+    "iso-8859-15",
+    "",
+    "",
+    "gbk",
+    "",
+    "iso-8859-2",
+    "",
+    "shift_jis",
+    "iso-8859-5",
+    "iso-8859-2",
+    "",
+    "ibm866",
+    "",
+    "iso-8859-2",
+    "euc-jp",
+    "",
+    "iso-8859-5",
+    "",
+    "",
+    "koi8-r",
+    "",
+    "",
+    "",
+    "iso-8859-1",
+    "iso-8859-11",
+    "",
+    "iso-8859-16",
+    "",
+    "iso-8859-8",
+    "iso-8859-16",
+    "iso-8859-16",
+    "ibm850",
+    "",
+    "iso-8859-8",
+    "iso-8859-14",
+    "tis-620",
+    "iso-8859-7",
+    "",
+    "iso-8859-8",
+    "iso-8859-14",
+    "gb18030",
+    "",
+    "",
+    "",
+    "iso-8859-14",
+    "koi8-r",
+    "",
+    "",
+    "iso-8859-4",
+    "iso-8859-15",
+    "",
+    "windows-1251",
+    "",
+    "iso-8859-4",
+    "iso-8859-15",
+    "euc-kr",
+    "windows-1258",
+    "",
+    "iso-8859-5",
+    "iso-8859-8",
+    "",
+    "",
+    "",
+    "iso-8859-4",
+    "iso-8859-16",
+    "",
+    "windows-1254",
+    "euc-kr",
+    "iso-8859-14",
+    "iso-8859-16",
+    "windows-874",
+    "",
+    "",
+    "iso-8859-5",
+    "iso-8859-10",
+    "windows-1251",
+    "windows-1255",
+    "",
+    "",
+    "iso-8859-13",
+    "windows-1258",
+    "ibm866",
+    "",
+    "iso-8859-3",
+    "iso-8859-13",
+    "",
+    "",
+    "",
+    "iso-8859-3",
+    "",
+    "windows-1254",
+    "windows-1256",
+    "",
+    "iso-8859-9",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-9",
+    "gb2312",
+    "windows-1255",
+    "windows-1250",
+    "",
+    "iso-8859-6",
+    "",
+    "",
+    "windows-1253",
+    "",
+    "",
+    "iso-8859-10",
+    "",
+    "tis-620",
+    "",
+    "iso-8859-1",
+    "windows-874",
+    "windows-1256",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-7",
+    "gbk",
+    "windows-1250",
+    "",
+    "",
+    "iso-8859-1",
+    "iso-8859-1",
+    "windows-1253",
+    "windows-1252",
+    "euc-kr",
+    "iso-8859-3",
+    "iso-8859-6",
+    "",
+    "iso-8859-5",
+    "",
+    "iso-8859-7",
+    "koi8-u",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-4",
+    "",
+    "",
+    "gb18030",
+    "",
+    "",
+    "",
+    "windows-1257",
+    "",
+    "",
+    "iso-8859-9",
+    "windows-1252",
+    "macintosh",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-6",
+    "iso-8859-10",
+    "",
+    "utf-16le",
+    "",
+    "",
+    "",
+    "windows-1257",
+    "",
+    "utf-16le",
+    "",
+    "utf-16",
+    "",
+    "utf-16be",
+    "",
+    "iso-8859-2",
+    "iso-8859-3",
+    "utf-16",
+    "iso-8859-7",
+    "utf-16be",
+    "iso-8859-10",
+    "iso-8859-8",
+    "",
+    "",
+    "",
+    "euc-jp",
+    "iso-2022-jp",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-6",
+    "",
+    "",
+    "",
+    "iso-8859-16",
+    "utf-7",
+    "",
+    "iso-8859-6",
+    "",
+    "iso-8859-2",
+    "iso-8859-2",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "shift_jis",
+    "",
+    "iso-8859-7",
+    "iso-8859-1",
+    "",
+    "",
+    "",
+    "iso-8859-7",
+    "ibm866",
+    "",
+    "",
+    "",
+    "gbk",
+    "macintosh",
+    "",
+    "",
+    "",
+    "utf-7",
+    "ibm850",
+    "",
+    "",
+    "",
+    "iso-8859-6",
+    "iso-2022-jp",
+    "",
+    "",
+    "euc-jp",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "koi8-u",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "hz-gb-2312",
+    "",
+    "",
+    "",
+    "macintosh",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "iso-8859-6",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "ibm850",
+];
+
 #[cfg(test)]
 mod tests {
-    use super::{charset_decoder, CH_MAP};
+    use super::{canonical_charset_name, charset_decoder, CH_MAP};
 
     #[test]
     fn decoder_charset() {
@@ -1220,4 +1837,40 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn canonical_charset() {
+        for (input, expected) in [
+            ("utf-8", Some("utf-8")),
+            ("UTF8", Some("utf-8")),
+            ("utf_8", Some("utf-8")),
+            ("US-ASCII", Some("us-ascii")),
+            ("latin1", Some("iso-8859-1")),
+            ("iso8859-1", Some("iso-8859-1")),
+            ("ISO-8859-1", Some("iso-8859-1")),
+            ("iso_8859-15", Some("iso-8859-15")),
+            ("cp1252", Some("windows-1252")),
+            ("x-cp1251", Some("windows-1251")),
+            ("Windows-1252", Some("windows-1252")),
+            ("ks_c_5601-1987", Some("euc-kr")),
+            ("gb2312", Some("gb2312")),
+            ("x-sjis", Some("shift_jis")),
+            ("HZ-GB-2312", Some("hz-gb-2312")),
+            ("x-hz", Some("hz-gb-2312")),
+            ("utf16le", Some("utf-16le")),
+            ("cp850", Some("ibm850")),
+            ("x-unknown", None),
+            ("cp99999", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                canonical_charset_name(input),
+                expected,
+                "Failed for {input}"
+            );
+        }
+
+        assert!(charset_decoder(b"cp1252").is_some());
+        assert!(charset_decoder(b"iso8859-1").is_some());
+    }
 }
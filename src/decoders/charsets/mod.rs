@@ -38,10 +38,25 @@ mod tests {
             ("windows-1256", b"\xe3\xd1\xcd\xc8\xc7 \xc8\xc7\xe1\xda\xc7\xe1\xe3".to_vec(),"مرحبا بالعالم"),
             ("windows-1257", b"Mu h\xf5ljuk on angerjaid t\xe4is".to_vec(),"Mu hõljuk on angerjaid täis"),
             ("windows-1258", b"Xin ch\xe0o".to_vec(),"Xin chào"),
+            ("windows-1250", b"\x8a\x8d\x8e\xb9".to_vec(),"ŠŤŽą"),
+            ("windows-1251", b"\xc0\x80\xa8\xff".to_vec(),"АЂЁя"),
+            ("cp1251", b"\xc0".to_vec(),"А"),
+            ("windows-1252", b"\x80\x9f\x99".to_vec(),"€Ÿ™"),
+            ("windows-1253", b"\xa2\xc1\xf9".to_vec(),"ΆΑω"),
+            ("windows-1254", b"\xd0\xdd\xfe".to_vec(),"Ğİş"),
+            ("windows-1255", b"\xe0\xfa\xa4".to_vec(),"את₪"),
+            ("windows-1256", b"\xc7\x81\xff".to_vec(),"اپے"),
+            ("windows-1257", b"\xe0\xd0\xfe".to_vec(),"ąŠž"),
+            ("windows-1258", b"\xfe\xde\xd2".to_vec(),"₫\u{303}\u{309}"),
+            ("cp1258", b"\xfe".to_vec(),"₫"),
             ("macintosh", b"\x87\x8e\x92\x97\x9c".to_vec(),"áéíóú"),
             ("ibm850", b"\x9b\x9c\x9d\x9e".to_vec(),"ø£Ø×"),
             ("koi8-r", b"\xf0\xd2\xc9\xd7\xc5\xd4, \xcd\xc9\xd2".to_vec(),"Привет, мир"),
             ("koi8-u", b"\xf0\xd2\xc9\xd7\xa6\xd4 \xf3\xd7\xa6\xd4".to_vec(),"Привіт Світ"),
+            ("koi8-r", b"\x80\x81\x82\x9f\xa0\xbf\xd0\xa3\xb3".to_vec(),"─│┌÷═©пёЁ"),
+            ("KOI8-R", b"\xd0".to_vec(),"п"),
+            ("cskoi8r", b"\xf0\xd2\xc9\xd7\xc5\xd4".to_vec(),"Привет"),
+            ("koi8-u", b"\xa4\xb4\xa6\xb6\xa7\xb7\xad\xbd\xa5".to_vec(),"єЄіІїЇґҐ╔"),
             ("utf-7", b"+ZYeB9FH6ckh5Pg-, 1980.".to_vec(),"文致出版社, 1980."),
             ("utf-16le", b"\xcf0\xed0\xfc0\xfb0\xef0\xfc0\xeb0\xc90".to_vec(),"ハロー・ワールド"),
             ("utf-16be", b"0\xcf0\xed0\xfc0\xfb0\xef0\xfc0\xeb0\xc9".to_vec(),"ハロー・ワールド"),
@@ -62,11 +77,23 @@ mod tests {
             ("gbk", b"\xc4\xe3\xba\xc3\xa3\xac\xca\xc0\xbd\xe7".to_vec(),"你好，世界"),
             #[cfg(feature = "full_encoding")]
             ("gb18030", b"\xc4\xe3\xba\xc3\xa3\xac\xca\xc0\xbd\xe7".to_vec(),"你好，世界"),
+            #[cfg(feature = "full_encoding")]
+            ("hz-gb-2312", b"~{Dc:C#,J@=g~}".to_vec(),"你好，世界"),
+            #[cfg(feature = "full_encoding")]
+            ("hz-gb-2312", b"In GB:~{<:Ky2;S{#,NpJ)l6HK!#~}Bye.".to_vec(),"In GB:己所不欲，勿施於人。Bye."),
+            #[cfg(feature = "full_encoding")]
+            ("HZ", b"~~ tilde ~\nwrapped ~x".to_vec(),"~ tilde wrapped ~x"),
+            #[cfg(feature = "full_encoding")]
+            ("hz-gb-2312", b"~{Dc:C".to_vec(),"你好"), // Unterminated shift
+            #[cfg(feature = "full_encoding")]
+            ("hz-gb-2312", b"~{Dc:".to_vec(),"你\u{fffd}"),
+            #[cfg(feature = "full_encoding")]
+            ("hz-gb-2312", b"~{Dc\nabc".to_vec(),"你\nabc"),
             ];
 
         for input in inputs {
             let decoder = charset_decoder(input.0.as_bytes())
-                .expect(&("Failed to find decoder for ".to_owned() + input.0));
+                .unwrap_or_else(|| panic!("Failed to find decoder for {}", input.0));
 
             assert_eq!(decoder(&input.1), input.2);
         }
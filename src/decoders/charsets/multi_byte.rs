@@ -131,3 +131,74 @@ pub fn decoder_ibm866(bytes: &[u8]) -> String {
         String::from_utf8_lossy(bytes).into_owned()
     }
 }
+
+// HZ (RFC 1843) wraps GB2312 text, with the high bits cleared, between the
+// "~{" and "~}" shift sequences. The text is rebuilt as GBK, a superset of
+// GB2312, before decoding.
+pub fn decoder_hz(bytes: &[u8]) -> String {
+    #[cfg(feature = "full_encoding")]
+    {
+        let mut gbk = Vec::with_capacity(bytes.len());
+        let mut is_gb = false;
+        let mut iter = bytes.iter().peekable();
+
+        while let Some(&ch) = iter.next() {
+            match (ch, is_gb) {
+                (b'~', _) => match iter.peek() {
+                    Some(b'{') if !is_gb => {
+                        iter.next();
+                        is_gb = true;
+                    }
+                    Some(b'}') if is_gb => {
+                        iter.next();
+                        is_gb = false;
+                    }
+                    Some(b'~') if !is_gb => {
+                        iter.next();
+                        gbk.push(b'~');
+                    }
+                    Some(b'\n') if !is_gb => {
+                        iter.next();
+                    }
+                    Some(b'\r') if !is_gb => {
+                        iter.next();
+                        iter.next_if_eq(&&b'\n');
+                    }
+                    _ if is_gb => {
+                        gbk.push(0xFF);
+                    }
+                    _ => {
+                        gbk.push(b'~');
+                    }
+                },
+                (b'\r' | b'\n', true) => {
+                    // Line breaks are not allowed in GB mode, resync to ASCII
+                    gbk.push(ch);
+                    is_gb = false;
+                }
+                (0x21..=0x7E, true) => match iter.next_if(|ch| (0x21..=0x7E).contains(*ch)) {
+                    Some(&trail) => {
+                        gbk.push(ch | 0x80);
+                        gbk.push(trail | 0x80);
+                    }
+                    None => {
+                        gbk.push(0xFF);
+                    }
+                },
+                (_, true) => {
+                    gbk.push(0xFF);
+                }
+                (_, false) => {
+                    gbk.push(ch);
+                }
+            }
+        }
+
+        multi_byte_decoder(GBK.new_decoder(), &gbk)
+    }
+
+    #[cfg(not(feature = "full_encoding"))]
+    {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
@@ -9,7 +9,9 @@
  * except according to those terms.
  */
 
-use crate::{decoders::charsets::map::charset_decoder, parsers::MessageStream};
+use crate::{
+    decoders::charsets::map::charset_decoder, parsers::MessageStream, EncodedWord, Encoding,
+};
 
 use super::DecodeWordFnc;
 
@@ -23,6 +25,8 @@ enum Rfc2047State {
 impl MessageStream<'_> {
     pub fn decode_rfc2047(&mut self) -> Option<String> {
         let mut state = Rfc2047State::Init;
+        let offset_start = self.offset().saturating_sub(1);
+        let mut encoding = Encoding::None;
 
         let mut charset_start = 0;
         let mut charset_end = 0;
@@ -48,10 +52,8 @@ impl MessageStream<'_> {
                         }
                         state = Rfc2047State::Encoding;
                     }
-                    b'*' => {
-                        if charset_end == charset_start {
-                            charset_end = self.offset() - 1;
-                        }
+                    b'*' if charset_end == charset_start => {
+                        charset_end = self.offset() - 1;
                     }
                     b'\n' => {
                         return None;
@@ -61,9 +63,13 @@ impl MessageStream<'_> {
                 Rfc2047State::Encoding => {
                     match ch {
                         b'q' | b'Q' => {
-                            decode_fnc = Some(MessageStream::decode_quoted_printable_word)
+                            decode_fnc = Some(MessageStream::decode_quoted_printable_word);
+                            encoding = Encoding::QuotedPrintable;
+                        }
+                        b'b' | b'B' => {
+                            decode_fnc = Some(MessageStream::decode_base64_word);
+                            encoding = Encoding::Base64;
                         }
-                        b'b' | b'B' => decode_fnc = Some(MessageStream::decode_base64_word),
                         _ => {
                             return None;
                         }
@@ -81,6 +87,18 @@ impl MessageStream<'_> {
         }
 
         if let Some(bytes) = decode_fnc.and_then(|fnc| fnc(self)) {
+            if self.encoded_words.is_some() {
+                let encoded_word = EncodedWord {
+                    charset: String::from_utf8_lossy(self.bytes(charset_start..charset_end)),
+                    encoding,
+                    offset_start,
+                    offset_end: self.offset(),
+                };
+                if let Some(encoded_words) = &mut self.encoded_words {
+                    encoded_words.push(encoded_word);
+                }
+            }
+
             if let Some(decoder) = charset_decoder(self.bytes(charset_start..charset_end)) {
                 decoder(&bytes).into()
             } else {
@@ -95,7 +113,7 @@ impl MessageStream<'_> {
 }
 #[cfg(test)]
 mod tests {
-    use crate::parsers::MessageStream;
+    use crate::{parsers::MessageStream, MessageParser};
 
     #[test]
     fn decode_rfc2047() {
@@ -176,4 +194,35 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn long_encoded_word_subject() {
+        const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let subject = "長い件名のテスト".repeat(300) + "!";
+        let mut encoded = String::new();
+        for chunk in subject.as_bytes().chunks(3) {
+            let b = [
+                chunk[0],
+                chunk.get(1).copied().unwrap_or(0),
+                chunk.get(2).copied().unwrap_or(0),
+            ];
+            let n = ((b[0] as usize) << 16) | ((b[1] as usize) << 8) | b[2] as usize;
+            for (i, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+                encoded.push(if i <= chunk.len() {
+                    BASE64[(n >> shift) & 0x3f] as char
+                } else {
+                    '='
+                });
+            }
+        }
+        assert!(encoded.len() > 8192);
+
+        for input in [
+            format!("Subject: =?utf-8?B?{encoded}?=\n\nbody"),
+            format!("Subject: =?UTF-8?b?{encoded}?=\r\n\r\nbody"),
+        ] {
+            let message = MessageParser::default().parse(&input).unwrap();
+            assert_eq!(message.subject(), Some(subject.as_str()));
+        }
+    }
 }
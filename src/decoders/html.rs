@@ -276,6 +276,145 @@ pub fn text_to_html(input: &str) -> String {
     String::from_utf8(result).unwrap()
 }
 
+/// Extracts the first `max_chars` visible characters of an HTML document,
+/// stripping tags, decoding entities and collapsing whitespace. Unlike
+/// [`html_to_text`], scanning stops as soon as enough text has been found,
+/// and the contents of `<head>`, `<script>` and `<style>` elements are skipped.
+pub fn html_snippet(input: &str, max_chars: usize) -> String {
+    let input = input.as_bytes();
+    let mut result = String::with_capacity(std::cmp::min(max_chars * 2, input.len()));
+    let mut char_count = 0;
+    let mut add_space = false;
+    let mut pos = 0;
+
+    while pos < input.len() && char_count < max_chars {
+        match input[pos] {
+            b'<' => {
+                if let Some(b"!--") = input.get(pos + 1..pos + 4) {
+                    pos = find_bytes(input, pos + 4, b"-->").map_or(input.len(), |p| p + 3);
+                    continue;
+                }
+
+                let tag_end = input[pos..]
+                    .iter()
+                    .position(|&ch| ch == b'>')
+                    .map_or(input.len(), |p| pos + p + 1);
+                let tag = input[pos + 1..tag_end].trim_ascii_start();
+                let (is_tag_close, tag) = match tag {
+                    [b'/', tag @ ..] => (true, tag.trim_ascii_start()),
+                    _ => (false, tag),
+                };
+                let name = &tag[..tag
+                    .iter()
+                    .position(|ch| !ch.is_ascii_alphanumeric())
+                    .unwrap_or(tag.len())];
+                pos = tag_end;
+
+                if !is_tag_close
+                    && [&b"head"[..], b"script", b"style"]
+                        .iter()
+                        .any(|skip| name.eq_ignore_ascii_case(skip))
+                {
+                    // Skip until the matching closing tag
+                    while let Some(close_pos) = find_bytes(input, pos, b"</") {
+                        pos = close_pos + 2;
+                        if input
+                            .get(pos..pos + name.len())
+                            .is_some_and(|close| close.eq_ignore_ascii_case(name))
+                        {
+                            break;
+                        }
+                    }
+                    pos = input[pos..]
+                        .iter()
+                        .position(|&ch| ch == b'>')
+                        .map_or(input.len(), |p| pos + p + 1);
+                } else if !name.is_empty() {
+                    // Tags act as word separators, except for inline formatting
+                    add_space |= ![
+                        &b"a"[..],
+                        b"b",
+                        b"i",
+                        b"u",
+                        b"em",
+                        b"strong",
+                        b"span",
+                        b"font",
+                        b"small",
+                        b"big",
+                        b"sub",
+                        b"sup",
+                        b"code",
+                    ]
+                    .iter()
+                    .any(|inline| name.eq_ignore_ascii_case(inline));
+                }
+            }
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                add_space = true;
+                pos += 1;
+            }
+            ch => {
+                let token_end = if ch == b'&' {
+                    input[pos + 1..]
+                        .iter()
+                        .take(32)
+                        .position(|&ch| ch == b';' || ch == b'<' || ch.is_ascii_whitespace())
+                        .filter(|&p| input[pos + 1 + p] == b';')
+                        .map_or(pos + 1, |p| pos + p + 2)
+                } else {
+                    pos + utf8_len(ch)
+                };
+                let token = input.get(pos..token_end).unwrap_or(&input[pos..]);
+                pos += token.len();
+
+                if add_space && !result.is_empty() {
+                    result.push(' ');
+                    char_count += 1;
+                    if char_count == max_chars {
+                        break;
+                    }
+                }
+                add_space = false;
+
+                if ch == b'&' {
+                    let token_start = result.len();
+                    add_html_token(&mut result, token, false);
+                    for (pos, _) in result[token_start..].char_indices() {
+                        if char_count == max_chars {
+                            result.truncate(token_start + pos);
+                            break;
+                        }
+                        char_count += 1;
+                    }
+                } else {
+                    result.push_str(std::str::from_utf8(token).unwrap_or("\u{fffd}"));
+                    char_count += 1;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn find_bytes(input: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    input
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+fn utf8_len(first_byte: u8) -> usize {
+    match first_byte {
+        0xF0..=0xF7 => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
+
 static ENTITY_HASH: &[u32; 260] = &[
     18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080,
     18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080, 18080,
@@ -1000,7 +1139,7 @@ static ENTITY_MAP: &[u32; 18016] = &[
 #[cfg(test)]
 mod tests {
 
-    use crate::decoders::html::{add_html_token, html_to_text, text_to_html};
+    use crate::decoders::html::{add_html_token, html_snippet, html_to_text, text_to_html};
 
     #[test]
     fn convert_text_to_html() {
@@ -1061,6 +1200,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn html_to_snippet() {
+        for (html, max_chars, expected) in [
+            ("<p>Hello,<br>world!</p>", 100, "Hello, world!"),
+            ("<p>Hello, <b>wor</b>ld!</p>", 100, "Hello, world!"),
+            ("<p>Hello, world!</p>", 5, "Hello"),
+            ("<p>Hello,</p><p>world!</p>", 7, "Hello, "),
+            (
+                concat!(
+                    "<html><head><title>Title</title><style>p { color: red; }</style></head>",
+                    "<body><script type=\"text/javascript\">var a = \"<p>x</p>\";</script>",
+                    "<!-- comment <p> -->   Dear   customer,\r\n\r\n\t your order</body></html>"
+                ),
+                100,
+                "Dear customer, your order",
+            ),
+            ("<SCRIPT>alert(1)</ScRiPt >visible", 100, "visible"),
+            (
+                "caf&eacute; &amp; cr&#xE8;me &hmmm; &",
+                100,
+                "café & crème &hmmm; &",
+            ),
+            ("a &hmmm; b", 5, "a &hm"),
+            ("長沮、<i>桀溺</i>耦而耕", 4, "長沮、桀"),
+            ("<p>unterminated <scr", 100, "unterminated"),
+            ("<style>never closed", 100, ""),
+            ("anything", 0, ""),
+        ] {
+            assert_eq!(html_snippet(html, max_chars), expected, "{html:?}");
+        }
+    }
+
     #[test]
     fn convert_html_entity() {
         let inputs = [
@@ -22,3 +22,68 @@ pub mod quoted_printable;
 
 pub type DecodeFnc<'x> = fn(&mut MessageStream<'x>, &[u8]) -> (usize, Cow<'x, [u8]>);
 pub type DecodeWordFnc<'x> = fn(&mut MessageStream<'x>) -> Option<Vec<u8>>;
+
+// Output buffer of the MIME part decoders that keeps at most `max_len` bytes.
+// Bytes past the limit are only counted, so that the trailing bytes removed
+// by the decoders are taken from the discarded ones first.
+pub(crate) struct DecodeBuf {
+    buf: Vec<u8>,
+    max_len: usize,
+    overflow: usize,
+}
+
+impl DecodeBuf {
+    pub(crate) fn with_capacity(capacity: usize, max_len: usize) -> Self {
+        DecodeBuf {
+            buf: Vec::with_capacity(std::cmp::min(capacity, max_len)),
+            max_len,
+            overflow: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn push(&mut self, ch: u8) {
+        if self.overflow == 0 && self.buf.len() < self.max_len {
+            self.buf.push(ch);
+        } else {
+            self.overflow += 1;
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn extend_from_slice(&mut self, bytes: &[u8]) {
+        if self.overflow == 0 && self.buf.len() + bytes.len() <= self.max_len {
+            self.buf.extend_from_slice(bytes);
+        } else {
+            let len = if self.overflow == 0 {
+                self.max_len - self.buf.len()
+            } else {
+                0
+            };
+            self.buf.extend_from_slice(&bytes[..len]);
+            self.overflow += bytes.len() - len;
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        self.buf.len() + self.overflow
+    }
+
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if len >= self.buf.len() {
+            self.overflow = std::cmp::min(self.overflow, len - self.buf.len());
+        } else {
+            self.overflow = 0;
+            self.buf.truncate(len);
+        }
+    }
+
+    pub(crate) fn is_truncated(&self) -> bool {
+        self.overflow > 0
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<u8> {
+        self.buf
+    }
+}
@@ -13,6 +13,8 @@ use std::borrow::Cow;
 
 use crate::parsers::MessageStream;
 
+use super::DecodeBuf;
+
 #[derive(PartialEq, Debug)]
 enum QuotedPrintableState {
     None,
@@ -27,8 +29,9 @@ pub fn quoted_printable_decode(bytes: &[u8]) -> Option<Vec<u8>> {
     let mut hex1 = 0;
     let mut ws_count = 0;
     let mut crlf = b"\n".as_ref();
+    let mut iter = bytes.iter().peekable();
 
-    for &ch in bytes {
+    while let Some(&ch) = iter.next() {
         match ch {
             b'=' => {
                 if let QuotedPrintableState::None = state {
@@ -48,9 +51,21 @@ pub fn quoted_printable_decode(bytes: &[u8]) -> Option<Vec<u8>> {
                 }
                 ws_count = 0;
             }
-            b'\r' => {
+            b'\r' if iter.peek() == Some(&&b'\n') => {
                 crlf = b"\r\n".as_ref();
             }
+            b'\r' => {
+                // Bare CR line ending
+                if QuotedPrintableState::Eq == state {
+                    state = QuotedPrintableState::None;
+                } else {
+                    if ws_count > 0 {
+                        buf.truncate(buf.len() - ws_count);
+                    }
+                    buf.push(b'\r');
+                }
+                ws_count = 0;
+            }
             _ => match state {
                 QuotedPrintableState::None => {
                     if ch.is_ascii_whitespace() {
@@ -123,7 +138,7 @@ pub fn quoted_printable_decode_char(hex1: u8, hex2: u8) -> Option<u8> {
 
 impl<'x> MessageStream<'x> {
     pub fn decode_quoted_printable_mime(&mut self, boundary: &[u8]) -> (usize, Cow<'x, [u8]>) {
-        let mut buf = Vec::with_capacity(128);
+        let mut buf = DecodeBuf::with_capacity(128, self.max_decoded_len);
 
         let mut state = QuotedPrintableState::None;
         let mut hex1 = 0;
@@ -161,9 +176,22 @@ impl<'x> MessageStream<'x> {
                     }
                     ws_count = 0;
                 }
-                b'\r' => {
+                b'\r' if self.peek_char(b'\n') => {
                     crlf = b"\r\n".as_ref();
                 }
+                b'\r' => {
+                    // Bare CR line ending
+                    end_pos = self.offset() - 1;
+                    if QuotedPrintableState::Eq == state {
+                        state = QuotedPrintableState::None;
+                    } else {
+                        if ws_count > 0 {
+                            buf.truncate(buf.len() - ws_count);
+                        }
+                        buf.push(b'\r');
+                    }
+                    ws_count = 0;
+                }
                 b'-' if !boundary.is_empty() && last_ch == b'-' && self.try_skip(boundary) => {
                     if before_last_ch == b'\n' {
                         buf.truncate(buf.len() - (crlf.len() + 1));
@@ -172,7 +200,8 @@ impl<'x> MessageStream<'x> {
                         end_pos = self.offset() - boundary.len() - 2;
                     }
 
-                    return (end_pos, buf.into());
+                    self.is_decoded_truncated = buf.is_truncated();
+                    return (end_pos, buf.into_vec().into());
                 }
                 _ => match state {
                     QuotedPrintableState::None => {
@@ -221,6 +250,7 @@ impl<'x> MessageStream<'x> {
             last_ch = ch;
         }
 
+        self.is_decoded_truncated = buf.is_truncated();
         (
             if boundary.is_empty() {
                 self.offset()
@@ -228,7 +258,7 @@ impl<'x> MessageStream<'x> {
                 self.restore();
                 usize::MAX
             },
-            buf.into(),
+            buf.into_vec().into(),
         )
     }
 
@@ -313,6 +343,118 @@ impl<'x> MessageStream<'x> {
     }
 }
 
+// Decodes a quoted-printable MIME part body received in chunks, producing the
+// same output as `MessageStream::decode_quoted_printable_mime` for the body
+// without its closing boundary. Whitespace is held back until it is known
+// whether it ends a line.
+#[derive(Debug)]
+pub(crate) struct QuotedPrintableDecoder {
+    state: QuotedPrintableState,
+    hex1: i8,
+    whitespace: Vec<u8>,
+    is_crlf: bool,
+    is_cr: bool,
+}
+
+impl QuotedPrintableDecoder {
+    pub(crate) fn new() -> Self {
+        QuotedPrintableDecoder {
+            state: QuotedPrintableState::None,
+            hex1: 0,
+            whitespace: Vec::new(),
+            is_crlf: false,
+            is_cr: false,
+        }
+    }
+
+    // Appends the decoded bytes to `buf`, returns `false` on invalid input
+    pub(crate) fn decode(&mut self, bytes: &[u8], buf: &mut Vec<u8>) -> bool {
+        for &ch in bytes {
+            if self.is_cr {
+                self.is_cr = false;
+                if ch == b'\n' {
+                    self.is_crlf = true;
+                } else {
+                    // Bare CR line ending
+                    self.line_break(b"\r", buf);
+                }
+            }
+
+            match ch {
+                b'=' => {
+                    if let QuotedPrintableState::None = self.state {
+                        self.state = QuotedPrintableState::Eq;
+                    } else {
+                        return false;
+                    }
+                }
+                b'\n' => {
+                    self.line_break(if self.is_crlf { b"\r\n" } else { b"\n" }, buf);
+                }
+                b'\r' => {
+                    self.is_cr = true;
+                }
+                _ => match self.state {
+                    QuotedPrintableState::None => {
+                        if ch.is_ascii_whitespace() {
+                            self.whitespace.push(ch);
+                        } else {
+                            buf.append(&mut self.whitespace);
+                            buf.push(ch);
+                        }
+                    }
+                    QuotedPrintableState::Eq => {
+                        self.hex1 = HEX_MAP[ch as usize];
+                        if self.hex1 != -1 {
+                            self.state = QuotedPrintableState::Hex1;
+                        } else if !ch.is_ascii_whitespace() {
+                            return false;
+                        }
+                    }
+                    QuotedPrintableState::Hex1 => {
+                        let hex2 = HEX_MAP[ch as usize];
+
+                        self.state = QuotedPrintableState::None;
+                        if hex2 != -1 {
+                            buf.append(&mut self.whitespace);
+                            buf.push(((self.hex1 as u8) << 4) | hex2 as u8);
+                        } else {
+                            return false;
+                        }
+                    }
+                },
+            }
+        }
+
+        true
+    }
+
+    // Writes the pending bytes once the body ends, `is_line_end` tells whether
+    // the body is followed by the line break preceding its boundary
+    pub(crate) fn finish(&mut self, is_line_end: bool, buf: &mut Vec<u8>) {
+        if self.is_cr {
+            self.is_cr = false;
+            self.line_break(b"\r", buf);
+        }
+        if is_line_end && self.state != QuotedPrintableState::Eq {
+            self.whitespace.clear();
+        } else {
+            buf.append(&mut self.whitespace);
+        }
+    }
+
+    fn line_break(&mut self, line_break: &[u8], buf: &mut Vec<u8>) {
+        if self.state == QuotedPrintableState::Eq {
+            // Soft line break
+            self.state = QuotedPrintableState::None;
+            buf.append(&mut self.whitespace);
+        } else {
+            self.whitespace.clear();
+            buf.extend_from_slice(line_break);
+        }
+    }
+}
+
 /*
  * Adapted from Daniel Lemire's source:
  * https://github.com/lemire/Code-used-on-Daniel-Lemire-s-blog/blob/master/2019/04/17/hexparse.cpp
@@ -383,6 +525,11 @@ mod tests {
                 "hello\r\nbar\r\nfoo\tbar\r\nfoo\t \tb\r\nfoo bar\r\nfoo b\r\nfoo\r\nbar\r\nfoo_bar\r\n",
             ),
             ("\n\n", "\n\n"),
+            ("foo=\nbar", "foobar"),
+            ("foo=\r\nbar", "foobar"),
+            ("foo=\rbar", "foobar"),
+            ("foo=\rbar=3D\rbaz \r", "foobar=\rbaz\r"),
+            ("foo=\n=\r=\r\nbar\n", "foobar\r\n"),
         ] {
             assert_eq!(
                 String::from_utf8(super::quoted_printable_decode(encoded_str.as_bytes()).unwrap_or_default()).unwrap(),
@@ -449,6 +596,9 @@ mod tests {
                 ),
                 "hello\r\nbar\r\nfoo\tbar\r\nfoo\t \tb\r\nfoo bar\r\nfoo b\r\nfoo\r\nbar\r\nfoo_bar\r\n",
             ),
+            ("foo=\nbar\n--boundary", "foobar"),
+            ("foo=\rbar=\r\nbaz\r\n--boundary", "foobarbaz"),
+            ("foo=\rbar\rbaz\n--boundary", "foobar\rbaz"),
         ] {
             let mut s = MessageStream::new(encoded_str.as_bytes());
             let (bytes_read, result) = s.decode_quoted_printable_mime(b"boundary");
@@ -458,6 +608,19 @@ mod tests {
                 expected_result,
                 "Failed for {encoded_str:?}",
             );
+
+            // Decoding stops at the limit, trailing whitespace and line breaks
+            // removed past the limit do not affect the kept contents
+            for max_len in 0..=result.len() + 1 {
+                let mut s = MessageStream::new(encoded_str.as_bytes());
+                s.max_decoded_len = max_len;
+                assert_eq!(
+                    s.decode_quoted_printable_mime(b"boundary"),
+                    (bytes_read, result[..max_len.min(result.len())].into()),
+                    "Failed for {encoded_str:?} limited to {max_len}",
+                );
+                assert_eq!(s.is_decoded_truncated, max_len < result.len());
+            }
         }
     }
 
@@ -261,13 +261,136 @@ use serde::{Deserialize, Serialize};
 
 /// RFC5322/RFC822 message parser.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[allow(unpredictable_function_pointer_comparisons)]
 pub struct MessageParser {
     pub(crate) header_map: HashMap<HeaderName<'static>, HdrParseFnc>,
     pub(crate) def_hdr_parse_fnc: HdrParseFnc,
+    pub(crate) comment_display_names: bool,
+    pub(crate) lenient_address_names: bool,
+    pub(crate) lenient_attribute_values: bool,
+    pub(crate) max_attribute_name_len: usize,
+    pub(crate) max_attribute_value_len: usize,
+    pub(crate) max_attribute_continuations: usize,
+    pub(crate) transfer_encoding_policy: TransferEncodingPolicy,
+    pub(crate) content_type_policy: ContentTypePolicy,
+    pub(crate) limits: ParserLimits,
+    pub(crate) unknown_header_fnc: Option<UnknownHeaderFnc>,
+}
+
+/// Limits on the resources used to parse a message, see
+/// [`MessageParser::with_limits`]. The default limits preserve the unlimited
+/// behavior. Reaching a limit flags the message as truncated, see
+/// [`Message::is_truncated`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserLimits {
+    /// Maximum number of MIME parts, see [`MessageParser::with_max_parts`].
+    pub max_parts: usize,
+    /// Maximum nesting depth of container parts, see
+    /// [`MessageParser::with_max_nesting_depth`].
+    pub max_nesting_depth: usize,
+    /// Maximum size in bytes of the decoded contents of each part, see
+    /// [`MessageParser::with_max_part_size`].
+    pub max_part_size: usize,
+}
+
+/// Resolution of MIME parts with more than one `Content-Transfer-Encoding`
+/// header field, which RFC 2045 does not allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferEncodingPolicy {
+    /// Use the first header field.
+    #[default]
+    First,
+    /// Use the last header field.
+    Last,
+    /// Do not decode the part when the header fields disagree.
+    Ignore,
+}
+
+/// Resolution of MIME parts whose `Content-Type` header field conflicts with
+/// the type implied by their position in the message structure. The default
+/// type of a part is `message/rfc822` inside a `multipart/digest` and
+/// `text/plain` anywhere else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentTypePolicy {
+    /// The explicit `Content-Type` header field always wins.
+    #[default]
+    Explicit,
+    /// The default type of the part is used instead of the explicit
+    /// `Content-Type` header field when:
+    ///
+    /// - the subtype is missing or empty, as in `text` or `message/`, which
+    ///   would otherwise make the part an attachment of unknown type.
+    /// - the type is `multipart` but its boundary never appears in the body,
+    ///   which would otherwise make the part a `text/*` attachment.
+    Structural,
+}
+
+/// Reason why a `Content-Type` header field could not be parsed, see
+/// [`MessageStream::parse_content_type_diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentTypeError {
+    /// The value is empty or contains only whitespace and comments.
+    Blank,
+    /// A subtype is present but the type before the `/` is missing,
+    /// as in `/plain`.
+    MissingType,
+    /// Both the type and the subtype around the `/` are empty, as in `/`.
+    EmptyType,
+    /// The value starts with parameters and has no type, as in `;` or
+    /// `; charset=utf-8`.
+    ParametersOnly,
+}
+
+/// A malformed token skipped, or an attribute dropped or truncated, while
+/// parsing a `Content-Type` header field, see
+/// [`MessageStream::parse_content_type_with_diagnostics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentTypeWarning {
+    /// The byte offset of the token within the parsed data.
+    pub offset: usize,
+    pub reason: ContentTypeWarningReason,
+}
+
+/// Reason why a token was skipped, see [`ContentTypeWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentTypeWarningReason {
+    /// An attribute name contains more than two `*`, as in `name*0**`, or
+    /// an empty RFC 2231 section index, as in `name**`.
+    DuplicateStar,
+    /// An attribute has a value but no name, as in `=value`.
+    EmptyAttributeName,
+    /// A quoted value is not closed before the end of the header field.
+    UnterminatedQuote,
+    /// An RFC 2231 section index is not a number, as in `name*x=value`.
+    InvalidContinuationIndex,
+    /// An attribute name exceeds the limit set with
+    /// [`MessageParser::with_max_attribute_name_len`], the attribute was dropped.
+    NameTooLong,
+    /// An attribute value exceeds the limit set with
+    /// [`MessageParser::with_max_attribute_value_len`] and was truncated.
+    ValueTooLong,
+    /// An RFC 2231 continuation segment exceeds the limit set with
+    /// [`MessageParser::with_max_attribute_continuations`] and was ignored.
+    TooManyContinuations,
+}
+
+/// Information about a `Content-Type` or `Content-Disposition` header field
+/// that is not part of its [`ContentType`] value, see
+/// [`MessageStream::parse_content_type_details`] and
+/// [`MessageParser::content_type_details`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ContentTypeDetails<'x> {
+    pub(crate) attributes: Vec<(Cow<'x, str>, ContentTypeAttribute<'x>)>,
+    pub(crate) original_names: Vec<(Cow<'x, str>, Cow<'x, str>)>,
+    pub(crate) warnings: Vec<ContentTypeWarning>,
 }
 
 pub(crate) type HdrParseFnc = for<'x> fn(&mut MessageStream<'x>) -> crate::HeaderValue<'x>;
 
+/// Callback invoked with the name and raw value of each header field not
+/// recognized by the parser, see [`MessageParser::on_unknown_header`].
+pub type UnknownHeaderFnc = fn(&str, &[u8]);
+
 /// An RFC5322/RFC822 message.
 #[derive(Debug, Default, PartialEq, Clone)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
@@ -278,6 +401,8 @@ pub struct Message<'x> {
     pub text_body: Vec<MessagePartId>,
     #[cfg_attr(feature = "serde_support", serde(default))]
     pub attachments: Vec<MessagePartId>,
+    #[cfg_attr(feature = "serde_support", serde(default))]
+    pub inline_parts: Vec<MessagePartId>,
 
     #[cfg_attr(feature = "serde_support", serde(default))]
     #[cfg_attr(feature = "serde_support", serde(borrow))]
@@ -285,6 +410,24 @@ pub struct Message<'x> {
 
     #[cfg_attr(feature = "serde_support", serde(skip))]
     pub raw_message: Cow<'x, [u8]>,
+
+    #[cfg_attr(feature = "serde_support", serde(skip))]
+    pub(crate) is_truncated: bool,
+
+    #[cfg_attr(feature = "serde_support", serde(skip))]
+    pub(crate) header_pool: HeaderPool<'x>,
+}
+
+/// Header lists kept by [`Message::recycle`] for reuse by the next parsed
+/// message. They are not part of the message contents and are ignored when
+/// comparing messages.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct HeaderPool<'x>(pub(crate) Vec<Vec<Header<'x>>>);
+
+impl PartialEq for HeaderPool<'_> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
 }
 
 /// MIME Message Part
@@ -325,6 +468,31 @@ impl From<u8> for Encoding {
     }
 }
 
+/// A Content-Transfer-Encoding mechanism (RFC 2045), see
+/// [`MessagePart::effective_encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferEncoding {
+    #[default]
+    SevenBit,
+    EightBit,
+    Binary,
+    QuotedPrintable,
+    Base64,
+    /// A value that is not defined by RFC 2045, such as `x-uuencode`.
+    Unknown,
+}
+
+/// The effective Content-Transfer-Encoding of a part, see
+/// [`MessagePart::effective_encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EffectiveEncoding {
+    pub encoding: TransferEncoding,
+    /// Whether the encoding is not allowed for the part, which is the case
+    /// when `multipart/*` and `message/rfc822` parts declare an encoding other
+    /// than `7bit`, `8bit` or `binary`.
+    pub is_invalid: bool,
+}
+
 /// Unique ID representing a MIME part within a message.
 pub type MessagePartId = usize;
 
@@ -391,6 +559,17 @@ pub struct Group<'x> {
     pub addresses: Vec<Addr<'x>>,
 }
 
+/// The original text of the addresses and groups of an address header
+/// field, before comments are removed and encoded-words are decoded. See
+/// [`MessageParser::address_raw_text`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct AddressRawText<'x> {
+    pub(crate) groups: Vec<GroupRawText<'x>>,
+}
+
+// Original text of a group and of each of its addresses
+pub(crate) type GroupRawText<'x> = (Option<Cow<'x, str>>, Vec<Cow<'x, str>>);
+
 /// A message header.
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
@@ -402,6 +581,15 @@ pub struct Header<'x> {
     pub offset_end: usize,
 }
 
+/// An RFC 2047 encoded-word decoded from a header value.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EncodedWord<'x> {
+    pub charset: Cow<'x, str>,
+    pub encoding: Encoding,
+    pub offset_start: usize,
+    pub offset_end: usize,
+}
+
 /// A header field
 #[derive(Debug, Clone, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
@@ -504,6 +692,168 @@ pub struct ContentType<'x> {
     pub attributes: Option<Vec<(Cow<'x, str>, Cow<'x, str>)>>,
 }
 
+/// A Content-Type attribute declared using RFC 2231 extended notation, as in
+/// `title*=us-ascii'en-us'This%20is%20fun`, see [`ContentTypeDetails::attribute_details`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub struct ContentTypeAttribute<'x> {
+    pub value: Cow<'x, str>,
+    #[cfg_attr(feature = "serde_support", serde(default))]
+    pub charset: Option<Cow<'x, str>>,
+    #[cfg_attr(feature = "serde_support", serde(default))]
+    pub language: Option<Cow<'x, str>>,
+}
+
+/// The headers of a single MIME part, as returned by
+/// [`MessageParser::parse_part_headers`].
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PartHeaders<'x> {
+    pub headers: Vec<Header<'x>>,
+    pub offset_body: usize,
+}
+
+/// An RFC3834 Auto-Submitted header field, including its optional parameters.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
+pub enum AutoSubmitted<'x> {
+    No,
+    AutoGenerated(Vec<(Cow<'x, str>, Cow<'x, str>)>),
+    AutoReplied(Vec<(Cow<'x, str>, Cow<'x, str>)>),
+    Other(Cow<'x, str>, Vec<(Cow<'x, str>, Cow<'x, str>)>),
+}
+
+/// Message priority, see [`Message::priority`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    High,
+    Normal,
+    Low,
+}
+
+/// How a part should be presented by a mail client, see
+/// [`Message::render_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartRole {
+    /// Part of the message body.
+    Body,
+    /// Image displayed within the message body.
+    InlineImage,
+    /// Part listed as an attachment.
+    Attachment,
+}
+
+/// An OpenPGP ASCII armored block (RFC 4880, Section 6.2) found within the
+/// text of a message, as used by inline PGP, see [`MessagePart::pgp_blocks`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PgpBlock<'x> {
+    pub block_type: PgpBlockType,
+    /// The armored text, from the `-----BEGIN` line to the `-----END` line.
+    pub armor: &'x str,
+    /// The byte offset of the block within the text.
+    pub offset: usize,
+}
+
+/// The contents of an OpenPGP armored block, see [`PgpBlock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgpBlockType {
+    /// Cleartext signed message, followed by its signature.
+    SignedMessage,
+    /// Encrypted or signed message.
+    Message,
+    /// Detached signature.
+    Signature,
+    PublicKey,
+    PrivateKey,
+}
+
+/// A multipart part with fewer or more children than allowed for its
+/// subtype, see [`Message::child_count_violations`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ChildCountViolation {
+    pub part_id: MessagePartId,
+    pub children: usize,
+    pub min_children: usize,
+    pub max_children: Option<usize>,
+}
+
+/// The differences between two messages, see [`Message::diff`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct MessageDiff {
+    /// Header fields added, removed or changed, by part.
+    pub headers: Vec<HeaderDiff>,
+    /// Parts added or removed and parts with a changed body.
+    pub parts: Vec<PartDiff>,
+}
+
+/// A header field difference between two messages. Header fields are matched
+/// by part, lowercased name and order of appearance.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HeaderDiff {
+    Added {
+        part_id: MessagePartId,
+        name: String,
+        value: String,
+    },
+    Removed {
+        part_id: MessagePartId,
+        name: String,
+        value: String,
+    },
+    Changed {
+        part_id: MessagePartId,
+        name: String,
+        old_value: String,
+        new_value: String,
+    },
+}
+
+/// A part difference between two messages. Parts are matched by position.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PartDiff {
+    Added(MessagePartId),
+    Removed(MessagePartId),
+    BodyChanged(MessagePartId),
+}
+
+/// The common contact properties of a vCard (RFC 6350, RFC 2426 and vCard
+/// 2.1), see [`VCard::parse`].
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct VCard {
+    /// The `FN` property.
+    pub full_name: Option<String>,
+    /// The components of the `N` property: family name, given name,
+    /// additional names, honorific prefixes and honorific suffixes.
+    pub name: Vec<String>,
+    /// The `EMAIL` properties.
+    pub emails: Vec<String>,
+    /// The `TEL` properties.
+    pub phones: Vec<String>,
+    /// The `ORG` property, with organizational units separated by `;`.
+    pub organization: Option<String>,
+    /// The decoded contents of an inline `PHOTO` property.
+    pub photo: Option<Vec<u8>>,
+}
+
+/// A SpamAssassin `X-Spam-Status` header field, see [`SpamStatus::parse`].
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SpamStatus {
+    /// Whether the message was classified as spam.
+    pub is_spam: bool,
+    /// The score of the message.
+    pub score: f64,
+    /// The score required to classify a message as spam.
+    pub required: Option<f64>,
+    /// The names of the tests that matched.
+    pub tests: Vec<String>,
+}
+
+/// A Microsoft Exchange `Thread-Index` header field, as described in
+/// MS-OXOMSG section 2.2.1.3 (PidTagConversationIndex).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ThreadIndex {
+    pub(crate) bytes: Vec<u8>,
+}
+
 /// An RFC5322 datetime.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde_support", derive(Serialize, Deserialize))]
@@ -631,7 +981,10 @@ pub trait MimeHeaders<'x> {
     fn content_language(&self) -> &HeaderValue<'_>;
     /// Returns the Content-Location field
     fn content_location(&self) -> Option<&str>;
-    /// Returns the attachment name, if any.
+    /// Returns the attachment name, if any. The `filename` parameter of the
+    /// Content-Disposition field takes precedence over the `name` parameter of
+    /// the Content-Type field, and RFC 2231 extended values take precedence
+    /// over plain values.
     fn attachment_name(&self) -> Option<&str> {
         self.content_disposition()
             .and_then(|cd| cd.attribute("filename"))
@@ -639,12 +992,12 @@ pub trait MimeHeaders<'x> {
     }
     // Returns true is the content type matches
     fn is_content_type(&self, type_: &str, subtype: &str) -> bool {
-        self.content_type().map_or(false, |ct| {
+        self.content_type().is_some_and(|ct| {
             ct.c_type.eq_ignore_ascii_case(type_)
                 && ct
                     .c_subtype
                     .as_ref()
-                    .map_or(false, |st| st.eq_ignore_ascii_case(subtype))
+                    .is_some_and(|st| st.eq_ignore_ascii_case(subtype))
         })
     }
 }
@@ -666,3 +1019,8 @@ pub struct AttachmentIterator<'x> {
     message: &'x Message<'x>,
     pos: isize,
 }
+
+#[doc(hidden)]
+pub struct PartWalker<'x> {
+    stack: Vec<(usize, &'x Message<'x>, MessagePartId)>,
+}
@@ -107,88 +107,14 @@ where
 
 impl Message {
     fn new(hdr: &str) -> Self {
-        let (internal_date, from) = if let Some((from, date)) = hdr
-            .strip_prefix("From ")
-            .and_then(|hdr| hdr.split_once(' '))
-        {
-            let mut dt = DateTime {
-                year: u16::MAX,
-                month: u8::MAX,
-                day: u8::MAX,
-                hour: u8::MAX,
-                minute: u8::MAX,
-                second: u8::MAX,
-                tz_before_gmt: false,
-                tz_hour: 0,
-                tz_minute: 0,
-            };
-
-            for (pos, part) in date.split_whitespace().enumerate() {
-                match pos {
-                    1 => {
-                        dt.month = if part.eq_ignore_ascii_case("jan") {
-                            1
-                        } else if part.eq_ignore_ascii_case("feb") {
-                            2
-                        } else if part.eq_ignore_ascii_case("mar") {
-                            3
-                        } else if part.eq_ignore_ascii_case("apr") {
-                            4
-                        } else if part.eq_ignore_ascii_case("may") {
-                            5
-                        } else if part.eq_ignore_ascii_case("jun") {
-                            6
-                        } else if part.eq_ignore_ascii_case("jul") {
-                            7
-                        } else if part.eq_ignore_ascii_case("aug") {
-                            8
-                        } else if part.eq_ignore_ascii_case("sep") {
-                            9
-                        } else if part.eq_ignore_ascii_case("oct") {
-                            10
-                        } else if part.eq_ignore_ascii_case("nov") {
-                            11
-                        } else if part.eq_ignore_ascii_case("dec") {
-                            12
-                        } else {
-                            u8::MAX
-                        };
-                    }
-                    2 => {
-                        dt.day = part.parse().unwrap_or(u8::MAX);
-                    }
-                    3 => {
-                        for (pos, part) in part.split(':').enumerate() {
-                            match pos {
-                                0 => {
-                                    dt.hour = part.parse().unwrap_or(u8::MAX);
-                                }
-                                1 => {
-                                    dt.minute = part.parse().unwrap_or(u8::MAX);
-                                }
-                                2 => {
-                                    dt.second = part.parse().unwrap_or(u8::MAX);
-                                }
-                                _ => {
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                    4 => {
-                        dt.year = part.parse().unwrap_or(u16::MAX);
-                    }
-                    _ => (),
-                }
-            }
-
+        let (internal_date, from) = if let Some((from, dt)) = parse_from_line(hdr) {
             (
                 if dt.is_valid() {
                     dt.to_timestamp() as u64
                 } else {
                     0
                 },
-                from.trim().to_string(),
+                from.to_string(),
             )
         } else {
             (0, "".to_string())
@@ -222,6 +148,100 @@ impl Message {
     }
 }
 
+/// Parses the sender address and date of an mbox `From ` line. Date fields
+/// that could not be parsed are set to their maximum value.
+pub(crate) fn parse_from_line(hdr: &str) -> Option<(&str, DateTime)> {
+    let (from, date) = hdr.strip_prefix("From ")?.split_once(' ')?;
+
+    let mut dt = DateTime {
+        year: u16::MAX,
+        month: u8::MAX,
+        day: u8::MAX,
+        hour: u8::MAX,
+        minute: u8::MAX,
+        second: u8::MAX,
+        tz_before_gmt: false,
+        tz_hour: 0,
+        tz_minute: 0,
+    };
+
+    for (pos, part) in date.split_whitespace().enumerate() {
+        match pos {
+            1 => {
+                dt.month = if part.eq_ignore_ascii_case("jan") {
+                    1
+                } else if part.eq_ignore_ascii_case("feb") {
+                    2
+                } else if part.eq_ignore_ascii_case("mar") {
+                    3
+                } else if part.eq_ignore_ascii_case("apr") {
+                    4
+                } else if part.eq_ignore_ascii_case("may") {
+                    5
+                } else if part.eq_ignore_ascii_case("jun") {
+                    6
+                } else if part.eq_ignore_ascii_case("jul") {
+                    7
+                } else if part.eq_ignore_ascii_case("aug") {
+                    8
+                } else if part.eq_ignore_ascii_case("sep") {
+                    9
+                } else if part.eq_ignore_ascii_case("oct") {
+                    10
+                } else if part.eq_ignore_ascii_case("nov") {
+                    11
+                } else if part.eq_ignore_ascii_case("dec") {
+                    12
+                } else {
+                    u8::MAX
+                };
+            }
+            2 => {
+                dt.day = part.parse().unwrap_or(u8::MAX);
+            }
+            3 => {
+                for (pos, part) in part.split(':').enumerate() {
+                    match pos {
+                        0 => {
+                            dt.hour = part.parse().unwrap_or(u8::MAX);
+                        }
+                        1 => {
+                            dt.minute = part.parse().unwrap_or(u8::MAX);
+                        }
+                        2 => {
+                            dt.second = part.parse().unwrap_or(u8::MAX);
+                        }
+                        _ => {
+                            break;
+                        }
+                    }
+                }
+            }
+            4 => {
+                dt.year = part.parse().unwrap_or(u16::MAX);
+            }
+            _ => (),
+        }
+    }
+
+    Some((from.trim(), dt))
+}
+
+/// Returns the length of the mbox `From ` envelope line at the start of a
+/// message, including its line ending. A line is only an envelope if it holds
+/// a sender without a `:` followed by a valid date, so that obsolete headers
+/// such as `From : John <john@example.com>` are kept.
+pub(crate) fn envelope_line_len(raw_message: &[u8]) -> Option<usize> {
+    if !raw_message.starts_with(b"From ") {
+        return None;
+    }
+    let len = raw_message.iter().position(|&ch| ch == b'\n')? + 1;
+    let line = std::str::from_utf8(&raw_message[..len]).ok()?.trim_end();
+    parse_from_line(line)
+        .filter(|(from, date)| !from.is_empty() && !from.contains(':') && date.is_valid())
+        .map(|_| len)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::mailbox::mbox::Message;
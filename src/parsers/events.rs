@@ -0,0 +1,595 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead},
+};
+
+use crate::{
+    decoders::{base64::Base64Decoder, quoted_printable::QuotedPrintableDecoder},
+    ContentType, GetHeader, Header, HeaderName, HeaderValue, MessageParser,
+};
+
+use super::MessageStream;
+
+// Maximum length of a line fragment read at once
+const LINE_LEN: usize = 8192;
+// Decoded body bytes are emitted once this many are buffered
+const CHUNK_LEN: usize = 8192;
+
+/// An event emitted by [`MessageEvents`] while walking the MIME structure
+/// of a message.
+#[derive(Debug, PartialEq, Clone)]
+pub enum MessageEvent {
+    /// A new part starts, the message itself being the first part.
+    StartPart(Vec<Header<'static>>),
+    /// The current part is a multipart container, its children follow.
+    StartMultipart(ContentType<'static>),
+    /// A chunk of the body of the current part, after transfer decoding.
+    BodyChunk(Vec<u8>),
+    /// All children of the current multipart container have been emitted.
+    EndMultipart,
+    /// The current part ends.
+    EndPart,
+}
+
+/// Streaming, SAX-style iterator over the MIME structure of a message read
+/// from a [`BufRead`] source.
+///
+/// Unlike [`MessageParser::parse`], no `Message` tree is built and the
+/// message is never held in memory as a whole: input is read line by line
+/// and only the header block of the current part, the boundaries of the open
+/// multipart containers and a fixed size body buffer are kept. Every part
+/// emits a `StartPart` event followed by either `StartMultipart`, the events
+/// of its children and `EndMultipart`, or by zero or more `BodyChunk`s, and
+/// is then closed with `EndPart`. Parts left open at the end of the input
+/// are closed.
+///
+/// The header block of each part is buffered until its end, its length is
+/// bounded by [`MessageParser::with_max_part_size`] and an
+/// [`io::ErrorKind::InvalidData`] error is returned, ending the iteration,
+/// once a longer header block is read.
+///
+/// Bodies are transfer decoded as they are read but not charset decoded.
+/// Nested `message/rfc822` parts are emitted as `BodyChunk`s which can in
+/// turn be passed to [`MessageParser::events`].
+pub struct MessageEvents<R: BufRead> {
+    conf: MessageParser,
+    reader: R,
+    state: State,
+    line: Vec<u8>,
+    is_line_start: bool,
+    skip_line: bool,
+    headers: Vec<u8>,
+    body: Vec<u8>,
+    body_eol: &'static [u8],
+    decoder: BodyDecoder,
+    boundaries: Vec<Vec<u8>>,
+    pending: VecDeque<MessageEvent>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum State {
+    Headers,
+    Body,
+    // Preamble, epilogue or the text between the parts of a multipart
+    SeekDelimiter,
+    Done,
+}
+
+enum BodyDecoder {
+    None,
+    Base64(Base64Decoder),
+    QuotedPrintable(QuotedPrintableDecoder),
+}
+
+impl MessageParser {
+    /// Returns an iterator of [`MessageEvent`]s describing the structure and
+    /// contents of the message read from `reader`, without building a
+    /// `Message` tree.
+    pub fn events<R: BufRead>(&self, reader: R) -> MessageEvents<R> {
+        MessageEvents {
+            conf: self.clone(),
+            reader,
+            state: State::Headers,
+            line: Vec::with_capacity(128),
+            is_line_start: true,
+            skip_line: false,
+            headers: Vec::with_capacity(1024),
+            body: Vec::new(),
+            body_eol: b"",
+            decoder: BodyDecoder::None,
+            boundaries: Vec::with_capacity(4),
+            pending: VecDeque::with_capacity(4),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for MessageEvents<R> {
+    type Item = io::Result<MessageEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && self.state != State::Done {
+            if let Err(err) = self.read_next() {
+                self.state = State::Done;
+                return Some(Err(err));
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+impl<R: BufRead> MessageEvents<R> {
+    fn read_next(&mut self) -> io::Result<()> {
+        let is_line_start = self.is_line_start;
+        let is_complete = self.read_line()?;
+        self.is_line_start = is_complete;
+
+        if self.line.is_empty() {
+            self.end_of_input();
+            return Ok(());
+        } else if self.skip_line {
+            // Remainder of an overlong delimiter line
+            self.skip_line = !is_complete;
+            return Ok(());
+        }
+
+        if let Some((depth, is_close)) = is_line_start.then(|| self.find_delimiter()).flatten() {
+            self.delimiter(depth, is_close);
+            self.skip_line = !is_complete;
+            return Ok(());
+        }
+
+        match self.state {
+            State::Headers => {
+                if self.headers.len() + self.line.len() > self.conf.limits.max_part_size {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "header block exceeds the maximum part size",
+                    ));
+                }
+                if is_line_start && matches!(self.line.as_slice(), b"\n" | b"\r\n") {
+                    self.headers.extend_from_slice(&self.line);
+                    self.start_part(true);
+                } else {
+                    self.headers.extend_from_slice(&self.line);
+                }
+            }
+            State::Body => self.write_body(is_complete),
+            State::SeekDelimiter | State::Done => (),
+        }
+
+        Ok(())
+    }
+
+    // Reads the next line into `self.line`, up to `LINE_LEN` bytes, and
+    // returns whether it includes its line break
+    fn read_line(&mut self) -> io::Result<bool> {
+        self.line.clear();
+
+        loop {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if buf.is_empty() {
+                return Ok(false);
+            }
+
+            let buf = &buf[..buf.len().min(LINE_LEN - self.line.len())];
+            let (len, is_complete) = match buf.iter().position(|&ch| ch == b'\n') {
+                Some(pos) => (pos + 1, true),
+                None => (buf.len(), false),
+            };
+            self.line.extend_from_slice(&buf[..len]);
+            self.reader.consume(len);
+
+            if is_complete {
+                return Ok(true);
+            } else if self.line.len() == LINE_LEN {
+                // Avoid splitting a CRLF line break
+                if self.line.last() == Some(&b'\r')
+                    && self.reader.fill_buf()?.first() == Some(&b'\n')
+                {
+                    self.line.push(b'\n');
+                    self.reader.consume(1);
+                    return Ok(true);
+                }
+                return Ok(false);
+            }
+        }
+    }
+
+    // Returns the depth of the open multipart container whose delimiter is
+    // the current line, and whether it is a close delimiter
+    fn find_delimiter(&self) -> Option<(usize, bool)> {
+        let line = self.line.strip_prefix(b"--")?;
+
+        self.boundaries
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, boundary)| {
+                let remaining = line.strip_prefix(boundary.as_slice())?;
+                if remaining.starts_with(b"--") {
+                    Some((depth, true))
+                } else if remaining.iter().all(|ch| ch.is_ascii_whitespace()) {
+                    Some((depth, false))
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn start_part(&mut self, has_body: bool) {
+        if !has_body {
+            // Header block interrupted by a delimiter or the end of input
+            if !self.headers.ends_with(b"\n") {
+                self.headers.push(b'\n');
+            }
+            self.headers.push(b'\n');
+        }
+
+        let mut headers = Vec::new();
+        MessageStream::new(&self.headers).parse_headers(&self.conf, &mut headers);
+        let headers = headers
+            .into_iter()
+            .map(|header| header.into_owned())
+            .collect::<Vec<_>>();
+        self.headers.clear();
+
+        let content_type = headers
+            .header_value(&HeaderName::ContentType)
+            .and_then(|c| c.as_content_type())
+            .filter(|ct| ct.ctype() == "multipart")
+            .cloned();
+        self.decoder = match self.conf.transfer_encoding(&headers) {
+            Some(HeaderValue::Text(encoding)) if encoding.eq_ignore_ascii_case("base64") => {
+                BodyDecoder::Base64(Base64Decoder::default())
+            }
+            Some(HeaderValue::Text(encoding))
+                if encoding.eq_ignore_ascii_case("quoted-printable") =>
+            {
+                BodyDecoder::QuotedPrintable(QuotedPrintableDecoder::new())
+            }
+            _ => BodyDecoder::None,
+        };
+        self.pending.push_back(MessageEvent::StartPart(headers));
+
+        if let Some(content_type) = content_type {
+            if let Some(boundary) = content_type.attribute("boundary").filter(|b| !b.is_empty()) {
+                self.boundaries.push(boundary.as_bytes().to_vec());
+                self.pending
+                    .push_back(MessageEvent::StartMultipart(content_type));
+                self.state = State::SeekDelimiter;
+                return;
+            }
+        }
+
+        self.body_eol = b"";
+        self.state = State::Body;
+    }
+
+    fn write_body(&mut self, is_complete: bool) {
+        // Line breaks are held back until the next line is known not to be a
+        // delimiter, as the line break preceding a delimiter belongs to it
+        let eol: &'static [u8] = if !is_complete {
+            b""
+        } else if self.line.ends_with(b"\r\n") {
+            b"\r\n"
+        } else {
+            b"\n"
+        };
+        let line = std::mem::take(&mut self.line);
+        self.decode(self.body_eol);
+        self.decode(&line[..line.len() - eol.len()]);
+        self.line = line;
+        self.body_eol = eol;
+
+        if self.body.len() >= CHUNK_LEN {
+            let chunk = std::mem::replace(&mut self.body, Vec::with_capacity(CHUNK_LEN));
+            self.pending.push_back(MessageEvent::BodyChunk(chunk));
+        }
+    }
+
+    fn decode(&mut self, bytes: &[u8]) {
+        let len = self.body.len();
+        let is_valid = match &mut self.decoder {
+            BodyDecoder::None => {
+                self.body.extend_from_slice(bytes);
+                true
+            }
+            BodyDecoder::Base64(decoder) => decoder.decode(bytes, &mut self.body),
+            BodyDecoder::QuotedPrintable(decoder) => decoder.decode(bytes, &mut self.body),
+        };
+
+        if !is_valid {
+            // Pass the remainder of an invalid part through undecoded
+            self.body.truncate(len);
+            self.body.extend_from_slice(bytes);
+            self.decoder = BodyDecoder::None;
+        }
+    }
+
+    fn end_body(&mut self, is_delimiter: bool) {
+        if !is_delimiter {
+            self.decode(self.body_eol);
+        }
+        if let BodyDecoder::QuotedPrintable(decoder) = &mut self.decoder {
+            decoder.finish(is_delimiter, &mut self.body);
+        }
+        self.decoder = BodyDecoder::None;
+
+        if !self.body.is_empty() {
+            self.pending
+                .push_back(MessageEvent::BodyChunk(std::mem::take(&mut self.body)));
+        }
+        self.pending.push_back(MessageEvent::EndPart);
+    }
+
+    fn delimiter(&mut self, depth: usize, is_close: bool) {
+        if self.state == State::Headers {
+            self.start_part(false);
+        }
+        if self.state == State::Body {
+            self.end_body(true);
+        }
+
+        // Close the containers nested in the delimited one
+        self.close_multiparts(depth + 1);
+
+        if is_close {
+            self.close_multiparts(depth);
+            self.state = if self.boundaries.is_empty() {
+                State::Done
+            } else {
+                State::SeekDelimiter
+            };
+        } else {
+            self.state = State::Headers;
+        }
+    }
+
+    fn end_of_input(&mut self) {
+        if self.state == State::Headers && !self.headers.is_empty() {
+            self.start_part(false);
+        }
+        if self.state == State::Body {
+            self.end_body(false);
+        }
+
+        self.close_multiparts(0);
+        self.state = State::Done;
+    }
+
+    fn close_multiparts(&mut self, depth: usize) {
+        while self.boundaries.len() > depth {
+            self.boundaries.pop();
+            self.pending.push_back(MessageEvent::EndMultipart);
+            self.pending.push_back(MessageEvent::EndPart);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::{parsers::events::MessageEvent, MessageParser};
+
+    fn events(message: &str, capacity: usize) -> Vec<String> {
+        MessageParser::new()
+            .events(BufReader::with_capacity(capacity, message.as_bytes()))
+            .map(|event| match event.unwrap() {
+                MessageEvent::StartPart(headers) => format!("StartPart({})", headers.len()),
+                MessageEvent::StartMultipart(ct) => {
+                    format!("StartMultipart({})", ct.subtype().unwrap_or_default())
+                }
+                MessageEvent::BodyChunk(bytes) => {
+                    format!("BodyChunk({})", String::from_utf8_lossy(&bytes))
+                }
+                MessageEvent::EndMultipart => "EndMultipart".to_string(),
+                MessageEvent::EndPart => "EndPart".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn message_events() {
+        for (message, expected) in [
+            (
+                concat!(
+                    "From: john@example.org\n",
+                    "Content-Type: multipart/mixed; boundary=\"outer\"\n\n",
+                    "Preamble\n",
+                    "--outer\n",
+                    "Content-Type: multipart/alternative; boundary=\"inner\"\n\n",
+                    "--inner\n",
+                    "Content-Type: text/plain\n\n",
+                    "Hello\n",
+                    "--inner\n",
+                    "Content-Type: text/html\n",
+                    "Content-Transfer-Encoding: base64\n\n",
+                    "PGI+SGVsbG88L2I+\n",
+                    "--inner--\n",
+                    "--outer\n",
+                    "Content-Type: application/octet-stream\n",
+                    "Content-Transfer-Encoding: quoted-printable\n\n",
+                    "a=3Db \n",
+                    "--outer--\n",
+                    "Epilogue\n",
+                ),
+                &[
+                    "StartPart(2)",
+                    "StartMultipart(mixed)",
+                    "StartPart(1)",
+                    "StartMultipart(alternative)",
+                    "StartPart(1)",
+                    "BodyChunk(Hello)",
+                    "EndPart",
+                    "StartPart(2)",
+                    "BodyChunk(<b>Hello</b>)",
+                    "EndPart",
+                    "EndMultipart",
+                    "EndPart",
+                    "StartPart(2)",
+                    "BodyChunk(a=b)",
+                    "EndPart",
+                    "EndMultipart",
+                    "EndPart",
+                ][..],
+            ),
+            (
+                concat!(
+                    "Content-Type: multipart/mixed; boundary=\"a\"\r\n\r\n",
+                    "--a\r\n",
+                    "Content-Type: text/plain\r\n\r\n",
+                    "Truncated",
+                ),
+                &[
+                    "StartPart(1)",
+                    "StartMultipart(mixed)",
+                    "StartPart(1)",
+                    "BodyChunk(Truncated)",
+                    "EndPart",
+                    "EndMultipart",
+                    "EndPart",
+                ][..],
+            ),
+            (
+                "Subject: test\n\nBody\n",
+                &["StartPart(1)", "BodyChunk(Body\n)", "EndPart"][..],
+            ),
+            (
+                // Empty body, delimiter within the header block and a child
+                // container closed by its parent's delimiter
+                concat!(
+                    "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                    "--a\n",
+                    "Content-Type: text/plain\n\n",
+                    "--a \n",
+                    "Content-Type: text/plain\n",
+                    "--a\n",
+                    "Content-Type: multipart/mixed; boundary=\"ab\"\n\n",
+                    "--ab\n\n",
+                    "Unterminated\n",
+                    "--a--\n",
+                ),
+                &[
+                    "StartPart(1)",
+                    "StartMultipart(mixed)",
+                    "StartPart(1)",
+                    "EndPart",
+                    "StartPart(1)",
+                    "EndPart",
+                    "StartPart(1)",
+                    "StartMultipart(mixed)",
+                    "StartPart(0)",
+                    "BodyChunk(Unterminated)",
+                    "EndPart",
+                    "EndMultipart",
+                    "EndPart",
+                    "EndMultipart",
+                    "EndPart",
+                ][..],
+            ),
+            ("", &[][..]),
+        ] {
+            for capacity in [1, 3, 8192] {
+                assert_eq!(
+                    events(message, capacity),
+                    expected,
+                    "{message:?} {capacity}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn message_events_chunked_body() {
+        for (encoding, line, decoded) in [
+            ("base64", "PGI+SGVsbG88L2I+\r\n", "<b>Hello</b>"),
+            ("quoted-printable", "a=3Db  \r\n", "a=b\r\n"),
+            ("7bit", "Hello\r\n", "Hello\r\n"),
+        ] {
+            let message = format!(
+                concat!(
+                    "Content-Type: multipart/mixed; boundary=\"a\"\r\n\r\n",
+                    "--a\r\n",
+                    "Content-Transfer-Encoding: {}\r\n\r\n",
+                    "{}",
+                    "--a--\r\n",
+                ),
+                encoding,
+                line.repeat(10000)
+            );
+            let mut expected = decoded.repeat(10000);
+            if expected.ends_with("\r\n") {
+                expected.truncate(expected.len() - 2);
+            }
+
+            let mut body = Vec::new();
+            let mut chunks = 0;
+            for event in MessageParser::new().events(message.as_bytes()) {
+                if let MessageEvent::BodyChunk(chunk) = event.unwrap() {
+                    assert!(chunk.len() < super::CHUNK_LEN + super::LINE_LEN);
+                    body.extend_from_slice(&chunk);
+                    chunks += 1;
+                }
+            }
+            assert!(chunks > 1, "{encoding}");
+            assert_eq!(String::from_utf8(body).unwrap(), expected, "{encoding}");
+        }
+    }
+
+    #[test]
+    fn message_events_max_header_size() {
+        let message = format!(
+            concat!(
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "{}\n",
+                "Body\n",
+                "--a--\n",
+            ),
+            "X-Header: value\n".repeat(100)
+        );
+
+        let mut events = MessageParser::new()
+            .with_max_part_size(1024)
+            .events(message.as_bytes());
+        assert!(matches!(
+            events.next(),
+            Some(Ok(MessageEvent::StartPart(_)))
+        ));
+        assert!(matches!(
+            events.next(),
+            Some(Ok(MessageEvent::StartMultipart(_)))
+        ));
+        assert_eq!(
+            events.next().unwrap().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert!(events.next().is_none());
+
+        assert_eq!(
+            MessageParser::new()
+                .with_max_part_size(2048)
+                .events(message.as_bytes())
+                .filter(|event| matches!(event, Ok(MessageEvent::BodyChunk(_))))
+                .count(),
+            1
+        );
+    }
+}
@@ -11,7 +11,9 @@
 
 use std::borrow::Cow;
 
-use crate::{parsers::MessageStream, Addr, Address, Group, HeaderValue};
+use crate::{
+    parsers::MessageStream, Addr, Address, AddressRawText, Group, GroupRawText, HeaderValue,
+};
 
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum AddressState {
@@ -40,6 +42,14 @@ pub struct AddressParser<'x> {
     group_name: Option<Cow<'x, str>>,
     group_comment: Option<Cow<'x, str>>,
     result: Vec<Group<'x>>,
+
+    is_raw_text: bool,
+    addr_start: usize,
+    addr_end: usize,
+    group_start: usize,
+    group_end: usize,
+    raw_addresses: Vec<Cow<'x, str>>,
+    raw_groups: Vec<GroupRawText<'x>>,
 }
 
 impl<'x> AddressParser<'x> {
@@ -81,7 +91,29 @@ impl<'x> AddressParser<'x> {
         }
     }
 
-    pub fn add_address(&mut self) {
+    pub fn add_address(&mut self, stream: &MessageStream<'x>) {
+        let raw = if self.is_raw_text && self.addr_start > 0 {
+            if self.group_start == 0 {
+                self.group_start = self.addr_start;
+            }
+            self.group_end = self.addr_end;
+            String::from_utf8_lossy(&stream.data[self.addr_start - 1..self.addr_end])
+        } else {
+            "".into()
+        };
+        self.addr_start = 0;
+
+        if !stream.comment_display_names && !self.comment_tokens.is_empty() {
+            if self.mail_tokens.is_empty() {
+                // Obsolete "addr (comment)" form, the address was read as a phrase
+                std::mem::swap(&mut self.name_tokens, &mut self.mail_tokens);
+            }
+            if self.name_tokens.is_empty() && !self.mail_tokens.is_empty() {
+                // Comments are not display names (RFC 5322, Section 3.4)
+                self.comment_tokens.clear();
+            }
+        }
+
         let has_mail = !self.mail_tokens.is_empty();
         let has_name = !self.name_tokens.is_empty();
         let has_comment = !self.comment_tokens.is_empty();
@@ -131,6 +163,10 @@ impl<'x> AddressParser<'x> {
         } else {
             return;
         });
+
+        if self.is_raw_text {
+            self.raw_addresses.push(raw);
+        }
     }
 
     pub fn add_group_details(&mut self) {
@@ -156,7 +192,16 @@ impl<'x> AddressParser<'x> {
         }
     }
 
-    pub fn add_group(&mut self) {
+    pub fn add_group(&mut self, stream: &MessageStream<'x>, group_end: usize) {
+        let raw = if self.is_raw_text && self.group_start > 0 {
+            Some(String::from_utf8_lossy(
+                &stream.data[self.group_start - 1..group_end],
+            ))
+        } else {
+            None
+        };
+        self.group_start = 0;
+
         let has_name = self.group_name.is_some();
         let has_comment = self.group_comment.is_some();
         let has_addresses = !self.addresses.is_empty();
@@ -192,11 +237,26 @@ impl<'x> AddressParser<'x> {
             } else {
                 return;
             });
+
+        if self.is_raw_text {
+            self.raw_groups
+                .push((raw, std::mem::take(&mut self.raw_addresses)));
+        }
     }
 }
 
 impl<'x> MessageStream<'x> {
     pub fn parse_address(&mut self) -> HeaderValue<'x> {
+        self.address_parser(false).0
+    }
+
+    /// Parses an address header field like [`Self::parse_address`], also
+    /// returning the original text of each address and group.
+    pub fn parse_address_raw_text(&mut self) -> (HeaderValue<'x>, AddressRawText<'x>) {
+        self.address_parser(true)
+    }
+
+    fn address_parser(&mut self, is_raw_text: bool) -> (HeaderValue<'x>, AddressRawText<'x>) {
         let mut parser = AddressParser {
             token_start: 0,
             token_end: 0,
@@ -216,9 +276,27 @@ impl<'x> MessageStream<'x> {
             group_name: None,
             group_comment: None,
             result: Vec::new(),
+
+            is_raw_text,
+            addr_start: 0,
+            addr_end: 0,
+            group_start: 0,
+            group_end: 0,
+            raw_addresses: Vec::new(),
+            raw_groups: Vec::new(),
         };
 
         while let Some(ch) = self.next() {
+            if parser.is_raw_text
+                && !ch.is_ascii_whitespace()
+                && (parser.state != AddressState::Name || !matches!(ch, b',' | b';' | b':'))
+            {
+                if parser.addr_start == 0 {
+                    parser.addr_start = self.offset();
+                }
+                parser.addr_end = self.offset();
+            }
+
             match ch {
                 b'\n' => {
                     parser.add_token(self, false);
@@ -243,7 +321,16 @@ impl<'x> MessageStream<'x> {
                 }
                 b',' if parser.state == AddressState::Name => {
                     parser.add_token(self, false);
-                    parser.add_address();
+                    if self.lenient_address_names
+                        && parser.mail_tokens.is_empty()
+                        && parser.comment_tokens.is_empty()
+                        && !parser.name_tokens.is_empty()
+                        && self.is_unquoted_name_continuation()
+                    {
+                        parser.name_tokens.push(",".into());
+                        continue;
+                    }
+                    parser.add_address(self);
                     continue;
                 }
                 b'<' if parser.state == AddressState::Name => {
@@ -325,15 +412,23 @@ impl<'x> MessageStream<'x> {
                     }
                 }
                 b':' if parser.state == AddressState::Name && !parser.is_escaped => {
-                    parser.add_group();
+                    let group_start = parser.addr_start;
+                    parser.addr_start = 0;
+                    parser.add_group(self, parser.group_end);
+                    parser.group_start = if group_start > 0 {
+                        group_start
+                    } else {
+                        self.offset()
+                    };
+                    parser.group_end = self.offset();
                     parser.add_token(self, false);
                     parser.add_group_details();
                     continue;
                 }
                 b';' if parser.state == AddressState::Name => {
                     parser.add_token(self, false);
-                    parser.add_address();
-                    parser.add_group();
+                    parser.add_address(self);
+                    parser.add_group(self, self.offset());
                     continue;
                 }
                 _ => (),
@@ -355,19 +450,56 @@ impl<'x> MessageStream<'x> {
             }
         }
 
-        parser.add_address();
+        parser.add_address(self);
 
         if parser.group_name.is_some() || !parser.result.is_empty() {
-            parser.add_group();
-            HeaderValue::Address(Address::Group(parser.result))
+            parser.add_group(self, parser.group_end);
+            (
+                HeaderValue::Address(Address::Group(parser.result)),
+                AddressRawText {
+                    groups: parser.raw_groups,
+                },
+            )
         } else if !parser.addresses.is_empty() {
-            HeaderValue::Address(Address::List(parser.addresses))
+            (
+                HeaderValue::Address(Address::List(parser.addresses)),
+                AddressRawText {
+                    groups: if is_raw_text {
+                        vec![(None, parser.raw_addresses)]
+                    } else {
+                        Vec::new()
+                    },
+                },
+            )
         } else {
-            HeaderValue::Empty
+            (HeaderValue::Empty, AddressRawText::default())
         }
     }
 }
 
+impl MessageStream<'_> {
+    // Returns true if the text following a comma is a plain phrase that ends
+    // with an angle-addr, as in the `John <jdoe@example.com>` in `Doe, John <jdoe@example.com>`.
+    fn is_unquoted_name_continuation(&self) -> bool {
+        let mut iter = self.data[self.offset()..].iter().peekable();
+        let mut has_name = false;
+        while let Some(ch) = iter.next() {
+            match ch {
+                b'<' => return has_name,
+                b',' | b';' | b':' | b'@' | b'"' | b'(' | b')' | b'>' => return false,
+                b'\n' => {
+                    if !matches!(iter.peek(), Some(b' ' | b'\t')) {
+                        return false;
+                    }
+                }
+                b' ' | b'\t' | b'\r' => (),
+                _ => has_name = true,
+            }
+        }
+        false
+    }
+}
+
 fn concat_tokens<'x>(tokens: &mut Vec<Cow<'x, str>>) -> Cow<'x, str> {
     if tokens.len() == 1 {
         tokens.pop().unwrap()
@@ -465,7 +597,10 @@ pub fn parse_address_detail_part(addr: &str) -> Option<&str> {
 
 #[cfg(test)]
 mod tests {
-    use crate::parsers::{fields::load_tests, MessageStream};
+    use crate::{
+        parsers::{fields::load_tests, MessageStream},
+        MessageParser,
+    };
 
     #[test]
     fn parse_addresses() {
@@ -480,4 +615,265 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn parse_addresses_comment_display_names() {
+        for (input, expected) in [
+            (
+                "<http://www.host.com/list/archive/> (Web Archive)\n",
+                vec![Some("Web Archive")],
+            ),
+            (
+                "(Use this command to join the list)\n   <mailto:list-manager@host.com>\n",
+                vec![Some("Use this command to join the list")],
+            ),
+            (
+                "NO (posting not allowed on this list)\n",
+                vec![Some("posting not allowed on this list")],
+            ),
+            (
+                "<ftp://ftp.host.com/list.txt> (FTP),\n   <mailto:list@host.com>\n",
+                vec![Some("FTP"), None],
+            ),
+            (
+                "A (Some (nested) people) Group:\n a@b.com,\n\t(first (nested)) c@d.com;\n",
+                vec![None, Some("first (nested)")],
+            ),
+            (
+                "\"Jane\" <jdoe@example.com> (John Doe)\n",
+                vec![Some("Jane (John Doe)")],
+            ),
+        ] {
+            let mut stream = MessageStream::new(input.as_bytes());
+            stream.comment_display_names = true;
+            assert_eq!(
+                stream
+                    .parse_address()
+                    .unwrap_address()
+                    .iter()
+                    .map(|addr| addr.name())
+                    .collect::<Vec<_>>(),
+                expected,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_addresses_raw_text() {
+        let parse =
+            |input: &'static str| MessageStream::new(input.as_bytes()).parse_address_raw_text();
+
+        let (address, raw_text) =
+            parse("John Doe <jdoe@machine.example>,\n =?utf-8?q?Mary?= <mary@x.org> (comment)\n");
+        assert_eq!(
+            (0..address.unwrap_address().iter().count())
+                .map(|index| raw_text.address(index).unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                "John Doe <jdoe@machine.example>",
+                "=?utf-8?q?Mary?= <mary@x.org> (comment)",
+            ]
+        );
+        assert_eq!(raw_text.group(0), None);
+
+        for (input, expected) in [
+            (
+                "a@b.c, Friends: jane@example.com, \"Smith, J\" <j@x.org>; Empty:;\n",
+                vec![
+                    ("a@b.c", vec!["a@b.c"]),
+                    (
+                        "Friends: jane@example.com, \"Smith, J\" <j@x.org>;",
+                        vec!["jane@example.com", "\"Smith, J\" <j@x.org>"],
+                    ),
+                    ("Empty:;", vec![]),
+                ],
+            ),
+            (
+                "Undisclosed recipients:\n",
+                vec![("Undisclosed recipients:", vec![])],
+            ),
+            (
+                "Friends: jane@example.com (Jane)\n",
+                vec![(
+                    "Friends: jane@example.com (Jane)",
+                    vec!["jane@example.com (Jane)"],
+                )],
+            ),
+            (": a@b.c;\n", vec![(": a@b.c;", vec!["a@b.c"])]),
+        ] {
+            let (address, raw_text) = parse(input);
+            let groups = address.unwrap_address().into_group();
+            assert_eq!(
+                groups
+                    .iter()
+                    .enumerate()
+                    .map(|(group_idx, group)| (
+                        raw_text.group(group_idx).unwrap(),
+                        (0..group.addresses.len())
+                            .map(|idx| raw_text.group_address(group_idx, idx).unwrap())
+                            .collect::<Vec<_>>()
+                    ))
+                    .collect::<Vec<_>>(),
+                expected,
+                "failed for {input:?}"
+            );
+            assert_eq!(raw_text.group(groups.len()), None);
+        }
+    }
+
+    #[test]
+    fn comment_display_names() {
+        for (input, expected, expected_comment_names) in [
+            (
+                "jdoe@example.com (=?utf-8?q?John_Doe?=)",
+                None,
+                Some("John Doe"),
+            ),
+            ("<jdoe@example.com> (John Doe)", None, Some("John Doe")),
+            (
+                "\"Jane\" <jdoe@example.com> (John Doe)",
+                Some("Jane (John Doe)"),
+                Some("Jane (John Doe)"),
+            ),
+            ("jdoe@example.com", None, None),
+        ] {
+            let input = format!("From: {input}\n\nbody");
+            for (parser, expected) in [
+                (MessageParser::default(), expected),
+                (
+                    MessageParser::default().with_comment_display_names(),
+                    expected_comment_names,
+                ),
+            ] {
+                let message = parser.parse(&input).unwrap();
+                let addr = message.from().unwrap().first().unwrap();
+                assert_eq!(addr.name(), expected, "failed for {input:?}");
+                assert_eq!(
+                    addr.address(),
+                    Some("jdoe@example.com"),
+                    "failed for {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lenient_address_names() {
+        for (input, expected, expected_lenient) in [
+            (
+                "Doe, John <j@x>",
+                vec![(Some("Doe"), None), (Some("John"), Some("j@x"))],
+                vec![(Some("Doe, John"), Some("j@x"))],
+            ),
+            (
+                "Doe, John <j@x>, Smith,\r\n Jane Q. <jane@example.org>",
+                vec![
+                    (Some("Doe"), None),
+                    (Some("John"), Some("j@x")),
+                    (Some("Smith"), None),
+                    (Some("Jane Q."), Some("jane@example.org")),
+                ],
+                vec![
+                    (Some("Doe, John"), Some("j@x")),
+                    (Some("Smith, Jane Q."), Some("jane@example.org")),
+                ],
+            ),
+            (
+                "a@example.org, John <j@x>",
+                vec![(None, Some("a@example.org")), (Some("John"), Some("j@x"))],
+                vec![(None, Some("a@example.org")), (Some("John"), Some("j@x"))],
+            ),
+            (
+                "Doe, \"John\" <j@x>",
+                vec![(Some("Doe"), None), (Some("John"), Some("j@x"))],
+                vec![(Some("Doe"), None), (Some("John"), Some("j@x"))],
+            ),
+            (
+                "Doe, j@x",
+                vec![(Some("Doe"), None), (None, Some("j@x"))],
+                vec![(Some("Doe"), None), (None, Some("j@x"))],
+            ),
+        ] {
+            let input = format!("To: {input}\r\n\r\nbody");
+            for (parser, expected) in [
+                (MessageParser::default(), expected),
+                (
+                    MessageParser::default().with_lenient_address_names(),
+                    expected_lenient,
+                ),
+            ] {
+                let message = parser.parse(&input).unwrap();
+                assert_eq!(
+                    message
+                        .to()
+                        .unwrap()
+                        .iter()
+                        .map(|addr| (addr.name(), addr.address()))
+                        .collect::<Vec<_>>(),
+                    expected,
+                    "failed for {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn address_groups() {
+        let message = MessageParser::new()
+            .with_address_headers()
+            .header_address("Recipients")
+            .parse(concat!(
+                "Recipients: A Group:a@b.com,c@d.com;\n",
+                "To: Team (core (and friends)):\n",
+                " =?utf-8?q?Jos=C3=A9?= <jose@example.com>,\n",
+                "\tzoe@example.com; Undisclosed recipients:;\n",
+                "\n",
+                "Hello",
+            ))
+            .unwrap();
+
+        let groups = message
+            .header("Recipients")
+            .unwrap()
+            .as_address()
+            .unwrap()
+            .as_group()
+            .unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name.as_deref(), Some("A Group"));
+        assert_eq!(
+            groups[0]
+                .addresses
+                .iter()
+                .map(|addr| addr.address().unwrap())
+                .collect::<Vec<_>>(),
+            ["a@b.com", "c@d.com"]
+        );
+
+        let groups = message.to().unwrap().as_group().unwrap();
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| (
+                    group.name.as_deref().unwrap(),
+                    group
+                        .addresses
+                        .iter()
+                        .map(|addr| (addr.name(), addr.address().unwrap()))
+                        .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "Team (core (and friends))",
+                    vec![
+                        (Some("José"), "jose@example.com"),
+                        (None, "zoe@example.com")
+                    ]
+                ),
+                ("Undisclosed recipients", vec![]),
+            ]
+        );
+    }
 }
@@ -9,12 +9,13 @@
  * except according to those terms.
  */
 
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet};
 
 use crate::{
     decoders::{charsets::map::charset_decoder, hex::decode_hex},
     parsers::MessageStream,
-    ContentType, HeaderValue,
+    ContentType, ContentTypeAttribute, ContentTypeDetails, ContentTypeError, ContentTypeWarning,
+    ContentTypeWarningReason, HeaderValue,
 };
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -27,7 +28,15 @@ enum ContentState {
     Comment,
 }
 
-type Continuation<'x> = (Cow<'x, str>, u32, Cow<'x, str>);
+// Default number of RFC 2231 continuation segments kept per header
+pub(crate) const MAX_ATTRIBUTE_CONTINUATIONS: usize = 1000;
+
+// Attribute name, index, value, number of attributes preceding the segment
+// and offset of the segment
+type Continuation<'x> = (Cow<'x, str>, u32, Cow<'x, str>, usize, usize);
+
+// Attribute name, RFC 2231 charset and language
+type Details<'x> = (Cow<'x, str>, Option<Cow<'x, str>>, Option<Cow<'x, str>>);
 
 struct ContentTypeParser<'x> {
     state: ContentState,
@@ -39,10 +48,18 @@ struct ContentTypeParser<'x> {
     attr_name: Option<Cow<'x, str>>,
     attr_charset: Option<Cow<'x, str>>,
     attr_position: u32,
+    attr_offset: usize,
 
     values: Vec<Cow<'x, str>>,
     attributes: Vec<(Cow<'x, str>, Cow<'x, str>)>,
     continuations: Option<Vec<Continuation<'x>>>,
+    details: Vec<Details<'x>>,
+    warnings: Option<Vec<ContentTypeWarning>>,
+    // Attribute whose unquoted value ended at a ';', in lenient mode
+    append_pos: Option<usize>,
+    // Lowercased and original names of attributes not written in lowercase,
+    // only collected along with warnings
+    original_names: Vec<(Cow<'x, str>, Cow<'x, str>)>,
 
     token_start: usize,
     token_end: usize,
@@ -51,11 +68,28 @@ struct ContentTypeParser<'x> {
     is_encoded_attribute: bool,
     is_escaped: bool,
     remove_crlf: bool,
+    // Set once a fold in the current quoted value was found to be part of it,
+    // which then holds for the remaining folds of that value
+    is_quote_fold: bool,
     is_lower_case: bool,
     is_token_start: bool,
+    is_dropped: bool,
+    has_separator: bool,
+    has_parameters: bool,
+
+    max_name_len: usize,
+    max_value_len: usize,
+    max_continuations: usize,
 }
 
 impl<'x> ContentTypeParser<'x> {
+    #[inline(always)]
+    fn warn(&mut self, offset: usize, reason: ContentTypeWarningReason) {
+        if let Some(warnings) = self.warnings.as_mut() {
+            warnings.push(ContentTypeWarning { offset, reason });
+        }
+    }
+
     #[inline(always)]
     fn reset_parser(&mut self) {
         self.token_start = 0;
@@ -68,13 +102,34 @@ impl<'x> ContentTypeParser<'x> {
                 &stream.data[self.token_start - 1..self.token_end],
             ));
 
+            let original_name = (!self.is_lower_case).then(|| attr.clone()).flatten();
             if !self.is_lower_case {
                 attr.as_mut().unwrap().to_mut().make_ascii_lowercase();
                 self.is_lower_case = true;
             }
 
             match self.state {
-                ContentState::AttributeName => self.attr_name = attr,
+                ContentState::AttributeName => {
+                    if attr
+                        .as_ref()
+                        .is_some_and(|attr| attr.len() > self.max_name_len)
+                    {
+                        // Dropping the name discards the value that follows
+                        self.attr_name = None;
+                        self.is_dropped = true;
+                        self.warn(self.token_start - 1, ContentTypeWarningReason::NameTooLong);
+                    } else {
+                        if let (Some(attr), Some(original_name), true) =
+                            (&attr, original_name, self.warnings.is_some())
+                        {
+                            if !self.original_names.iter().any(|(name, _)| name == attr) {
+                                self.original_names.push((attr.clone(), original_name));
+                            }
+                        }
+                        self.attr_name = attr;
+                        self.attr_offset = self.token_start - 1;
+                    }
+                }
                 ContentState::Type => self.c_type = attr,
                 ContentState::SubType => self.c_subtype = attr,
                 _ => unreachable!(),
@@ -92,18 +147,35 @@ impl<'x> ContentTypeParser<'x> {
             let attr_part =
                 String::from_utf8_lossy(&stream.data[self.token_start - 1..self.token_end]);
 
+            let details = self.attr_name.as_ref().map(|attr_name| {
+                match self
+                    .details
+                    .iter()
+                    .position(|(name, _, _)| name == attr_name)
+                {
+                    Some(pos) => pos,
+                    None => {
+                        self.details.push((attr_name.clone(), None, None));
+                        self.details.len() - 1
+                    }
+                }
+            });
+
             if self.attr_charset.is_none() {
+                if let Some(details) = details {
+                    self.details[details]
+                        .1
+                        .get_or_insert_with(|| attr_part.clone());
+                }
                 self.attr_charset = attr_part.into();
+            } else if let Some(language) = details
+                .map(|details| &mut self.details[details].2)
+                .filter(|language| language.is_none())
+            {
+                *language = attr_part.into();
             } else {
-                let attr_name =
-                    self.attr_name.as_ref().unwrap_or(&"unknown".into()).clone() + "-language";
-
-                if !self.attributes.iter().any(|(name, _)| name == &attr_name) {
-                    self.attributes.push((attr_name, attr_part));
-                } else {
-                    self.values.push("'".into());
-                    self.values.push(attr_part);
-                }
+                self.values.push("'".into());
+                self.values.push(attr_part);
             }
 
             self.reset_parser();
@@ -131,6 +203,16 @@ impl<'x> ContentTypeParser<'x> {
 
     fn add_value(&mut self, stream: &MessageStream<'x>) {
         if self.attr_name.is_none() {
+            if self.is_dropped {
+                self.is_dropped = false;
+                // Discard the value of a dropped attribute
+                self.values.clear();
+                self.is_continuation = false;
+                self.is_encoded_attribute = false;
+                self.attr_position = 0;
+                self.attr_charset = None;
+                self.reset_parser();
+            }
             return;
         }
 
@@ -138,6 +220,7 @@ impl<'x> ContentTypeParser<'x> {
         let value = if self.token_start > 0 {
             let value = &stream.data[self.token_start - 1..self.token_end];
             Some(if !self.remove_crlf {
+                // Borrows from the input when the value is valid UTF-8
                 String::from_utf8_lossy(value)
             } else {
                 self.remove_crlf = false;
@@ -160,17 +243,16 @@ impl<'x> ContentTypeParser<'x> {
         };
 
         if !self.is_continuation {
-            self.attributes.push((
-                self.attr_name.take().unwrap(),
-                if !has_values {
-                    value.unwrap()
-                } else {
-                    if let Some(value) = value {
-                        self.values.push(value);
-                    }
-                    self.values.concat().into()
-                },
-            ));
+            let attr_name = self.attr_name.take().unwrap();
+            let value = if !has_values {
+                value.unwrap()
+            } else {
+                if let Some(value) = value {
+                    self.values.push(value);
+                }
+                self.values.concat().into()
+            };
+            self.push_attribute(attr_name, value, self.attr_offset);
         } else {
             let attr_name = self.attr_name.take().unwrap();
             let mut value = if let Some(value) = value {
@@ -185,9 +267,16 @@ impl<'x> ContentTypeParser<'x> {
 
             if self.is_encoded_attribute {
                 if let (true, decoded_bytes) = decode_hex(value.as_bytes()) {
+                    // Only the initial section of a continued value declares a charset
                     value = if let Some(decoder) = self
                         .attr_charset
                         .as_ref()
+                        .or_else(|| {
+                            self.details
+                                .iter()
+                                .find(|(name, _, _)| name == &attr_name)
+                                .and_then(|(_, charset, _)| charset.as_ref())
+                        })
                         .and_then(|c| charset_decoder(c.as_bytes()))
                     {
                         decoder(&decoded_bytes).into()
@@ -201,16 +290,26 @@ impl<'x> ContentTypeParser<'x> {
             }
 
             if self.attr_position > 0 {
-                let continuation = (attr_name, self.attr_position, value);
-                if let Some(continuations) = self.continuations.as_mut() {
+                let continuation = (
+                    attr_name,
+                    self.attr_position,
+                    value,
+                    self.attributes.len(),
+                    self.attr_offset,
+                );
+                let continuations = self.continuations.get_or_insert_with(Vec::new);
+                if continuations.len() < self.max_continuations {
                     continuations.push(continuation);
                 } else {
-                    self.continuations = Some(vec![continuation]);
+                    self.warn(
+                        self.attr_offset,
+                        ContentTypeWarningReason::TooManyContinuations,
+                    );
                 }
 
                 self.attr_position = 0;
             } else {
-                self.attributes.push((attr_name, value));
+                self.push_attribute(attr_name, value, self.attr_offset);
             }
             self.is_continuation = false;
             self.attr_charset = None;
@@ -223,12 +322,92 @@ impl<'x> ContentTypeParser<'x> {
         self.reset_parser();
     }
 
+    // Appends a parameter without a value to the preceding unquoted value,
+    // recovering values such as `filename=foo;bar.txt`
+    fn append_to_value(&mut self, stream: &MessageStream<'x>) -> bool {
+        match self.append_pos {
+            Some(pos) if self.token_start > 0 && !self.is_continuation => {
+                let fragment =
+                    String::from_utf8_lossy(&stream.data[self.token_start - 1..self.token_end]);
+                let mut value = Cow::from(format!("{};{fragment}", self.attributes[pos].1));
+                self.truncate_value(&mut value, self.token_start - 1);
+                self.attributes[pos].1 = value;
+                self.is_lower_case = true;
+                self.reset_parser();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn push_attribute(&mut self, name: Cow<'x, str>, mut value: Cow<'x, str>, offset: usize) {
+        self.truncate_value(&mut value, offset);
+
+        // RFC 2231 extended values take precedence over plain values with the
+        // same name, as in `filename="=?utf-8?q?...?="; filename*=utf-8''...`
+        if self.details.iter().any(|(n, _, _)| n == &name) {
+            if let Some(pos) = self.attributes.iter().position(|(n, _)| n == &name) {
+                if self.is_continuation {
+                    self.attributes[pos].1 = value;
+                    let mut pos = pos + 1;
+                    while pos < self.attributes.len() {
+                        if self.attributes[pos].0 == name {
+                            self.attributes.remove(pos);
+                        } else {
+                            pos += 1;
+                        }
+                    }
+                }
+                return;
+            }
+        }
+
+        self.attributes.push((name, value));
+    }
+
+    // Collapses redundant duplicates, conflicting ones are kept
+    fn remove_duplicate_attributes(&mut self) {
+        if self.attributes.len() > 1 {
+            let mut seen = HashSet::with_capacity(self.attributes.len());
+            let mut is_unique = self
+                .attributes
+                .iter()
+                .map(|(name, value)| seen.insert((name.as_ref(), value.as_ref())))
+                .collect::<Vec<_>>()
+                .into_iter();
+            self.attributes.retain(|_| is_unique.next().unwrap_or(true));
+        }
+    }
+
+    fn truncate_value(&mut self, value: &mut Cow<'x, str>, offset: usize) {
+        if value.len() > self.max_value_len {
+            let mut len = self.max_value_len;
+            while !value.is_char_boundary(len) {
+                len -= 1;
+            }
+            match value {
+                Cow::Borrowed(value) => *value = &value[..len],
+                Cow::Owned(value) => value.truncate(len),
+            }
+            self.warn(offset, ContentTypeWarningReason::ValueTooLong);
+        }
+    }
+
     fn add_attr_position(&mut self, stream: &MessageStream<'_>) -> bool {
         if self.token_start > 0 {
             self.attr_position =
-                String::from_utf8_lossy(&stream.data[self.token_start - 1..self.token_end])
+                match String::from_utf8_lossy(&stream.data[self.token_start - 1..self.token_end])
                     .parse()
-                    .unwrap_or(0);
+                {
+                    Ok(position) => position,
+                    Err(_) => {
+                        self.warn(
+                            self.token_start - 1,
+                            ContentTypeWarningReason::InvalidContinuationIndex,
+                        );
+                        0
+                    }
+                };
 
             self.reset_parser();
             true
@@ -237,22 +416,271 @@ impl<'x> ContentTypeParser<'x> {
         }
     }
 
+    // RFC 2231 continuations are concatenated in ascending index order. Gaps
+    // in the sequence are tolerated, segments after a missing index are kept.
+    // The merged attribute takes the position of the first segment found.
     fn merge_continuations(&mut self) {
-        let continuations = self.continuations.as_mut().unwrap();
-        continuations.sort();
-        for (key, _, value) in continuations.drain(..) {
-            if let Some((_, old_value)) = self.attributes.iter_mut().find(|(name, _)| name == &key)
-            {
-                *old_value = format!("{old_value}{value}").into();
+        let mut continuations = self
+            .continuations
+            .take()
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .collect::<Vec<_>>();
+        continuations.sort_by(|(_, a), (_, b)| (&a.0, a.1, &a.2).cmp(&(&b.0, b.1, &b.2)));
+
+        // Plain attributes sort after segments preceding the same number of attributes
+        let mut attributes = std::mem::take(&mut self.attributes)
+            .into_iter()
+            .enumerate()
+            .map(|(pos, (name, value))| ((pos, 1, 0), name, value))
+            .collect::<Vec<_>>();
+        for (seq, (key, _, value, insert_pos, offset)) in continuations {
+            let order = (insert_pos, 0, seq);
+            if let Some(attr) = attributes.iter_mut().find(|(_, name, _)| name == &key) {
+                let mut value = Cow::from(format!("{}{value}", attr.2));
+                self.truncate_value(&mut value, offset);
+                attr.0 = std::cmp::min(attr.0, order);
+                attr.2 = value;
             } else {
-                self.attributes.push((key, value));
+                let mut value = value;
+                self.truncate_value(&mut value, offset);
+                attributes.push((order, key, value));
             }
         }
+        attributes.sort_by_key(|(order, _, _)| *order);
+        self.attributes = attributes
+            .into_iter()
+            .map(|(_, name, value)| (name, value))
+            .collect();
+    }
+
+    fn into_content_type(self) -> HeaderValue<'x> {
+        match self.into_result() {
+            Ok(content_type) => HeaderValue::ContentType(content_type),
+            Err(_) => HeaderValue::Empty,
+        }
+    }
+
+    fn into_result(self) -> Result<ContentType<'x>, ContentTypeError> {
+        self.into_parts().0
+    }
+
+    // Details are only collected when warnings are requested
+    fn into_parts(
+        mut self,
+    ) -> (
+        Result<ContentType<'x>, ContentTypeError>,
+        ContentTypeDetails<'x>,
+    ) {
+        if self.continuations.is_some() {
+            self.merge_continuations();
+        }
+        self.remove_duplicate_attributes();
+
+        let details = if let Some(warnings) = self.warnings.take() {
+            ContentTypeDetails {
+                attributes: std::mem::take(&mut self.details)
+                    .into_iter()
+                    .filter_map(|(name, charset, language)| {
+                        let value = self
+                            .attributes
+                            .iter()
+                            .find(|(attr_name, _)| attr_name == &name)?
+                            .1
+                            .clone();
+                        Some((
+                            name,
+                            ContentTypeAttribute {
+                                value,
+                                charset,
+                                language,
+                            },
+                        ))
+                    })
+                    .collect(),
+                original_names: self
+                    .attributes
+                    .iter()
+                    .map(|(name, _)| {
+                        (
+                            name.clone(),
+                            self.original_names
+                                .iter()
+                                .find(|(key, _)| key == name)
+                                .map_or_else(|| name.clone(), |(_, original)| original.clone()),
+                        )
+                    })
+                    .collect(),
+                warnings,
+            }
+        } else {
+            ContentTypeDetails::default()
+        };
+
+        let result = if let Some(content_type) = self.c_type {
+            Ok(ContentType {
+                c_type: content_type,
+                c_subtype: self.c_subtype.take(),
+                attributes: if !self.attributes.is_empty() {
+                    Some(self.attributes)
+                } else {
+                    None
+                },
+            })
+        } else if self.c_subtype.is_some() {
+            Err(ContentTypeError::MissingType)
+        } else if self.has_separator {
+            Err(ContentTypeError::EmptyType)
+        } else if self.has_parameters {
+            Err(ContentTypeError::ParametersOnly)
+        } else {
+            Err(ContentTypeError::Blank)
+        };
+
+        (result, details)
     }
 }
 
 impl<'x> MessageStream<'x> {
+    /// Parses a `Content-Type` header field leniently: a type without a
+    /// subtype, such as `inline` or `text/`, is accepted with no subtype
+    /// rather than rejected, as RFC 5322 (Section 3.6.8) allows for
+    /// optional fields that do not follow their specified syntax.
+    ///
+    /// A quoted value that is not closed before the end of the header field
+    /// ends at the first line break when the folded lines that follow contain
+    /// other tokens, which are then parsed as parameters.
     pub fn parse_content_type(&mut self) -> HeaderValue<'x> {
+        self.content_type_parser(true, None).into_content_type()
+    }
+
+    /// Parses a `Content-Type` header field following the RFC 2045
+    /// (Section 5.1) syntax `type "/" subtype`, returning [`HeaderValue::Empty`]
+    /// when the `/` separator or the subtype are missing.
+    pub fn parse_content_type_strict(&mut self) -> HeaderValue<'x> {
+        match self.content_type_parser(true, None).into_result() {
+            Ok(content_type)
+                if content_type
+                    .c_subtype
+                    .as_ref()
+                    .is_some_and(|subtype| !subtype.is_empty()) =>
+            {
+                HeaderValue::ContentType(content_type)
+            }
+            _ => HeaderValue::Empty,
+        }
+    }
+
+    /// Parses a `Content-Disposition` header field, sharing the parameter
+    /// handling of `Content-Type` including RFC 2231 continuations and
+    /// encoded values. Disposition types have no subtype, so a `/` is kept as
+    /// part of the type, as in `attachment/weird`.
+    pub fn parse_content_disposition(&mut self) -> HeaderValue<'x> {
+        self.content_type_parser(false, None).into_content_type()
+    }
+
+    /// Parses a `Content-Type` header field like [`Self::parse_content_type`]
+    /// but reports why the value could not be parsed instead of returning
+    /// [`HeaderValue::Empty`].
+    pub fn parse_content_type_diagnostic(&mut self) -> Result<ContentType<'x>, ContentTypeError> {
+        self.content_type_parser(true, None).into_result()
+    }
+
+    /// Parses a `Content-Type` header field like [`Self::parse_content_type`],
+    /// also returning the malformed tokens that were skipped.
+    pub fn parse_content_type_with_diagnostics(
+        &mut self,
+    ) -> (HeaderValue<'x>, Vec<ContentTypeWarning>) {
+        let (content_type, details) = self.parse_content_type_details();
+        (content_type, details.warnings)
+    }
+
+    /// Parses a `Content-Type` header field like [`Self::parse_content_type`],
+    /// also returning the information that is not part of the [`ContentType`]
+    /// value.
+    pub fn parse_content_type_details(&mut self) -> (HeaderValue<'x>, ContentTypeDetails<'x>) {
+        let (result, details) = self
+            .content_type_parser(true, Some(Vec::new()))
+            .into_parts();
+        (
+            result.map_or(HeaderValue::Empty, HeaderValue::ContentType),
+            details,
+        )
+    }
+
+    /// Parses a `Content-Disposition` header field like
+    /// [`Self::parse_content_disposition`], also returning the information
+    /// that is not part of the [`ContentType`] value.
+    pub fn parse_content_disposition_details(
+        &mut self,
+    ) -> (HeaderValue<'x>, ContentTypeDetails<'x>) {
+        let (result, details) = self
+            .content_type_parser(false, Some(Vec::new()))
+            .into_parts();
+        (
+            result.map_or(HeaderValue::Empty, HeaderValue::ContentType),
+            details,
+        )
+    }
+
+    // Returns whether the line following a fold starts a new parameter
+    fn is_parameter_fold(&self) -> bool {
+        let mut line = self.data[self.offset()..]
+            .iter()
+            .skip_while(|ch| matches!(ch, b' ' | b'\t'))
+            .peekable();
+        line.peek() == Some(&&b';')
+            || line
+                .find(|ch| matches!(ch, b'=' | b';' | b' ' | b'\t' | b'\r' | b'\n' | b'"' | b'('))
+                .is_some_and(|ch| *ch == b'=')
+    }
+
+    // Returns whether the parenthesis just read is closed before the end of
+    // the unquoted value it is attached to
+    fn is_literal_parenthesis(&self) -> bool {
+        let mut depth = 1;
+        for ch in &self.data[self.offset()..] {
+            match ch {
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return true;
+                    }
+                }
+                b' ' | b'\t' | b'\r' | b'\n' | b';' | b'"' => break,
+                _ => (),
+            }
+        }
+        false
+    }
+
+    // Returns whether a quoted value interrupted by a fold is not closed before
+    // the end of the header, while the folded lines contain other tokens
+    fn is_unterminated_quote_fold(&self) -> bool {
+        let mut has_tokens = false;
+        let mut iter = self.data[self.offset()..].iter().peekable();
+        while let Some(ch) = iter.next() {
+            match ch {
+                b'"' => return false,
+                b'\\' => {
+                    iter.next();
+                    has_tokens = true;
+                }
+                b'\n' if !matches!(iter.peek(), Some(b' ' | b'\t')) => break,
+                b' ' | b'\t' | b'\r' | b'\n' => (),
+                _ => has_tokens = true,
+            }
+        }
+        has_tokens
+    }
+
+    fn content_type_parser(
+        &mut self,
+        has_subtype: bool,
+        warnings: Option<Vec<ContentTypeWarning>>,
+    ) -> ContentTypeParser<'x> {
         let mut parser = ContentTypeParser {
             state: ContentState::Type,
             state_stack: Vec::new(),
@@ -263,10 +691,15 @@ impl<'x> MessageStream<'x> {
             attr_name: None,
             attr_charset: None,
             attr_position: 0,
+            attr_offset: 0,
 
             attributes: Vec::new(),
             values: Vec::new(),
             continuations: None,
+            details: Vec::new(),
+            original_names: Vec::new(),
+            append_pos: None,
+            warnings,
 
             is_continuation: false,
             is_encoded_attribute: false,
@@ -274,6 +707,14 @@ impl<'x> MessageStream<'x> {
             is_token_start: true,
             is_escaped: false,
             remove_crlf: false,
+            is_quote_fold: false,
+            is_dropped: false,
+            has_separator: false,
+            has_parameters: false,
+
+            max_name_len: self.max_attribute_name_len,
+            max_value_len: self.max_attribute_value_len,
+            max_continuations: self.max_attribute_continuations,
 
             token_start: 0,
             token_end: 0,
@@ -295,33 +736,57 @@ impl<'x> MessageStream<'x> {
                     }
                     continue;
                 }
-                b'A'..=b'Z' => {
-                    if parser.is_lower_case {
-                        if let ContentState::Type
-                        | ContentState::SubType
-                        | ContentState::AttributeName = parser.state
-                        {
-                            parser.is_lower_case = false;
-                        }
+                b'A'..=b'Z' if parser.is_lower_case => {
+                    if let ContentState::Type
+                    | ContentState::SubType
+                    | ContentState::AttributeName = parser.state
+                    {
+                        parser.is_lower_case = false;
                     }
                 }
                 b'\n' => {
                     let next_is_space = self.peek_next_is_space();
-                    match parser.state {
+                    let state = parser.state;
+                    match state {
+                        ContentState::AttributeName if parser.append_to_value(self) => {}
                         ContentState::Type
                         | ContentState::AttributeName
                         | ContentState::SubType => {
                             parser.add_attribute(self);
                         }
+                        ContentState::AttributeValue
+                            if next_is_space
+                                && self.lenient_attribute_values
+                                && parser.token_start > 0
+                                && !self.is_parameter_fold() =>
+                        {
+                            // Illegal fold within an unquoted value, the value continues
+                            // after the whitespace
+                            parser.values.push(String::from_utf8_lossy(
+                                &self.data[parser.token_start - 1..parser.token_end],
+                            ));
+                            parser.reset_parser();
+                            while self.try_next_is_space() {}
+                            continue;
+                        }
                         ContentState::AttributeValue => {
                             parser.add_value(self);
                         }
                         ContentState::AttributeQuotedValue => {
-                            if next_is_space {
+                            // Folds are part of the quoted value only if the quote is closed
+                            // or if nothing but whitespace follows
+                            if next_is_space
+                                && (parser.is_quote_fold || !self.is_unterminated_quote_fold())
+                            {
+                                parser.is_quote_fold = true;
                                 self.next();
                                 parser.remove_crlf = true;
                                 continue;
                             } else {
+                                parser.warn(
+                                    self.offset() - 1,
+                                    ContentTypeWarningReason::UnterminatedQuote,
+                                );
                                 parser.add_value(self);
                             }
                         }
@@ -337,39 +802,41 @@ impl<'x> MessageStream<'x> {
                         }
                         continue;
                     } else {
-                        if parser.continuations.is_some() {
-                            parser.merge_continuations();
-                        }
-
-                        return if let Some(content_type) = parser.c_type {
-                            HeaderValue::ContentType(ContentType {
-                                c_type: content_type,
-                                c_subtype: parser.c_subtype.take(),
-                                attributes: if !parser.attributes.is_empty() {
-                                    Some(parser.attributes)
-                                } else {
-                                    None
-                                },
-                            })
-                        } else {
-                            HeaderValue::Empty
-                        };
+                        return parser;
                     }
                 }
-                b'/' if parser.state == ContentState::Type => {
+                b'/' if has_subtype && parser.state == ContentState::Type => {
                     parser.add_attribute(self);
+                    parser.has_separator = true;
                     parser.state = ContentState::SubType;
                     continue;
                 }
                 b';' => match parser.state {
-                    ContentState::Type | ContentState::SubType | ContentState::AttributeName => {
+                    ContentState::Type | ContentState::SubType => {
                         parser.add_attribute(self);
+                        parser.has_parameters = true;
                         parser.state = ContentState::AttributeName;
                         continue;
                     }
+                    ContentState::AttributeName => {
+                        if parser.append_to_value(self) {
+                            continue;
+                        }
+                        // Attribute without a value
+                        parser.reset_parser();
+                        parser.attr_name = None;
+                        parser.is_lower_case = true;
+                        continue;
+                    }
                     ContentState::AttributeValue => {
                         if !parser.is_escaped {
+                            let num_attributes = parser.attributes.len();
                             parser.add_value(self);
+                            if self.lenient_attribute_values
+                                && parser.attributes.len() > num_attributes
+                            {
+                                parser.append_pos = Some(num_attributes);
+                            }
                             parser.state = ContentState::AttributeName;
                         } else {
                             parser.is_escaped = false;
@@ -382,18 +849,27 @@ impl<'x> MessageStream<'x> {
                     if !parser.is_continuation {
                         parser.is_continuation = parser.add_attribute(self);
                     } else if !parser.is_encoded_attribute {
-                        parser.add_attr_position(self);
+                        if !parser.add_attr_position(self) {
+                            parser.warn(self.offset() - 1, ContentTypeWarningReason::DuplicateStar);
+                        }
                         parser.is_encoded_attribute = true;
                     } else {
                         // Malformed data, reset parser.
+                        parser.warn(self.offset() - 1, ContentTypeWarningReason::DuplicateStar);
                         parser.reset_parser();
                     }
                     continue;
                 }
                 b'=' => match parser.state {
                     ContentState::AttributeName => {
+                        parser.append_pos = None;
                         if !parser.is_continuation {
-                            if !parser.add_attribute(self) {
+                            // The attribute name might have been added before a comment
+                            if !parser.add_attribute(self) && parser.attr_name.is_none() {
+                                parser.warn(
+                                    self.offset() - 1,
+                                    ContentTypeWarningReason::EmptyAttributeName,
+                                );
                                 continue;
                             }
                         } else if !parser.is_encoded_attribute {
@@ -409,6 +885,9 @@ impl<'x> MessageStream<'x> {
                     ContentState::AttributeValue | ContentState::AttributeQuotedValue
                         if parser.is_token_start && self.peek_char(b'?') =>
                     {
+                        // Encoded-words are not allowed in parameter values but some
+                        // senders use them, even for the charset. The decoded text is
+                        // used as is, without looking for further encoded-words in it.
                         self.checkpoint();
                         if let Some(token) = self.decode_rfc2047() {
                             parser.add_partial_value(self, false);
@@ -425,6 +904,7 @@ impl<'x> MessageStream<'x> {
                             parser.is_token_start = true;
                         }
                         parser.state = ContentState::AttributeQuotedValue;
+                        parser.is_quote_fold = false;
                         continue;
                     }
                     ContentState::AttributeQuotedValue => {
@@ -460,6 +940,14 @@ impl<'x> MessageStream<'x> {
                     parser.add_attribute_parameter(self);
                     continue;
                 }
+                b'(' if parser.state == ContentState::AttributeValue
+                    && self.lenient_attribute_values
+                    && parser.token_start > 0
+                    && !parser.is_token_start
+                    && self.is_literal_parenthesis() =>
+                {
+                    // Parentheses within an unquoted value, as in `report(final).pdf`
+                }
                 b'(' if parser.state != ContentState::AttributeQuotedValue => {
                     if !parser.is_escaped {
                         match parser.state {
@@ -510,12 +998,1035 @@ impl<'x> MessageStream<'x> {
             }
         }
 
-        HeaderValue::Empty
+        // End of input reached without a trailing newline
+        let state = parser.state;
+        match state {
+            ContentState::AttributeName if parser.append_to_value(self) => {}
+            ContentState::Type | ContentState::AttributeName | ContentState::SubType => {
+                parser.add_attribute(self);
+            }
+            ContentState::AttributeValue => {
+                parser.add_value(self);
+            }
+            ContentState::AttributeQuotedValue => {
+                parser.warn(self.offset(), ContentTypeWarningReason::UnterminatedQuote);
+                parser.add_value(self);
+            }
+            ContentState::Comment => {
+                // Unterminated comment, tokens preceding it were added when it was opened
+            }
+        }
+
+        parser
     }
 }
 #[cfg(test)]
 mod tests {
-    use crate::parsers::{fields::load_tests, MessageStream};
+    use std::borrow::Cow;
+
+    use crate::{
+        parsers::{fields::load_tests, MessageStream},
+        ContentType, ContentTypeError, ContentTypeWarning, ContentTypeWarningReason, HeaderName,
+        HeaderValue, MessageParser, MimeHeaders,
+    };
+
+    #[test]
+    fn parse_content_type_borrowed() {
+        // Values that need no transformation borrow from the input
+        for (input, expected) in [
+            ("text/plain; charset=utf-8\n", true),
+            ("text/plain; name=\"a file.txt\"\n", true),
+            ("text/plain;\n charset=utf-8 (comment)\n", true),
+            ("text/plain; charset=\"utf-8\"; format=flowed", true),
+            ("text/plain; name=\"a\n b\"\n", false),
+            ("text/plain; name=\"a\\\"b\"\n", false),
+            ("text/plain; name*=utf-8''a%20b\n", false),
+            ("text/plain; name*0=a; name*1=b\n", false),
+        ] {
+            let content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type()
+                .into_content_type()
+                .unwrap();
+            assert!(
+                matches!(content_type.c_type, Cow::Borrowed(_))
+                    && matches!(content_type.c_subtype, Some(Cow::Borrowed(_)))
+            );
+            for (name, value) in content_type.attributes.unwrap() {
+                assert!(matches!(name, Cow::Borrowed(_)), "failed for {input:?}");
+                assert_eq!(
+                    matches!(value, Cow::Borrowed(_)),
+                    expected,
+                    "failed for {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_content_type_limits() {
+        let long_name = "n".repeat(100);
+        let long_value = "v".repeat(100);
+        for (input, expected, is_truncated) in [
+            (
+                "text/plain; charset=utf-8; name=file.txt".to_string(),
+                vec![("charset", "utf-8"), ("name", "file.txt")],
+                false,
+            ),
+            (
+                format!("text/plain; {long_name}=value; charset=utf-8"),
+                vec![("charset", "utf-8")],
+                true,
+            ),
+            (
+                format!("text/plain; {long_name}*0=a; {long_name}*1=b; name=x"),
+                vec![("name", "x")],
+                true,
+            ),
+            (
+                format!("text/plain; name=\"{long_value}\"; charset=utf-8"),
+                vec![("name", &long_value[..32]), ("charset", "utf-8")],
+                true,
+            ),
+            (
+                "text/plain; name=\"ääääääääääääääää\"".to_string(),
+                vec![("name", "ääääääääääääääää")],
+                false,
+            ),
+            (
+                "text/plain; name=\"ääääääääääääääääx\"".to_string(),
+                vec![("name", "ääääääääääääääää")],
+                true,
+            ),
+            (
+                "text/plain; name=\"xääääääääääääääää\"".to_string(),
+                vec![("name", "xäääääääääääääää")],
+                true,
+            ),
+            (
+                format!("text/plain; name*0={long_value}; name*1={long_value}"),
+                vec![("name", &long_value[..32])],
+                true,
+            ),
+            (
+                "text/plain; name*0=0123456789; name*1=0123456789; name*2=0123456789; name*3=0123456789"
+                    .to_string(),
+                vec![("name", "01234567890123456789012345678901")],
+                true,
+            ),
+        ] {
+            let mut stream = MessageStream::new(input.as_bytes());
+            stream.max_attribute_name_len = 32;
+            stream.max_attribute_value_len = 32;
+            let (content_type, details) = stream.parse_content_type_details();
+            let content_type = content_type.into_content_type().unwrap();
+            assert_eq!(
+                content_type
+                    .attributes()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|(name, value)| (name.as_ref(), value.as_ref()))
+                    .collect::<Vec<_>>(),
+                expected,
+                "failed for {input:?}"
+            );
+            assert_eq!(details.is_truncated(), is_truncated, "failed for {input:?}");
+        }
+    }
+
+    #[test]
+    fn parse_content_type_continuation_limit() {
+        use crate::MimeHeaders;
+
+        let mut input = String::from("application/octet-stream");
+        for pos in 0..5000 {
+            input.push_str(&format!(";\n name*{pos}*=ab%20"));
+        }
+        input.push_str("; charset=utf-8\n");
+
+        let (content_type, details) =
+            MessageStream::new(input.as_bytes()).parse_content_type_details();
+        let content_type = content_type.into_content_type().unwrap();
+        assert_eq!(content_type.attribute("name").map(|v| v.len()), Some(3003));
+        assert_eq!(content_type.attribute("charset"), Some("utf-8"));
+        assert!(details.is_truncated());
+        assert_eq!(details.warnings().len(), 5000 - 1001);
+        assert!(details
+            .warnings()
+            .iter()
+            .all(|warning| warning.reason == ContentTypeWarningReason::TooManyContinuations));
+
+        let raw_message = format!("Content-Type: {input}\nhello\n");
+        let parser = crate::MessageParser::new().with_max_attribute_continuations(3);
+        let message = parser.parse(raw_message.as_bytes()).unwrap();
+        let content_type = message.parts[0].content_type().unwrap();
+        assert_eq!(content_type.attribute("name"), Some("ab ab ab ab "));
+        let details = parser
+            .content_type_details(&message.raw_message[..], &message.parts[0].headers[0])
+            .unwrap();
+        assert!(details.is_truncated());
+        assert_eq!(
+            details.warnings()[0],
+            ContentTypeWarning {
+                // Offsets are relative to the value, which includes the space after the colon
+                offset: input.find("name*4*").unwrap() + 1,
+                reason: ContentTypeWarningReason::TooManyContinuations,
+            }
+        );
+        assert!(!MessageStream::new(b"text/plain; name*0=a; name*1=b\n")
+            .parse_content_type_details()
+            .1
+            .is_truncated());
+    }
+
+    #[test]
+    fn parse_content_type_unterminated_comment() {
+        for (input, subtype, charset) in [
+            (
+                "text/plain; charset=utf-8 (unterminated comment",
+                Some("plain"),
+                Some("utf-8"),
+            ),
+            (
+                "text/plain; charset=utf-8 (unterminated comment\n",
+                Some("plain"),
+                Some("utf-8"),
+            ),
+            (
+                "text/plain; charset=utf-8 (nested (comment)",
+                Some("plain"),
+                Some("utf-8"),
+            ),
+            ("text/plain (unterminated comment", Some("plain"), None),
+            ("text (unterminated comment", None, None),
+            (
+                "text/plain; charset=\"utf-8\" (unterminated \\) comment",
+                Some("plain"),
+                Some("utf-8"),
+            ),
+        ] {
+            let content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type()
+                .into_content_type()
+                .unwrap_or_else(|| panic!("failed for {input:?}"));
+            assert_eq!(content_type.ctype(), "text", "failed for {input:?}");
+            assert_eq!(content_type.subtype(), subtype, "failed for {input:?}");
+            assert_eq!(content_type.charset(), charset, "failed for {input:?}");
+            assert_eq!(
+                content_type.attributes().map_or(0, |a| a.len()),
+                charset.map_or(0, |_| 1),
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_content_type_strict() {
+        for (input, lenient, strict) in [
+            ("text/plain\n", Some(("text", Some("plain"))), true),
+            (
+                "text/plain; charset=utf-8\n",
+                Some(("text", Some("plain"))),
+                true,
+            ),
+            ("inline\n", Some(("inline", None)), false),
+            ("test/\n", Some(("test", None)), false),
+            ("text/ ; charset=utf-8\n", Some(("text", None)), false),
+            ("text; charset=utf-8\n", Some(("text", None)), false),
+            ("/plain\n", None, false),
+            ("\n", None, false),
+        ] {
+            let content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type()
+                .into_content_type();
+            assert_eq!(
+                content_type.as_ref().map(|ct| (ct.ctype(), ct.subtype())),
+                lenient,
+                "failed for {input:?}"
+            );
+
+            let strict_content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type_strict()
+                .into_content_type();
+            assert_eq!(
+                strict_content_type,
+                if strict { content_type } else { None },
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_content_type_diagnostic() {
+        for (input, expected) in [
+            ("/invalid\n", ContentTypeError::MissingType),
+            ("/invalid; charset=utf-8\n", ContentTypeError::MissingType),
+            ("/\n", ContentTypeError::EmptyType),
+            ("/ ; name=value\n", ContentTypeError::EmptyType),
+            (";\n", ContentTypeError::ParametersOnly),
+            ("; charset=utf-8\n", ContentTypeError::ParametersOnly),
+            ("\n", ContentTypeError::Blank),
+            (" (comment)\n", ContentTypeError::Blank),
+        ] {
+            assert_eq!(
+                MessageStream::new(input.as_bytes()).parse_content_type_diagnostic(),
+                Err(expected),
+                "failed for {input:?}"
+            );
+            assert_eq!(
+                MessageStream::new(input.as_bytes()).parse_content_type(),
+                HeaderValue::Empty,
+                "failed for {input:?}"
+            );
+        }
+
+        let content_type = MessageStream::new(b"text/plain; charset=utf-8\n")
+            .parse_content_type_diagnostic()
+            .unwrap();
+        assert_eq!(content_type.ctype(), "text");
+        assert_eq!(content_type.subtype(), Some("plain"));
+        assert_eq!(content_type.attribute("charset"), Some("utf-8"));
+    }
+
+    #[test]
+    fn parse_content_type_with_diagnostics() {
+        use crate::ContentTypeWarningReason::*;
+
+        for (input, expected) in [
+            ("text/plain; charset=utf-8\n", vec![]),
+            ("text/plain; title*0*=us-ascii'en'a%20; title*1=b\n", vec![]),
+            (
+                "text/plain; =value; charset=utf-8\n",
+                vec![(12, EmptyAttributeName)],
+            ),
+            ("text/plain; *=value\n", vec![(13, EmptyAttributeName)]),
+            ("text/plain; name**=value\n", vec![(17, DuplicateStar)]),
+            ("text/plain; name*0**=value\n", vec![(19, DuplicateStar)]),
+            (
+                "text/plain; name*x=value\n",
+                vec![(17, InvalidContinuationIndex)],
+            ),
+            ("text/plain; name=\"value\n", vec![(23, UnterminatedQuote)]),
+            ("text/plain; name=\"value", vec![(23, UnterminatedQuote)]),
+            (
+                "malicious/attempt; 1*2*=a; 3**=b; 4***=c; 15*x=g; =h\n",
+                vec![
+                    (29, DuplicateStar),
+                    (36, DuplicateStar),
+                    (37, DuplicateStar),
+                    (45, InvalidContinuationIndex),
+                    (50, EmptyAttributeName),
+                ],
+            ),
+        ] {
+            let (content_type, warnings) =
+                MessageStream::new(input.as_bytes()).parse_content_type_with_diagnostics();
+            assert_eq!(
+                content_type,
+                MessageStream::new(input.as_bytes()).parse_content_type(),
+                "failed for {input:?}"
+            );
+            assert_eq!(
+                warnings
+                    .iter()
+                    .map(|warning| (warning.offset, warning.reason))
+                    .collect::<Vec<_>>(),
+                expected,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_content_type_many_attributes() {
+        let mut input = "text/plain".to_string();
+        for i in 0..80_000 {
+            input.push_str(&format!("; a{}=v", i % 40_000));
+        }
+        input.push('\n');
+        let mut stream = MessageStream::new(input.as_bytes());
+        let content_type = stream.parse_content_type();
+        let attributes = content_type
+            .as_content_type()
+            .unwrap()
+            .attributes()
+            .unwrap();
+        assert_eq!(attributes.len(), 40_000);
+        assert_eq!(
+            attributes.last().map(|(n, v)| (n.as_ref(), v.as_ref())),
+            Some(("a39999", "v"))
+        );
+    }
+
+    #[test]
+    fn parse_content_type_many_quote_folds() {
+        // Each fold of a quoted value must not rescan the rest of the header
+        let input = format!(
+            "text/plain; name=\"a{}\"\nSubject: x\n",
+            "\n b".repeat(80_000)
+        );
+        let mut stream = MessageStream::new(input.as_bytes());
+        let content_type = stream.parse_content_type();
+        let name = content_type
+            .as_content_type()
+            .unwrap()
+            .attribute("name")
+            .unwrap();
+        assert_eq!(name.len(), 160_001);
+        assert!(name.starts_with("a b b"));
+        assert_eq!(&input[stream.offset()..], "Subject: x\n");
+
+        let input = format!(
+            "text/plain; name=\"a{}\nSubject: x\n",
+            "\n b".repeat(80_000)
+        );
+        let mut stream = MessageStream::new(input.as_bytes());
+        let (content_type, found) = stream.parse_content_type_with_diagnostics();
+        assert_eq!(
+            content_type.as_content_type().unwrap().attribute("name"),
+            Some("a")
+        );
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn parse_content_type_unterminated_quote() {
+        use crate::ContentTypeWarningReason::UnterminatedQuote;
+
+        for (input, expected, warnings, remainder) in [
+            // Quote closed on a folded line
+            (
+                "inva/lid; name=\"   \n    \"; test=test\nSubject: x\n",
+                vec![("name", "       "), ("test", "test")],
+                vec![],
+                "Subject: x\n",
+            ),
+            (
+                "text/plain; name=\"a\n b\"; charset=utf-8\nSubject: x\n",
+                vec![("name", "a b"), ("charset", "utf-8")],
+                vec![],
+                "Subject: x\n",
+            ),
+            // Only whitespace follows the unterminated quote
+            (
+                "inva/lid; name=\"   \n    \nSubject: x\n",
+                vec![("name", "       ")],
+                vec![(24, UnterminatedQuote)],
+                "Subject: x\n",
+            ),
+            // Quote never closed before the end of the header
+            (
+                "text/plain; name=\"a\n charset=utf-8\nSubject: \"x\"\n",
+                vec![("name", "a"), ("charset", "utf-8")],
+                vec![(19, UnterminatedQuote)],
+                "Subject: \"x\"\n",
+            ),
+            (
+                "multipart/mixed; name=\"a;\n\tboundary=b\n",
+                vec![("name", "a;"), ("boundary", "b")],
+                vec![(25, UnterminatedQuote)],
+                "",
+            ),
+            (
+                "text/plain; name=\"a\nSubject: \"x\"\n",
+                vec![("name", "a")],
+                vec![(19, UnterminatedQuote)],
+                "Subject: \"x\"\n",
+            ),
+        ] {
+            let mut stream = MessageStream::new(input.as_bytes());
+            let (content_type, found) = stream.parse_content_type_with_diagnostics();
+            assert_eq!(
+                content_type
+                    .as_content_type()
+                    .unwrap()
+                    .attributes()
+                    .unwrap()
+                    .iter()
+                    .map(|(name, value)| (name.as_ref(), value.as_ref()))
+                    .collect::<Vec<_>>(),
+                expected,
+                "failed for {input:?}"
+            );
+            assert_eq!(
+                found
+                    .iter()
+                    .map(|warning| (warning.offset, warning.reason))
+                    .collect::<Vec<_>>(),
+                warnings,
+                "failed for {input:?}"
+            );
+            assert_eq!(&input[stream.offset()..], remainder, "failed for {input:?}");
+        }
+    }
+
+    #[test]
+    fn parse_content_disposition() {
+        for (input, c_type, filename) in [
+            (
+                "attachment/weird; filename=x\n",
+                "attachment/weird",
+                Some("x"),
+            ),
+            (
+                "attachment/weird; filename=\"a/b.txt\"\n",
+                "attachment/weird",
+                Some("a/b.txt"),
+            ),
+            ("Inline/ ; filename*=utf-8''x%2Fy\n", "inline/", Some("x/y")),
+            ("attachment; filename=x\n", "attachment", Some("x")),
+            ("attachment\n", "attachment", None),
+        ] {
+            let disposition = MessageStream::new(input.as_bytes()).parse_content_disposition();
+            let disposition = disposition.as_content_type().unwrap();
+            assert_eq!(disposition.ctype(), c_type, "failed for {input:?}");
+            assert_eq!(disposition.subtype(), None, "failed for {input:?}");
+            assert_eq!(
+                disposition.attribute("filename"),
+                filename,
+                "failed for {input:?}"
+            );
+        }
+
+        assert_eq!(
+            MessageStream::new(b"/\n").parse_content_disposition(),
+            HeaderValue::ContentType(ContentType {
+                c_type: "/".into(),
+                c_subtype: None,
+                attributes: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_content_disposition_rfc2231() {
+        // RFC 2231 parameters are parsed as in Content-Type header fields
+        let mut tests_run = 0;
+        for test in load_tests::<Option<ContentType<'static>>>("content_type.json") {
+            let Some((c_type, params)) = test.header.split_once(['\n', ';']) else {
+                continue;
+            };
+            if test.expected.is_none() || !params.contains('*') || c_type.contains(' ') {
+                continue;
+            }
+            let input = format!("attachment{}", &test.header[c_type.len()..]);
+            let (content_type, content_type_details) =
+                MessageStream::new(test.header.as_bytes()).parse_content_type_details();
+            let (disposition, disposition_details) =
+                MessageStream::new(input.as_bytes()).parse_content_disposition_details();
+            let (content_type, disposition) = (
+                content_type.into_content_type().unwrap(),
+                disposition.into_content_type().unwrap(),
+            );
+
+            assert_eq!(disposition.ctype(), "attachment", "failed for {input:?}");
+            assert_eq!(
+                (disposition.attributes, disposition_details.attributes),
+                (content_type.attributes, content_type_details.attributes),
+                "failed for {input:?}"
+            );
+            tests_run += 1;
+        }
+        assert!(tests_run >= 10);
+
+        let (disposition, details) = MessageStream::new(
+            concat!(
+                "attachment;\n filename*0*=iso-8859-1'es'%D1and%FA;\n",
+                " filename*1*=%20r%E1pido.pdf; size=1024\n"
+            )
+            .as_bytes(),
+        )
+        .parse_content_disposition_details();
+        let disposition = disposition.into_content_type().unwrap();
+        assert_eq!(disposition.attribute("filename"), Some("Ñandú rápido.pdf"));
+        assert_eq!(disposition.attribute("size"), Some("1024"));
+        let details = details.attribute_details("filename").unwrap();
+        assert_eq!(details.charset.as_deref(), Some("iso-8859-1"));
+        assert_eq!(details.language.as_deref(), Some("es"));
+    }
+
+    #[test]
+    fn parse_content_disposition_extended_precedence() {
+        for input in [
+            "attachment; filename=\"=?utf-8?Q?Rechnung.pdf?=\"; filename*=utf-8''Factura%20ma%C3%B1ana.pdf\n",
+            "attachment; filename*=utf-8''Factura%20ma%C3%B1ana.pdf; filename=\"plain.pdf\"\n",
+            "attachment; filename=plain.pdf; filename*0*=utf-8''Factura%20;\n filename*1*=ma%C3%B1ana.pdf; filename=other.pdf\n",
+        ] {
+            let (disposition, details) =
+                MessageStream::new(input.as_bytes()).parse_content_disposition_details();
+            let disposition = disposition.into_content_type().unwrap();
+            assert_eq!(
+                disposition.attributes(),
+                Some(&[("filename".into(), "Factura mañana.pdf".into())][..]),
+                "failed for {input:?}"
+            );
+            assert_eq!(
+                details.attribute_details("filename").unwrap().value,
+                "Factura mañana.pdf",
+                "failed for {input:?}"
+            );
+        }
+
+        // Conflicting plain values are kept
+        let disposition = MessageStream::new(b"attachment; filename=a.pdf; filename=b.pdf\n")
+            .parse_content_disposition()
+            .into_content_type()
+            .unwrap();
+        assert_eq!(disposition.attributes().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn content_type_accessors() {
+        let mut has_boundary = false;
+        let mut has_charset = false;
+
+        for test in load_tests::<Option<ContentType<'static>>>("content_type.json") {
+            let Some(content_type) = test.expected else {
+                continue;
+            };
+
+            for (name, _) in content_type.attributes().unwrap_or_default() {
+                let value = content_type
+                    .attributes()
+                    .unwrap()
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v.as_ref());
+                assert_eq!(content_type.attribute(&name.to_ascii_uppercase()), value);
+                assert!(content_type.has_attribute(&name.to_ascii_uppercase()));
+            }
+
+            has_boundary |= content_type.boundary().is_some();
+            has_charset |= content_type.charset().is_some();
+            assert_eq!(
+                content_type.boundary(),
+                content_type.attribute("Boundary"),
+                "failed for {:?}",
+                test.header
+            );
+            assert_eq!(
+                content_type.charset(),
+                content_type.attribute("CHARSET"),
+                "failed for {:?}",
+                test.header
+            );
+            assert_eq!(
+                content_type.is_multipart(),
+                content_type.ctype() == "multipart",
+                "failed for {:?}",
+                test.header
+            );
+            assert_eq!(
+                content_type.is_message(),
+                content_type.ctype() == "message",
+                "failed for {:?}",
+                test.header
+            );
+        }
+        assert!(has_boundary && has_charset);
+
+        let content_type = MessageStream::new(b"Multipart/Mixed; BOUNDARY=\"b1\"\n")
+            .parse_content_type()
+            .into_content_type()
+            .unwrap();
+        assert!(content_type.is_multipart());
+        assert!(!content_type.is_message());
+        assert_eq!(content_type.boundary(), Some("b1"));
+        assert_eq!(content_type.charset(), None);
+    }
+
+    #[test]
+    fn content_type_display() {
+        let content_type = MessageStream::new(b"multipart/mixed; boundary=\"simple boundary\"\n")
+            .parse_content_type()
+            .into_content_type()
+            .unwrap();
+        assert_eq!(content_type.full_type(), "multipart/mixed");
+        assert_eq!(
+            content_type.to_string(),
+            "multipart/mixed; boundary=\"simple boundary\""
+        );
+
+        for test in load_tests::<Option<ContentType<'static>>>("content_type.json") {
+            let Some(content_type) = test.expected else {
+                continue;
+            };
+            // RFC 2231 charsets and languages are not written back
+            let header = format!("{content_type}\n");
+            let parsed = MessageStream::new(header.as_bytes())
+                .parse_content_type()
+                .into_content_type()
+                .unwrap();
+            assert_eq!(
+                (parsed.c_type, parsed.c_subtype, parsed.attributes),
+                (
+                    content_type.c_type,
+                    content_type.c_subtype,
+                    content_type.attributes
+                ),
+                "failed for {:?} -> {header:?}",
+                test.header
+            );
+        }
+
+        for (input, full_type, subtype, output) in [
+            ("inline\n", "inline", "none", "inline"),
+            ("text/plain\n", "text/plain", "plain", "text/plain"),
+            (
+                "text/plain; name=\"a \\\"b\\\" c\"; y=a/b\n",
+                "text/plain",
+                "plain",
+                "text/plain; name=\"a \\\"b\\\" c\"; y=\"a/b\"",
+            ),
+        ] {
+            let content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type()
+                .into_content_type()
+                .unwrap();
+            assert_eq!(content_type.full_type(), full_type);
+            assert_eq!(content_type.subtype_or("none"), subtype);
+            assert_eq!(content_type.to_string(), output);
+        }
+    }
+
+    #[test]
+    fn parse_content_type_attribute_order() {
+        for (input, expected) in [
+            (
+                concat!(
+                    "message/external-body; name=\"BodyFormats.ps\";\n",
+                    "   site=\"thumper.bellcore.com\"; mode=\"image\";\n",
+                    "  access-type=ANON-FTP; directory=\"pub\";\n",
+                    "  expiration=\"Fri, 14 Jun 1991 19:13:14 -0400 (EDT)\"\n"
+                ),
+                &[
+                    "name",
+                    "site",
+                    "mode",
+                    "access-type",
+                    "directory",
+                    "expiration",
+                ][..],
+            ),
+            (
+                concat!(
+                    "application/x-stuff; a=1; title*1*=%2A%2A%2Afun%2A%2A%2A%20;\n",
+                    " b=2; title*0*=us-ascii'en'This%20is%20even%20more%20; c=3;\n",
+                    " title*2=\"isn't it!\"\n"
+                ),
+                &["a", "title", "b", "c"][..],
+            ),
+        ] {
+            let content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type()
+                .into_content_type()
+                .unwrap();
+            assert_eq!(
+                content_type
+                    .attributes()
+                    .unwrap()
+                    .iter()
+                    .map(|(name, _)| name.as_ref())
+                    .collect::<Vec<_>>(),
+                expected,
+                "failed for {input:?}"
+            );
+
+            // Source order is kept when formatting the header value
+            let header = format!("{content_type}\n");
+            let mut last_pos = 0;
+            for name in expected {
+                let pos = header.find(&format!(" {name}=")).unwrap();
+                assert!(pos > last_pos, "failed for {header:?}");
+                last_pos = pos;
+            }
+        }
+    }
+
+    #[test]
+    fn parse_content_type_attribute_details() {
+        let (content_type, attributes) = MessageStream::new(
+            concat!(
+                "application/x-stuff; title*1*=%2A%2A%2Afun%2A%2A%2A%20;\n",
+                " title*0*=us-ascii'en'This%20is%20even%20more%20;\n",
+                " title*2=\"isn't it!\"; name=plain; key*=utf-8''empty\n"
+            )
+            .as_bytes(),
+        )
+        .parse_content_type_details();
+        let content_type = content_type.into_content_type().unwrap();
+
+        assert_eq!(
+            content_type.attribute("title"),
+            Some("This is even more ***fun*** isn't it!")
+        );
+        let details = attributes.attribute_details("TITLE").unwrap();
+        assert_eq!(details.value, "This is even more ***fun*** isn't it!");
+        assert_eq!(details.charset.as_deref(), Some("us-ascii"));
+        assert_eq!(details.language.as_deref(), Some("en"));
+        assert!(!content_type.has_attribute("title-language"));
+
+        assert!(attributes.attribute_details("name").is_none());
+        let details = attributes.attribute_details("key").unwrap();
+        assert_eq!(details.value, "empty");
+        assert_eq!(details.charset.as_deref(), Some("utf-8"));
+        assert_eq!(details.language, None);
+
+        // Details are not returned by the plain parser
+        assert_eq!(
+            MessageStream::new(b"text/plain; key*=utf-8''empty\n")
+                .parse_content_type()
+                .into_content_type()
+                .unwrap(),
+            ContentType {
+                c_type: "text".into(),
+                c_subtype: Some("plain".into()),
+                attributes: Some(vec![("key".into(), "empty".into())]),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_content_type_original_names() {
+        let (content_type, details) = MessageStream::new(
+            concat!(
+                "Application/Octet-Stream; X-Mac-Type=\"42494E41\"; name=file.bin;\n",
+                " X-Mac-Creator=\"4D4F5353\"; Title*0=\"A \"; Title*1=\"B\"\n"
+            )
+            .as_bytes(),
+        )
+        .parse_content_type_details();
+        let content_type = content_type.into_content_type().unwrap();
+
+        assert_eq!(content_type.ctype(), "application");
+        assert_eq!(content_type.attribute("x-mac-type"), Some("42494E41"));
+        for (name, original_name) in [
+            ("x-mac-type", Some("X-Mac-Type")),
+            ("X-MAC-CREATOR", Some("X-Mac-Creator")),
+            ("name", Some("name")),
+            ("title", Some("Title")),
+            ("x-mac-unknown", None),
+        ] {
+            assert_eq!(
+                details.attribute_original_name(name),
+                original_name,
+                "failed for {name:?}"
+            );
+        }
+        assert_eq!(
+            details
+                .into_owned()
+                .attribute_original_name("x-mac-creator"),
+            Some("X-Mac-Creator")
+        );
+
+        // The original casing does not affect comparisons
+        assert_eq!(
+            content_type,
+            MessageStream::new(b"application/octet-stream; x-mac-type=42494E41; name=file.bin; x-mac-creator=4D4F5353; title=\"A B\"\n")
+                .parse_content_type()
+                .into_content_type()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_content_type_lenient_values() {
+        for (input, strict, lenient) in [
+            (
+                "attachment; filename=foo;bar.txt\n",
+                vec![("filename", "foo")],
+                vec![("filename", "foo;bar.txt")],
+            ),
+            (
+                "attachment; filename=foo;bar.txt",
+                vec![("filename", "foo")],
+                vec![("filename", "foo;bar.txt")],
+            ),
+            (
+                "attachment; filename=a;B;c.txt; size=10\n",
+                vec![("filename", "a"), ("size", "10")],
+                vec![("filename", "a;B;c.txt"), ("size", "10")],
+            ),
+            (
+                "attachment; filename=\"foo\";bar.txt\n",
+                vec![("filename", "foo")],
+                vec![("filename", "foo")],
+            ),
+            (
+                "attachment; filename=foo; size=10\n",
+                vec![("filename", "foo"), ("size", "10")],
+                vec![("filename", "foo"), ("size", "10")],
+            ),
+        ] {
+            for (is_lenient, expected) in [(false, strict), (true, lenient)] {
+                let mut stream = MessageStream::new(input.as_bytes());
+                stream.lenient_attribute_values = is_lenient;
+                let content_type = stream
+                    .parse_content_disposition()
+                    .into_content_type()
+                    .unwrap();
+                assert_eq!(
+                    content_type
+                        .attributes()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|(name, value)| (name.as_ref(), value.as_ref()))
+                        .collect::<Vec<_>>(),
+                    expected,
+                    "failed for {input:?} (lenient: {is_lenient})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_content_type_lenient_fold() {
+        for (input, strict, lenient) in [
+            (
+                "multipart/mixed; boundary=----=_Part_0_\r\n 1234.5678\r\n",
+                vec![("boundary", "----=_Part_0_")],
+                vec![("boundary", "----=_Part_0_1234.5678")],
+            ),
+            (
+                "multipart/mixed; boundary=abc\n\tdef; charset=utf-8\n",
+                vec![("boundary", "abc"), ("charset", "utf-8")],
+                vec![("boundary", "abcdef"), ("charset", "utf-8")],
+            ),
+            (
+                "text/plain; name=file\n .txt\n",
+                vec![("name", "file")],
+                vec![("name", "file.txt")],
+            ),
+            (
+                "text/plain; name*=utf-8''%C3%\n A1.txt\n",
+                vec![("name", "\u{fffd}")],
+                vec![("name", "á.txt")],
+            ),
+            (
+                "text/plain; charset=utf-8\n format=flowed\n",
+                vec![("charset", "utf-8"), ("format", "flowed")],
+                vec![("charset", "utf-8"), ("format", "flowed")],
+            ),
+            (
+                "text/plain; charset=utf-8\n ; format=flowed\n",
+                vec![("charset", "utf-8"), ("format", "flowed")],
+                vec![("charset", "utf-8"), ("format", "flowed")],
+            ),
+            (
+                "text/plain; name=\"file\n .txt\"\n",
+                vec![("name", "file .txt")],
+                vec![("name", "file .txt")],
+            ),
+        ] {
+            for (is_lenient, expected) in [(false, strict), (true, lenient)] {
+                let mut stream = MessageStream::new(input.as_bytes());
+                stream.lenient_attribute_values = is_lenient;
+                let content_type = stream.parse_content_type().into_content_type().unwrap();
+                assert_eq!(
+                    content_type
+                        .attributes()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|(name, value)| (name.as_ref(), value.as_ref()))
+                        .collect::<Vec<_>>(),
+                    expected,
+                    "failed for {input:?} (lenient: {is_lenient})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_content_type_lenient_parentheses() {
+        for (input, strict, lenient) in [
+            (
+                "application/pdf; name=report(final).pdf\n",
+                vec![("name", "report")],
+                vec![("name", "report(final).pdf")],
+            ),
+            (
+                "application/pdf; name=report(final).pdf; size=10\n",
+                vec![("name", "report"), (".pdf; size", "10")],
+                vec![("name", "report(final).pdf"), ("size", "10")],
+            ),
+            (
+                "application/pdf; name=a((b)c)(d)\n",
+                vec![("name", "a")],
+                vec![("name", "a((b)c)(d)")],
+            ),
+            (
+                "application/pdf; name=report(final.pdf\n",
+                vec![("name", "report")],
+                vec![("name", "report")],
+            ),
+            (
+                "application/pdf; name=report(final copy).pdf\n",
+                vec![("name", "report")],
+                vec![("name", "report")],
+            ),
+            (
+                "application/pdf; name=report (final).pdf\n",
+                vec![("name", "report")],
+                vec![("name", "report")],
+            ),
+            (
+                "application/pdf; name=report.pdf (comment)\n",
+                vec![("name", "report.pdf")],
+                vec![("name", "report.pdf")],
+            ),
+            (
+                "application/pdf; (comment)name=report.pdf\n",
+                vec![("name", "report.pdf")],
+                vec![("name", "report.pdf")],
+            ),
+        ] {
+            for (is_lenient, expected) in [(false, strict), (true, lenient)] {
+                let mut stream = MessageStream::new(input.as_bytes());
+                stream.lenient_attribute_values = is_lenient;
+                let content_type = stream.parse_content_type().into_content_type().unwrap();
+                assert_eq!(
+                    content_type
+                        .attributes()
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|(name, value)| (name.as_ref(), value.as_ref()))
+                        .collect::<Vec<_>>(),
+                    expected,
+                    "failed for {input:?} (lenient: {is_lenient})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn parse_content_type_encoded_charset() {
+        for (input, expected) in [
+            ("text/plain; charset=\"=?utf-8?q?utf-8?=\"\n", Some("utf-8")),
+            (
+                "text/plain; charset==?us-ascii?b?aXNvLTg4NTktMQ==?=\n",
+                Some("iso-8859-1"),
+            ),
+            (
+                "text/plain; charset=\"=?utf-8?q?=3D=3Futf-8=3Fq=3Fx=3F=3D?=\"\n",
+                Some("=?utf-8?q?x?="),
+            ),
+            (
+                "text/plain; charset=\"=?utf-8?q?=00=FF?=\"\n",
+                Some("\0\u{fffd}"),
+            ),
+            (
+                "text/plain; charset=\"=?utf-8?q?unterminated\"\n",
+                Some("=?utf-8?q?unterminated"),
+            ),
+            ("text/plain; charset=\"=?utf-8?q??=\"\n", Some("")),
+        ] {
+            let content_type = MessageStream::new(input.as_bytes())
+                .parse_content_type()
+                .into_content_type()
+                .unwrap();
+            assert_eq!(content_type.charset(), expected, "failed for {input:?}");
+        }
+    }
 
     #[test]
     fn parse_content_fields() {
@@ -543,4 +2054,122 @@ mod tests {
 
         builder.write();*/
     }
+
+    #[test]
+    fn message_strict_content_type() {
+        let input = concat!(
+            "Content-Type: text/\n",
+            "Content-Disposition: inline\n\n",
+            "hello\n",
+        );
+
+        let message = MessageParser::default().parse(input).unwrap();
+        assert_eq!(message.content_type().unwrap().ctype(), "text");
+        assert_eq!(message.content_type().unwrap().subtype(), None);
+
+        let message = MessageParser::new()
+            .with_mime_headers()
+            .header_content_type_strict(HeaderName::ContentType)
+            .parse(input)
+            .unwrap();
+        assert_eq!(message.content_type(), None);
+        assert_eq!(
+            message.content_disposition().unwrap().ctype(),
+            "inline",
+            "only Content-Type is parsed strictly"
+        );
+        assert_eq!(message.body_text(0).unwrap(), "hello\n");
+    }
+
+    #[test]
+    fn message_lenient_attribute_values() {
+        let input = concat!(
+            "Content-Type: application/pdf; name=foo;bar.pdf\n",
+            "Content-Disposition: attachment; filename=foo;bar.pdf\n\n",
+            "pdf\n",
+        );
+
+        let message = MessageParser::default().parse(input).unwrap();
+        assert_eq!(
+            message.attachment(0).unwrap().attachment_name(),
+            Some("foo")
+        );
+
+        let message = MessageParser::default()
+            .with_lenient_attribute_values()
+            .parse(input)
+            .unwrap();
+        let part = message.attachment(0).unwrap();
+        assert_eq!(part.attachment_name(), Some("foo;bar.pdf"));
+        assert_eq!(
+            part.content_type().unwrap().attribute("name"),
+            Some("foo;bar.pdf")
+        );
+
+        let input = concat!(
+            "Content-Type: application/pdf\n",
+            "Content-Disposition: attachment; filename=report(final).pdf\n\n",
+            "pdf\n",
+        );
+        for (parser, expected) in [
+            (MessageParser::default(), "report"),
+            (
+                MessageParser::default().with_lenient_attribute_values(),
+                "report(final).pdf",
+            ),
+        ] {
+            let message = parser.parse(input).unwrap();
+            assert_eq!(
+                message.attachment(0).unwrap().attachment_name(),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn message_lenient_boundary_fold() {
+        let input = concat!(
+            "Subject: folded boundary\r\n",
+            "Content-Type: multipart/mixed; boundary=----=_Part_0_\r\n",
+            " 1234.5678\r\n\r\n",
+            "------=_Part_0_1234.5678\r\n",
+            "Content-Type: text/plain\r\n\r\n",
+            "hello\r\n",
+            "------=_Part_0_1234.5678\r\n",
+            "Content-Type: text/plain\r\n\r\n",
+            "world\r\n",
+            "------=_Part_0_1234.5678--\r\n",
+        );
+
+        let message = MessageParser::default().parse(input).unwrap();
+        assert_eq!(
+            message.content_type().unwrap().boundary(),
+            Some("----=_Part_0_")
+        );
+
+        let message = MessageParser::default()
+            .with_lenient_attribute_values()
+            .parse(input)
+            .unwrap();
+        assert_eq!(
+            message.content_type().unwrap().boundary(),
+            Some("----=_Part_0_1234.5678")
+        );
+        assert_eq!(message.body_text(0).unwrap(), "hello");
+        assert_eq!(message.body_text(1).unwrap(), "world");
+    }
+
+    #[test]
+    fn message_encoded_word_charset() {
+        let message = MessageParser::default()
+            .parse(
+                &b"Subject: test\nContent-Type: text/plain; charset=\"=?us-ascii?q?iso-8859-1?=\"\n\ncaf\xe9\n"[..],
+            )
+            .unwrap();
+        assert_eq!(
+            message.parts[0].content_type().unwrap().charset(),
+            Some("iso-8859-1")
+        );
+        assert_eq!(message.body_text(0).as_deref(), Some("café\n"));
+    }
 }
@@ -19,6 +19,13 @@ pub static MONTH: &[&str] = &[
 ];
 
 impl DateTime {
+    /// Parses a date in any of the RFC822/RFC5322 formats accepted when
+    /// parsing the `Date` header field, returning `None` if the input can't
+    /// be parsed or does not describe a valid date.
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::parse_rfc822(value).filter(|dt| dt.is_valid())
+    }
+
     /// Parses an RFC822 date
     pub fn parse_rfc822(value: &str) -> Option<Self> {
         match MessageStream::new(value.as_bytes()).parse_date() {
@@ -171,31 +178,56 @@ impl DateTime {
         }
     }
 
-    /// Returns true if the date is valid
+    /// Returns true if all the fields are within their range. The day is
+    /// checked against the length of the month, taking leap years into
+    /// account, a second of `60` is accepted for leap seconds and the offset
+    /// can't exceed 24 hours. Out of range dates are not rejected while
+    /// parsing header fields, this method can be used to detect them.
     pub fn is_valid(&self) -> bool {
-        (0..=23).contains(&self.tz_hour)
-            && (1900..=3000).contains(&self.year)
-            && (0..=59).contains(&self.tz_minute)
+        (1900..=3000).contains(&self.year)
             && (1..=12).contains(&self.month)
-            && (1..=31).contains(&self.day)
+            && (1..=self.days_in_month()).contains(&self.day)
             && (0..=23).contains(&self.hour)
             && (0..=59).contains(&self.minute)
-            && (0..=59).contains(&self.second)
+            && (0..=60).contains(&self.second)
+            && (0..=59).contains(&self.tz_minute)
+            && (self.tz_hour as u32 * 60 + self.tz_minute as u32) <= 24 * 60
+    }
+
+    fn days_in_month(&self) -> u8 {
+        match self.month {
+            4 | 6 | 9 | 11 => 30,
+            2 if self.year.is_multiple_of(4)
+                && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            _ => 31,
+        }
+    }
+
+    /// Returns true if the offset is `-0000`, which RFC 5322 uses for times
+    /// in UTC whose local time zone is unknown. Obsolete single letter
+    /// military zones other than `Z` are parsed as `-0000`.
+    pub fn has_unknown_offset(&self) -> bool {
+        self.tz_before_gmt && self.tz_hour == 0 && self.tz_minute == 0
     }
 
-    /// Returns the numbers of seconds since 1970-01-01T00:00:00Z (Unix epoch)
-    /// or None if the date is invalid.
+    /// Returns the numbers of seconds since 1970-01-01T00:00:00Z (Unix epoch).
+    /// Out of range fields of invalid dates are clamped to their valid range.
     pub fn to_timestamp(&self) -> i64 {
         self.to_timestamp_local()
-            + ((self.tz_hour as i64 * 3600 + self.tz_minute as i64 * 60)
+            + ((self.tz_hour as i64 * 60 + self.tz_minute.min(59) as i64).min(24 * 60)
+                * 60
                 * if self.tz_before_gmt { 1 } else { -1 })
     }
 
-    /// Returns the numbers of seconds since 1970-01-01T00:00:00Z (Unix epoch) in local time
-    /// or None if the date is invalid.
+    /// Returns the numbers of seconds since 1970-01-01T00:00:00Z (Unix epoch) in local time.
+    /// Out of range fields of invalid dates are clamped to their valid range.
     pub fn to_timestamp_local(&self) -> i64 {
         // Ported from https://github.com/protocolbuffers/upb/blob/22182e6e/upb/json_decode.c#L982-L992
-        let month = self.month as u32;
+        let month = self.month.clamp(1, 12) as u32;
         let year_base = 4800; /* Before min year, multiple of 400. */
         let m_adj = month.wrapping_sub(3); /* March-based month. */
         let carry = i64::from(m_adj > month);
@@ -203,10 +235,11 @@ impl DateTime {
         let y_adj = self.year as i64 + year_base - carry;
         let month_days = ((m_adj.wrapping_add(adjust)) * 62719 + 769) / 2048;
         let leap_days = y_adj / 4 - y_adj / 100 + y_adj / 400;
-        (y_adj * 365 + leap_days + month_days as i64 + (self.day as i64 - 1) - 2472632) * 86400
-            + self.hour as i64 * 3600
-            + self.minute as i64 * 60
-            + self.second as i64
+        (y_adj * 365 + leap_days + month_days as i64 + (self.day.clamp(1, 31) as i64 - 1) - 2472632)
+            * 86400
+            + self.hour.min(23) as i64 * 3600
+            + self.minute.min(59) as i64 * 60
+            + self.second.min(59) as i64
     }
 
     /// Creates a DateTime object from a timestamp
@@ -247,7 +280,7 @@ impl DateTime {
         let (month, year) = if self.month > 2 {
             ((self.month - 3) as i64, self.year as i64)
         } else {
-            ((self.month + 9) as i64, (self.year - 1) as i64)
+            ((self.month + 9) as i64, self.year as i64 - 1)
         };
 
         let c = year / 100;
@@ -302,6 +335,9 @@ impl<'x> MessageStream<'x> {
         ];
         let mut month_hash: usize = 0;
         let mut month_pos: usize = 0;
+        let mut zone = [0u8; 3];
+        let mut zone_len: usize = 0;
+        let mut zone_pos: usize = 0;
 
         let mut is_plus = true;
         let mut is_new_token = true;
@@ -346,10 +382,8 @@ impl<'x> MessageStream<'x> {
                         is_new_token = false;
                     }
                 }
-                b':' => {
-                    if !is_new_token && !ignore && (pos == 3 || pos == 4) {
-                        next_part = true;
-                    }
+                b':' if !is_new_token && !ignore && (pos == 3 || pos == 4) => {
+                    next_part = true;
                 }
                 b'+' => {
                     pos = 6;
@@ -358,10 +392,8 @@ impl<'x> MessageStream<'x> {
                     is_plus = false;
                     pos = 6;
                 }
-                b' ' | b'\t' => {
-                    if !is_new_token && !ignore {
-                        next_part = true;
-                    }
+                b' ' | b'\t' if !is_new_token && !ignore => {
+                    next_part = true;
                 }
                 b'a'..=b'z' | b'A'..=b'Z' => {
                     if pos == 1 {
@@ -371,6 +403,13 @@ impl<'x> MessageStream<'x> {
                                 as usize;
                         }
                         month_pos += 1;
+                    } else if (5..=6).contains(&pos) && (zone_len == 0 || zone_pos == pos) {
+                        // Obsolete named zone
+                        if zone_len < zone.len() {
+                            zone[zone_len] = ch.to_ascii_uppercase();
+                        }
+                        zone_len += 1;
+                        zone_pos = pos;
                     }
                     if is_new_token {
                         is_new_token = false;
@@ -397,6 +436,7 @@ impl<'x> MessageStream<'x> {
                     ];
                     month_hash = 0;
                     month_pos = 0;
+                    zone_len = 0;
 
                     is_plus = true;
                     is_new_token = true;
@@ -415,11 +455,24 @@ impl<'x> MessageStream<'x> {
             }
         }
 
-        if pos >= 6 {
+        // Named zones are only used when no numeric offset is present
+        let zone = if parts_sizes[6] == 4 && zone_len <= zone.len() {
+            obsolete_zone(&zone[..zone_len])
+        } else {
+            None
+        };
+
+        if pos >= 6 || pos == 5 && zone.is_some() {
+            if let Some((zone_is_plus, zone_hour)) = zone {
+                is_plus = zone_is_plus;
+                parts[6] = zone_hour * 100;
+            }
+
+            // Obsolete two and three digit years (RFC 5322, Section 4.3)
             HeaderValue::DateTime(DateTime {
                 year: if (0..=49).contains(&parts[2]) {
                     parts[2] + 2000
-                } else if (50..=99).contains(&parts[2]) {
+                } else if (50..=999).contains(&parts[2]) {
                     parts[2] + 1900
                 } else {
                     parts[2]
@@ -443,6 +496,22 @@ impl<'x> MessageStream<'x> {
     }
 }
 
+// Returns the offset of an obsolete zone name as (is_plus, hours). Military
+// zones other than `Z` have an unpredictable meaning in RFC 822 and are
+// treated as `-0000` (RFC 5322, Section 4.3).
+fn obsolete_zone(zone: &[u8]) -> Option<(bool, u32)> {
+    Some(match zone {
+        b"UT" | b"GMT" | b"Z" => (true, 0),
+        b"EDT" => (false, 4),
+        b"EST" | b"CDT" => (false, 5),
+        b"CST" | b"MDT" => (false, 6),
+        b"MST" | b"PDT" => (false, 7),
+        b"PST" => (false, 8),
+        [b'A'..=b'I' | b'K'..=b'Y'] => (false, 0),
+        _ => return None,
+    })
+}
+
 static MONTH_HASH: &[u8] = &[
     31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31,
     31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31, 31,
@@ -465,7 +534,234 @@ pub static MONTH_MAP: &[u8; 31] = &[
 mod tests {
     use chrono::{FixedOffset, LocalResult, SecondsFormat, TimeZone, Utc};
 
-    use crate::parsers::{fields::load_tests, MessageStream};
+    use crate::{
+        parsers::{fields::load_tests, MessageStream},
+        DateTime,
+    };
+
+    #[test]
+    fn parse_standalone_dates() {
+        for (input, expected) in [
+            (
+                "Tue, 1 Jul 2003 10:52:37 +0200",
+                Some("2003-07-01T10:52:37+02:00"),
+            ),
+            (
+                "1 Jul 2003 10:52:37 -0330",
+                Some("2003-07-01T10:52:37-03:30"),
+            ),
+            (
+                "  Thu, 13 Feb 1969 23:32 -0330 (Newfoundland Time)",
+                Some("1969-02-13T23:32:00-03:30"),
+            ),
+            ("Mon, 32 Jan 2003 10:52:37 +0200", None),
+            ("not a date", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                DateTime::parse(input).map(|dt| dt.to_rfc3339()),
+                expected.map(String::from),
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_dates() {
+        for (input, is_valid) in [
+            ("Sun, 31 Jan 2021 10:00:00 +0000", true),
+            ("Wed, 31 Feb 2021 10:00:00 +0000", false),
+            ("Sat, 29 Feb 2020 10:00:00 +0000", true),
+            ("Mon, 29 Feb 2021 10:00:00 +0000", false),
+            ("Tue, 29 Feb 2000 10:00:00 +0000", true),
+            ("Thu, 29 Feb 1900 10:00:00 +0000", false),
+            ("Fri, 31 Apr 2021 10:00:00 +0000", false),
+            ("Fri, 30 Apr 2021 10:00:00 +0000", true),
+            ("Thu, 31 Dec 2015 23:59:60 +0000", true),
+            ("Thu, 31 Dec 2015 23:59:61 +0000", false),
+            ("Thu, 31 Dec 2015 25:00:00 +0000", false),
+            ("Thu, 31 Dec 2015 23:60:00 +0000", false),
+            ("Thu, 31 Dec 2015 23:00:00 +2400", true),
+            ("Thu, 31 Dec 2015 23:00:00 -2401", false),
+            ("Thu, 31 Dec 2015 23:00:00 +0160", false),
+        ] {
+            let datetime = DateTime::parse_rfc822(input).unwrap();
+            assert_eq!(datetime.is_valid(), is_valid, "failed for {input:?}");
+            assert_eq!(
+                DateTime::parse(input).is_some(),
+                is_valid,
+                "failed for {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn date_conversions() {
+        let datetime = DateTime::parse("Fri, 14 Jun 1991 19:13:14 -0400").unwrap();
+        assert_eq!(datetime.to_rfc3339(), "1991-06-14T19:13:14-04:00");
+        assert_eq!(datetime.to_timestamp(), 676941194);
+        assert_eq!(datetime.to_timestamp_local(), 676926794);
+        assert_eq!(
+            DateTime::from_timestamp(datetime.to_timestamp()).to_rfc3339(),
+            "1991-06-14T23:13:14Z"
+        );
+
+        for (input, expected_rfc3339, expected_timestamp) in [
+            (
+                "Fri, 14 Jun 1991 19:13:14 +0530",
+                "1991-06-14T19:13:14+05:30",
+                676906994,
+            ),
+            ("Thu, 1 Jan 1970 00:00:00 +0000", "1970-01-01T00:00:00Z", 0),
+            (
+                "Wed, 31 Dec 1969 23:59:59 +0000",
+                "1969-12-31T23:59:59Z",
+                -1,
+            ),
+            (
+                "Tue, 29 Feb 2000 12:00:00 -1200",
+                "2000-02-29T12:00:00-12:00",
+                951868800,
+            ),
+        ] {
+            let datetime = DateTime::parse(input).unwrap();
+            assert_eq!(
+                datetime.to_rfc3339(),
+                expected_rfc3339,
+                "failed for {input:?}"
+            );
+            assert_eq!(
+                datetime.to_timestamp(),
+                expected_timestamp,
+                "failed for {input:?}"
+            );
+        }
+
+        // Out of range fields are clamped
+        let datetime = DateTime {
+            year: 0,
+            month: 0,
+            day: 0,
+            hour: 99,
+            minute: 99,
+            second: 99,
+            tz_before_gmt: false,
+            tz_hour: 99,
+            tz_minute: 99,
+        };
+        assert_eq!(
+            datetime.to_timestamp(),
+            DateTime {
+                month: 1,
+                day: 1,
+                hour: 23,
+                minute: 59,
+                second: 59,
+                tz_hour: 24,
+                tz_minute: 0,
+                ..datetime
+            }
+            .to_timestamp()
+        );
+
+        // The offset range matches the one accepted by is_valid
+        let datetime = DateTime::parse_rfc822("Mon, 1 Jan 2024 00:00:00 +2400").unwrap();
+        assert!(datetime.is_valid());
+        assert_eq!(datetime.to_timestamp(), 1704067200 - 24 * 3600);
+        let datetime = DateTime::parse_rfc822("Mon, 1 Jan 2024 00:00:00 -2400").unwrap();
+        assert!(datetime.is_valid());
+        assert_eq!(datetime.to_timestamp(), 1704067200 + 24 * 3600);
+        // Must not overflow for years before 1 AD
+        datetime.julian_day();
+    }
+
+    #[test]
+    fn parse_obsolete_dates() {
+        for (input, expected, unknown_offset) in [
+            // Zone names
+            ("2 Jan 2006 15:04:05 UT", "2006-01-02T15:04:05Z", false),
+            ("2 Jan 2006 15:04:05 GMT", "2006-01-02T15:04:05Z", false),
+            (
+                "2 Jan 2006 15:04:05 EST",
+                "2006-01-02T15:04:05-05:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 edt",
+                "2006-01-02T15:04:05-04:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 CST",
+                "2006-01-02T15:04:05-06:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 CDT",
+                "2006-01-02T15:04:05-05:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 MST",
+                "2006-01-02T15:04:05-07:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 MDT",
+                "2006-01-02T15:04:05-06:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 PST",
+                "2006-01-02T15:04:05-08:00",
+                false,
+            ),
+            ("2 Jan 2006 15:04 PDT", "2006-01-02T15:04:00-07:00", false),
+            (
+                "2 Jan 2006 15:04:05 EST (Eastern)",
+                "2006-01-02T15:04:05-05:00",
+                false,
+            ),
+            (
+                "2 Jan 2006 15:04:05 +0100 EST",
+                "2006-01-02T15:04:05+01:00",
+                false,
+            ),
+            ("2 Jan 2006 15:04:05 XYZ", "2006-01-02T15:04:05Z", false),
+            // Military zones
+            ("2 Jan 2006 15:04:05 Z", "2006-01-02T15:04:05Z", false),
+            ("2 Jan 2006 15:04:05 A", "2006-01-02T15:04:05Z", true),
+            ("2 Jan 2006 15:04:05 n", "2006-01-02T15:04:05Z", true),
+            ("2 Jan 2006 15:04:05 Y", "2006-01-02T15:04:05Z", true),
+            ("2 Jan 2006 15:04:05 J", "2006-01-02T15:04:05Z", false),
+            ("2 Jan 2006 15:04:05 -0000", "2006-01-02T15:04:05Z", true),
+            // Year window
+            (
+                "Mon, 2 Jan 06 15:04:05 EST",
+                "2006-01-02T15:04:05-05:00",
+                false,
+            ),
+            ("1 Jan 00 00:00:00 +0000", "2000-01-01T00:00:00Z", false),
+            ("31 Dec 49 23:59:59 +0000", "2049-12-31T23:59:59Z", false),
+            ("1 Jan 50 00:00:00 +0000", "1950-01-01T00:00:00Z", false),
+            ("31 Dec 99 23:59:59 +0000", "1999-12-31T23:59:59Z", false),
+            ("1 Jan 106 00:00:00 +0000", "2006-01-01T00:00:00Z", false),
+            ("1 Jan 1949 00:00:00 +0000", "1949-01-01T00:00:00Z", false),
+        ] {
+            let datetime =
+                DateTime::parse(input).unwrap_or_else(|| panic!("failed to parse {input:?}"));
+            assert_eq!(datetime.to_rfc3339(), expected, "failed for {input:?}");
+            assert_eq!(
+                datetime.has_unknown_offset(),
+                unknown_offset,
+                "failed for {input:?}"
+            );
+        }
+
+        for input in ["2 Jan 2006 15:04 XYZ", "2 Jan 2006 15:04 am"] {
+            assert_eq!(DateTime::parse(input), None, "failed for {input:?}");
+        }
+    }
 
     #[test]
     fn parse_dates() {
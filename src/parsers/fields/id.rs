@@ -9,6 +9,8 @@
  * except according to those terms.
  */
 
+use std::borrow::Cow;
+
 use crate::{parsers::MessageStream, HeaderValue};
 
 impl<'x> MessageStream<'x> {
@@ -17,6 +19,9 @@ impl<'x> MessageStream<'x> {
         let mut token_end: usize = 0;
         let mut token_invalid_start: usize = 0; // Handle broken clients
         let mut token_invalid_end: usize = 0; // Handle broken clients
+        let mut bare_start: usize = 0; // Ids missing their angle brackets
+        let mut bare_end: usize = 0;
+        let mut comment_depth = 0;
         let mut is_id_part = false;
         let mut ids = Vec::new();
 
@@ -24,28 +29,29 @@ impl<'x> MessageStream<'x> {
             match ch {
                 b'\n' => {
                     if !self.try_next_is_space() {
-                        return match ids.len() {
-                            1 => HeaderValue::Text(ids.pop().unwrap()),
-                            0 => {
-                                if token_invalid_start > 0 {
-                                    HeaderValue::Text(String::from_utf8_lossy(
-                                        self.bytes(token_invalid_start - 1..token_invalid_end),
-                                    ))
-                                } else {
-                                    HeaderValue::Empty
-                                }
-                            }
-                            _ => HeaderValue::TextList(ids),
-                        };
+                        return self.id_list(
+                            ids,
+                            (token_start, token_end),
+                            (bare_start, bare_end),
+                            (token_invalid_start, token_invalid_end),
+                        );
                     } else {
                         continue;
                     }
                 }
-                b'<' => {
+                b'<' if comment_depth == 0 => {
+                    if is_id_part {
+                        // Unterminated id, keep it if it looks like a valid one
+                        ids.extend(self.bare_id(token_start, token_end));
+                    } else {
+                        ids.extend(self.bare_id(bare_start, bare_end));
+                        bare_start = 0;
+                    }
                     is_id_part = true;
+                    token_start = 0;
                     continue;
                 }
-                b'>' => {
+                b'>' if comment_depth == 0 => {
                     is_id_part = false;
                     if token_start > 0 {
                         ids.push(String::from_utf8_lossy(
@@ -56,7 +62,24 @@ impl<'x> MessageStream<'x> {
                         continue;
                     }
                 }
-                b' ' | b'\t' | b'\r' => continue,
+                b'(' if !is_id_part => {
+                    ids.extend(self.bare_id(bare_start, bare_end));
+                    bare_start = 0;
+                    comment_depth += 1;
+                    continue;
+                }
+                b')' if comment_depth > 0 => {
+                    comment_depth -= 1;
+                    continue;
+                }
+                b' ' | b'\t' | b'\r' => {
+                    if !is_id_part {
+                        ids.extend(self.bare_id(bare_start, bare_end));
+                        bare_start = 0;
+                    }
+                    continue;
+                }
+                _ if comment_depth > 0 => continue,
                 _ => {}
             }
             if is_id_part {
@@ -64,15 +87,64 @@ impl<'x> MessageStream<'x> {
                     token_start = self.offset();
                 }
                 token_end = self.offset();
-            } else {
+            } else if ch != b'>' {
                 if token_invalid_start == 0 {
                     token_invalid_start = self.offset();
                 }
                 token_invalid_end = self.offset();
+                if bare_start == 0 {
+                    bare_start = self.offset();
+                }
+                bare_end = self.offset();
             }
         }
 
-        HeaderValue::Empty
+        self.id_list(
+            ids,
+            (token_start, token_end),
+            (bare_start, bare_end),
+            (token_invalid_start, token_invalid_end),
+        )
+    }
+
+    fn id_list(
+        &self,
+        mut ids: Vec<Cow<'x, str>>,
+        unterminated: (usize, usize),
+        bare: (usize, usize),
+        invalid: (usize, usize),
+    ) -> HeaderValue<'x> {
+        ids.extend(self.bare_id(unterminated.0, unterminated.1));
+        ids.extend(self.bare_id(bare.0, bare.1));
+
+        match ids.len() {
+            1 => HeaderValue::Text(ids.pop().unwrap()),
+            0 => {
+                if invalid.0 > 0 {
+                    HeaderValue::Text(String::from_utf8_lossy(
+                        self.bytes(invalid.0 - 1..invalid.1),
+                    ))
+                } else {
+                    HeaderValue::Empty
+                }
+            }
+            _ => HeaderValue::TextList(ids),
+        }
+    }
+
+    /// Returns the token if it looks like a `local@domain` message id.
+    fn bare_id(&self, token_start: usize, token_end: usize) -> Option<Cow<'x, str>> {
+        if token_start == 0 {
+            return None;
+        }
+        let id = self.bytes(token_start - 1..token_end);
+        let at = id.iter().position(|&ch| ch == b'@')?;
+
+        (at > 0
+            && at < id.len() - 1
+            && !id[at + 1..].contains(&b'@')
+            && !id.iter().any(|ch| b"<>()[]\\\",;: \t\r\n".contains(ch)))
+        .then(|| String::from_utf8_lossy(id))
     }
 }
 #[cfg(test)]
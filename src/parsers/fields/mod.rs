@@ -16,6 +16,7 @@ pub mod id;
 pub mod list;
 pub mod raw;
 pub mod received;
+pub mod spam;
 pub mod thread;
 pub mod unstructured;
 
@@ -268,43 +268,32 @@ impl<'x> MessageStream<'x> {
                         }
                     }
                 }
-                Token::IpAddr(ip) => {
+                Token::IpAddr(ip)
                     if state == State::From
                         && (token.bracket_depth > 0
-                            || (token.comment_depth > 0 && received.from_ip.is_none()))
-                    {
-                        received.from_ip = Some(ip);
-                    }
+                            || (token.comment_depth > 0 && received.from_ip.is_none())) =>
+                {
+                    received.from_ip = Some(ip);
                 }
-                Token::Domain => {
-                    if state == State::From && token.comment_depth > 0 {
-                        received.from_iprev = Some(token.text.into());
-                    }
+                Token::Domain if state == State::From && token.comment_depth > 0 => {
+                    received.from_iprev = Some(token.text.into());
                 }
-                Token::Email => {
-                    if state == State::From {
-                        received.ident =
-                            Some(token.text.strip_suffix('@').unwrap_or(token.text).into());
-                    }
+                Token::Email if state == State::From => {
+                    received.ident =
+                        Some(token.text.strip_suffix('@').unwrap_or(token.text).into());
                 }
-                Token::Integer(num) => {
-                    if state == State::Date {
-                        if let Some(part) = date_part.next() {
-                            *part = num;
-                        }
+                Token::Integer(num) if state == State::Date => {
+                    if let Some(part) = date_part.next() {
+                        *part = num;
                     }
                 }
-                Token::Month(month) => {
-                    if state == State::Date {
-                        if let Some(part) = date_part.next() {
-                            *part = month.to_number();
-                        }
+                Token::Month(month) if state == State::Date => {
+                    if let Some(part) = date_part.next() {
+                        *part = month.to_number();
                     }
                 }
-                Token::Cipher => {
-                    if token.comment_depth > 0 || received.tls_cipher.is_none() {
-                        received.tls_cipher = Some(token.text.into());
-                    }
+                Token::Cipher if (token.comment_depth > 0 || received.tls_cipher.is_none()) => {
+                    received.tls_cipher = Some(token.text.into());
                 }
                 Token::TlsVersion(tls)
                     if token.comment_depth > 0 && received.tls_version.is_none() =>
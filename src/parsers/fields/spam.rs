@@ -0,0 +1,170 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use crate::SpamStatus;
+
+impl SpamStatus {
+    /// Parses an `X-Spam-Status` value in the SpamAssassin format, such as
+    /// `Yes, score=7.3 required=5.0 tests=BAYES_99,HTML_MESSAGE autolearn=no`.
+    /// The legacy `hits=` attribute is accepted in place of `score=`, and test
+    /// scores such as `BAYES_99=3.5` are removed from the test names. Returns
+    /// `None` if the verdict or the score are missing.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (verdict, attributes) = value
+            .split_once(|ch: char| ch == ',' || ch.is_ascii_whitespace())
+            .unwrap_or((value, ""));
+        let is_spam = if verdict.eq_ignore_ascii_case("yes") {
+            true
+        } else if verdict.eq_ignore_ascii_case("no") {
+            false
+        } else {
+            return None;
+        };
+
+        let mut score = None;
+        let mut required = None;
+        let mut tests = Vec::new();
+        let mut in_tests = false;
+
+        for token in attributes.split_ascii_whitespace() {
+            // Folded test lists continue after a trailing comma
+            let tests_list = if in_tests {
+                Some(token)
+            } else {
+                match token.split_once('=') {
+                    Some((name, value)) if name.eq_ignore_ascii_case("score") => {
+                        score = parse_score(value);
+                        None
+                    }
+                    Some((name, value)) if name.eq_ignore_ascii_case("hits") => {
+                        score = score.or_else(|| parse_score(value));
+                        None
+                    }
+                    Some((name, value)) if name.eq_ignore_ascii_case("required") => {
+                        required = parse_score(value);
+                        None
+                    }
+                    Some((name, value)) if name.eq_ignore_ascii_case("tests") => Some(value),
+                    _ => None,
+                }
+            };
+
+            if let Some(tests_list) = tests_list {
+                in_tests = tests_list.ends_with(',');
+                tests.extend(
+                    tests_list
+                        .split(',')
+                        .map(|test| test.split_once('=').map_or(test, |(name, _)| name))
+                        .filter(|test| !test.is_empty() && !test.eq_ignore_ascii_case("none"))
+                        .map(String::from),
+                );
+            }
+        }
+
+        Some(SpamStatus {
+            is_spam,
+            score: score?,
+            required,
+            tests,
+        })
+    }
+}
+
+/// Parses an `X-Spam-Score` value, which starts with the score of the message
+/// and is optionally followed by other details, such as `7.3 (+++++++)`.
+pub fn parse_spam_score(value: &str) -> Option<f64> {
+    parse_score(value.split_ascii_whitespace().next()?)
+}
+
+fn parse_score(value: &str) -> Option<f64> {
+    value
+        .trim_end_matches(',')
+        .parse::<f64>()
+        .ok()
+        .filter(|score| score.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_spam_score;
+    use crate::SpamStatus;
+
+    #[test]
+    fn parse_spam_status() {
+        for (input, expected) in [
+            (
+                concat!(
+                    "Yes, score=7.3 required=5.0 tests=BAYES_99,HTML_MESSAGE,\n",
+                    "\tRDNS_NONE autolearn=no autolearn_force=no version=3.4.6"
+                ),
+                Some(SpamStatus {
+                    is_spam: true,
+                    score: 7.3,
+                    required: Some(5.0),
+                    tests: vec![
+                        "BAYES_99".to_string(),
+                        "HTML_MESSAGE".to_string(),
+                        "RDNS_NONE".to_string(),
+                    ],
+                }),
+            ),
+            (
+                "No, score=-1.9 required=5.0 tests=BAYES_00=-1.9,DKIM_SIGNED=0.1 autolearn=ham",
+                Some(SpamStatus {
+                    is_spam: false,
+                    score: -1.9,
+                    required: Some(5.0),
+                    tests: vec!["BAYES_00".to_string(), "DKIM_SIGNED".to_string()],
+                }),
+            ),
+            (
+                "no, hits=0.2 required=5.0 tests=none version=2.64",
+                Some(SpamStatus {
+                    is_spam: false,
+                    score: 0.2,
+                    required: Some(5.0),
+                    tests: vec![],
+                }),
+            ),
+            (
+                "Yes,score=12",
+                Some(SpamStatus {
+                    is_spam: true,
+                    score: 12.0,
+                    required: None,
+                    tests: vec![],
+                }),
+            ),
+            ("Yes, required=5.0 tests=BAYES_99", None),
+            ("Yes, score=high required=5.0", None),
+            ("Maybe, score=2.0 required=5.0", None),
+            ("spam", None),
+            ("", None),
+        ] {
+            assert_eq!(SpamStatus::parse(input), expected, "failed for {input:?}");
+        }
+    }
+
+    #[test]
+    fn parse_spam_scores() {
+        for (input, expected) in [
+            ("7.3", Some(7.3)),
+            (" -0.1 ", Some(-0.1)),
+            ("5.2 (+++++)", Some(5.2)),
+            ("high", None),
+            ("NaN", None),
+            ("", None),
+        ] {
+            assert_eq!(parse_spam_score(input), expected, "failed for {input:?}");
+        }
+    }
+}
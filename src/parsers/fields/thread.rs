@@ -9,6 +9,8 @@
  * except according to those terms.
  */
 
+use crate::{decoders::base64::base64_decode, DateTime, ThreadIndex};
+
 static RE_PREFIXES: &[&str] = &[
     "re", "res", "sv", "antw", "ref", "aw", "απ", "השב", "vá", "r", "rif", "bls", "odp", "ynt",
     "atb", "رد", "回复", "转发",
@@ -218,6 +220,42 @@ pub fn trim_trailing_fwd(text: &str) -> &str {
     }
 }
 
+impl ThreadIndex {
+    /// Decodes a base64 encoded `Thread-Index` value, returning `None` if it is
+    /// shorter than the 22-byte header block.
+    pub fn parse(value: &[u8]) -> Option<Self> {
+        base64_decode(value.trim_ascii())
+            .filter(|bytes| bytes.len() >= 22)
+            .map(|bytes| ThreadIndex { bytes })
+    }
+
+    /// Returns the decoded bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the 16-byte GUID identifying the conversation
+    pub fn guid(&self) -> &[u8] {
+        &self.bytes[6..22]
+    }
+
+    /// Returns the date of the message that started the conversation
+    pub fn date(&self) -> DateTime {
+        // The header holds the six most significant bytes of a FILETIME,
+        // which counts 100-nanosecond intervals since 1601-01-01.
+        let filetime = self.bytes[..6]
+            .iter()
+            .fold(0u64, |acc, &b| (acc << 8) | b as u64)
+            << 16;
+        DateTime::from_timestamp((filetime / 10_000_000) as i64 - 11_644_473_600)
+    }
+
+    /// Returns the number of replies or forwards recorded after the header block
+    pub fn child_count(&self) -> usize {
+        (self.bytes.len() - 22) / 5
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::parsers::fields::thread::{thread_name, trim_trailing_fwd};
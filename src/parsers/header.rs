@@ -11,12 +11,13 @@
 
 use std::borrow::Cow;
 
-use crate::{Header, HeaderName, MessageParser};
+use crate::{Header, HeaderName, HeaderValue, MessageParser};
 
 use super::MessageStream;
 
 impl<'x> MessageStream<'x> {
     pub fn parse_headers(&mut self, conf: &MessageParser, headers: &mut Vec<Header<'x>>) -> bool {
+        self.set_options(conf);
         loop {
             loop {
                 match self.peek() {
@@ -38,54 +39,20 @@ impl<'x> MessageStream<'x> {
 
             if let Some(header_name) = self.parse_header_name() {
                 let from_offset = self.offset();
-                let value = if conf.header_map.is_empty() {
-                    match &header_name {
-                        HeaderName::Subject
-                        | HeaderName::Comments
-                        | HeaderName::ContentDescription
-                        | HeaderName::ContentLocation
-                        | HeaderName::ContentTransferEncoding => self.parse_unstructured(),
-                        HeaderName::From
-                        | HeaderName::To
-                        | HeaderName::Cc
-                        | HeaderName::Bcc
-                        | HeaderName::ReplyTo
-                        | HeaderName::Sender
-                        | HeaderName::ResentTo
-                        | HeaderName::ResentFrom
-                        | HeaderName::ResentBcc
-                        | HeaderName::ResentCc
-                        | HeaderName::ResentSender
-                        | HeaderName::ListArchive
-                        | HeaderName::ListHelp
-                        | HeaderName::ListId
-                        | HeaderName::ListOwner
-                        | HeaderName::ListPost
-                        | HeaderName::ListSubscribe
-                        | HeaderName::ListUnsubscribe => self.parse_address(),
-                        HeaderName::Date | HeaderName::ResentDate => self.parse_date(),
-                        HeaderName::MessageId
-                        | HeaderName::References
-                        | HeaderName::InReplyTo
-                        | HeaderName::ReturnPath
-                        | HeaderName::ContentId
-                        | HeaderName::ResentMessageId => self.parse_id(),
-                        HeaderName::Keywords | HeaderName::ContentLanguage => {
-                            self.parse_comma_separared()
-                        }
-                        HeaderName::Received => self.parse_received(),
-                        HeaderName::MimeVersion => self.parse_raw(),
-                        HeaderName::ContentType | HeaderName::ContentDisposition => {
-                            self.parse_content_type()
-                        }
-                        HeaderName::Other(_) => self.parse_raw(),
-                    }
-                } else {
-                    (conf
-                        .header_map
-                        .get(&header_name)
-                        .unwrap_or(&conf.def_hdr_parse_fnc))(self)
-                };
+                let value = self.parse_header_value(conf, &header_name);
+
+                if let (HeaderName::Other(name), Some(fnc)) =
+                    (&header_name, conf.unknown_header_fnc)
+                {
+                    let raw_value = self.bytes(from_offset..self.offset());
+                    fnc(
+                        name,
+                        raw_value
+                            .strip_suffix(b"\r\n")
+                            .or_else(|| raw_value.strip_suffix(b"\n"))
+                            .unwrap_or(raw_value),
+                    );
+                }
 
                 headers.push(Header {
                     name: header_name,
@@ -100,6 +67,69 @@ impl<'x> MessageStream<'x> {
         }
     }
 
+    // Parses a header value with the function configured for its name
+    pub(crate) fn parse_header_value(
+        &mut self,
+        conf: &MessageParser,
+        header_name: &HeaderName<'_>,
+    ) -> HeaderValue<'x> {
+        if conf.header_map.is_empty() {
+            match header_name {
+                HeaderName::Subject
+                | HeaderName::Comments
+                | HeaderName::ContentDescription
+                | HeaderName::ContentLocation
+                | HeaderName::ContentTransferEncoding => self.parse_unstructured(),
+                HeaderName::From
+                | HeaderName::To
+                | HeaderName::Cc
+                | HeaderName::Bcc
+                | HeaderName::ReplyTo
+                | HeaderName::Sender
+                | HeaderName::ResentTo
+                | HeaderName::ResentFrom
+                | HeaderName::ResentBcc
+                | HeaderName::ResentCc
+                | HeaderName::ResentSender
+                | HeaderName::ListArchive
+                | HeaderName::ListHelp
+                | HeaderName::ListId
+                | HeaderName::ListOwner
+                | HeaderName::ListPost
+                | HeaderName::ListSubscribe
+                | HeaderName::ListUnsubscribe => self.parse_address(),
+                HeaderName::Date | HeaderName::ResentDate => self.parse_date(),
+                HeaderName::MessageId
+                | HeaderName::References
+                | HeaderName::InReplyTo
+                | HeaderName::ReturnPath
+                | HeaderName::ContentId
+                | HeaderName::ResentMessageId => self.parse_id(),
+                HeaderName::Keywords | HeaderName::ContentLanguage => self.parse_comma_separared(),
+                HeaderName::Received => self.parse_received(),
+                HeaderName::MimeVersion => self.parse_raw(),
+                HeaderName::ContentType => self.parse_content_type(),
+                HeaderName::ContentDisposition => self.parse_content_disposition(),
+                HeaderName::Other(_) => self.parse_raw(),
+            }
+        } else {
+            (conf
+                .header_map
+                .get(header_name)
+                .unwrap_or(&conf.def_hdr_parse_fnc))(self)
+        }
+    }
+
+    // Copies the header value parsing options of the parser
+    pub(crate) fn set_options(&mut self, conf: &MessageParser) {
+        self.comment_display_names = conf.comment_display_names;
+        self.lenient_address_names = conf.lenient_address_names;
+        self.lenient_attribute_values = conf.lenient_attribute_values;
+        self.max_attribute_name_len = conf.max_attribute_name_len;
+        self.max_attribute_value_len = conf.max_attribute_value_len;
+        self.max_attribute_continuations = conf.max_attribute_continuations;
+    }
+
     pub fn parse_header_name(&mut self) -> Option<HeaderName<'x>> {
         let mut token_start: usize = 0;
         let mut token_end: usize = 0;
@@ -388,7 +418,7 @@ static HDR_NAMES: &[&[u8]] = &[
 
 #[cfg(test)]
 mod tests {
-    use crate::{parsers::MessageStream, HeaderName};
+    use crate::{parsers::MessageStream, Encoding, HeaderName, MessageParser};
 
     #[test]
     fn header_name_parse() {
@@ -415,4 +445,91 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn header_encoded_word_metadata() {
+        let input = concat!(
+            "Subject: =?iso-8859-1?q?caf=E9?= and =?UTF-8*en?B?8J+Ynw==?=\n",
+            "From: =?utf-8?Q?Jos=C3=A9?= <jose@example.com>\n",
+            "X-Raw: =?utf-8?Q?raw?=\n",
+            "\n"
+        );
+        let expected = [
+            vec![
+                (
+                    "iso-8859-1",
+                    Encoding::QuotedPrintable,
+                    "=?iso-8859-1?q?caf=E9?=",
+                ),
+                ("UTF-8", Encoding::Base64, "=?UTF-8*en?B?8J+Ynw==?="),
+            ],
+            vec![("utf-8", Encoding::QuotedPrintable, "=?utf-8?Q?Jos=C3=A9?=")],
+            vec![],
+        ];
+
+        let parser = MessageParser::new();
+        let message = parser.parse_headers(input).unwrap();
+        assert_eq!(message.headers().len(), expected.len());
+        for (header, expected) in message.headers().iter().zip(expected) {
+            assert_eq!(
+                parser
+                    .encoded_words(input, header)
+                    .iter()
+                    .map(|w| (
+                        w.charset(),
+                        w.encoding(),
+                        &input[w.offset_start()..w.offset_end()]
+                    ))
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_header_callback() {
+        thread_local! {
+            static UNKNOWN: std::cell::RefCell<Vec<(String, Vec<u8>)>> = Default::default();
+        }
+
+        let message = MessageParser::default()
+            .on_unknown_header(|name, value| {
+                UNKNOWN.with(|unknown| {
+                    unknown
+                        .borrow_mut()
+                        .push((name.to_string(), value.to_vec()))
+                })
+            })
+            .parse(concat!(
+                "From: john@example.org\r\n",
+                "X-Custom: =?utf-8?q?raw?=\r\n",
+                "Subject: hello\r\n",
+                "X-Experimental-Header: value\r\n\tfolded\r\n",
+                "Content-Type: multipart/mixed; boundary=\"a\"\r\n\r\n",
+                "--a\r\n",
+                "Content-Type: text/plain\r\n",
+                "X-Part-Header: part\r\n\r\n",
+                "body\r\n",
+                "--a--\r\n",
+            ))
+            .unwrap();
+
+        assert_eq!(
+            UNKNOWN.with(|unknown| unknown.take()),
+            [
+                ("X-Custom".to_string(), b" =?utf-8?q?raw?=".to_vec()),
+                (
+                    "X-Experimental-Header".to_string(),
+                    b" value\r\n\tfolded".to_vec()
+                ),
+                ("X-Part-Header".to_string(), b" part".to_vec()),
+            ]
+        );
+        assert_eq!(
+            message.get_header_raw("X-Custom"),
+            Some(&b" =?utf-8?q?raw?="[..])
+        );
+        assert!(message.headers()[1].name.is_other());
+        assert!(!message.headers()[2].name.is_other());
+    }
 }
@@ -13,8 +13,10 @@ use std::borrow::Cow;
 
 use crate::{
     decoders::{charsets::map::charset_decoder, DecodeFnc},
-    ContentType, Encoding, GetHeader, HeaderName, HeaderValue, Message, MessageParser, MessagePart,
-    MessagePartId, PartType,
+    mailbox::mbox::envelope_line_len,
+    AddressRawText, ContentType, ContentTypeDetails, ContentTypePolicy, EncodedWord, Encoding,
+    GetHeader, Header, HeaderName, HeaderPool, HeaderValue, Message, MessageParser, MessagePart,
+    MessagePartId, PartHeaders, PartType, TransferEncodingPolicy,
 };
 
 use super::MessageStream;
@@ -40,7 +42,12 @@ enum MimeType {
 fn mime_type(
     content_type: Option<&ContentType<'_>>,
     parent_content_type: &MimeType,
+    policy: ContentTypePolicy,
 ) -> (bool, bool, bool, MimeType) {
+    let content_type = content_type.filter(|ct| {
+        policy == ContentTypePolicy::Explicit || ct.subtype().is_some_and(|st| !st.is_empty())
+    });
+
     if let Some(content_type) = content_type {
         match content_type.ctype() {
             "multipart" => (
@@ -114,7 +121,42 @@ impl MessageParser {
     /// if no headers are found None is returned.
     ///
     pub fn parse<'x>(&self, raw_message: impl IntoByteSlice<'x>) -> Option<Message<'x>> {
-        self.parse_(raw_message.into_byte_slice(), MAX_NESTED_ENCODED, false)
+        self.parse_(
+            raw_message.into_byte_slice(),
+            MAX_NESTED_ENCODED,
+            0,
+            false,
+            self.limits.max_parts,
+            Message::new(),
+        )
+    }
+
+    /// Parses a byte slice containing the RFC5322 raw message into an existing
+    /// `Message`, reusing the allocations of its part, header and body lists
+    /// rather than allocating new ones. Returns `false` and leaves `message` empty if no
+    /// headers are found.
+    ///
+    /// To reuse a `Message` across different input buffers, convert it with
+    /// [`Message::recycle`] before parsing the next buffer.
+    pub fn parse_into<'x>(
+        &self,
+        raw_message: impl IntoByteSlice<'x>,
+        message: &mut Message<'x>,
+    ) -> bool {
+        match self.parse_(
+            raw_message.into_byte_slice(),
+            MAX_NESTED_ENCODED,
+            0,
+            false,
+            self.limits.max_parts,
+            std::mem::take(message).recycle(),
+        ) {
+            Some(parsed) => {
+                *message = parsed;
+                true
+            }
+            None => false,
+        }
     }
 
     /// Parses a byte slice containing the RFC5322 raw message and returns a
@@ -123,25 +165,301 @@ impl MessageParser {
         &self,
         raw_message: impl IntoByteSlice<'x> + 'x,
     ) -> Option<Message<'x>> {
-        self.parse_(raw_message.into_byte_slice(), MAX_NESTED_ENCODED, true)
+        self.parse_(
+            raw_message.into_byte_slice(),
+            MAX_NESTED_ENCODED,
+            0,
+            true,
+            self.limits.max_parts,
+            Message::new(),
+        )
+    }
+
+    /// Parses the headers of a single MIME part from its raw bytes, without
+    /// requiring the enclosing message. The structured MIME headers are
+    /// available through the [`MimeHeaders`](crate::MimeHeaders) trait and
+    /// `offset_body` is the position in `raw_part` where the body starts.
+    ///
+    /// Returns `None` if no headers could be parsed.
+    pub fn parse_part_headers<'x>(
+        &self,
+        raw_part: impl IntoByteSlice<'x>,
+    ) -> Option<PartHeaders<'x>> {
+        let mut stream = MessageStream::new(raw_part.into_byte_slice());
+        let mut headers = Vec::new();
+
+        if stream.parse_headers(self, &mut headers) || !headers.is_empty() {
+            Some(PartHeaders {
+                headers,
+                offset_body: stream.offset(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Parses again a `Content-Type` or `Content-Disposition` header field of
+    /// a message parsed with this parser, returning the information that is
+    /// not part of its [`ContentType`] value, such as the attributes dropped
+    /// or truncated because of the configured limits. `raw_message` is the
+    /// data the offsets of `header` refer to, usually [`Message::raw_message`].
+    ///
+    /// Returns `None` if `header` is not a `Content-Type` or
+    /// `Content-Disposition` header field.
+    pub fn content_type_details<'x>(
+        &self,
+        raw_message: impl IntoByteSlice<'x>,
+        header: &Header<'_>,
+    ) -> Option<ContentTypeDetails<'x>> {
+        let mut stream = MessageStream::new(
+            raw_message
+                .into_byte_slice()
+                .get(header.offset_start..header.offset_end)?,
+        );
+        stream.set_options(self);
+        match (&header.name, &header.value) {
+            (HeaderName::ContentDisposition, _) => {
+                Some(stream.parse_content_disposition_details().1)
+            }
+            (HeaderName::ContentType, _) | (_, HeaderValue::ContentType(_)) => {
+                Some(stream.parse_content_type_details().1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses again a header field of a message parsed with this parser,
+    /// returning the charset, encoding and offsets of each RFC 2047
+    /// encoded-word decoded from its value, which allows re-encoding an
+    /// unchanged header the same way. `raw_message` is the data the offsets
+    /// of `header` refer to, usually [`Message::raw_message`], and the
+    /// offsets of the returned encoded-words are relative to it.
+    pub fn encoded_words<'x>(
+        &self,
+        raw_message: impl IntoByteSlice<'x>,
+        header: &Header<'_>,
+    ) -> Vec<EncodedWord<'x>> {
+        let mut stream = match raw_message
+            .into_byte_slice()
+            .get(header.offset_start..header.offset_end)
+        {
+            Some(bytes) => MessageStream::new(bytes),
+            None => return Vec::new(),
+        };
+        stream.set_options(self);
+        stream.encoded_words = Some(Vec::new());
+        stream.parse_header_value(self, &header.name);
+
+        let mut encoded_words = stream.encoded_words.unwrap_or_default();
+        for encoded_word in &mut encoded_words {
+            encoded_word.offset_start += header.offset_start;
+            encoded_word.offset_end += header.offset_start;
+        }
+        encoded_words
+    }
+
+    /// Parses again an address header field of a message parsed with this
+    /// parser, returning the original text of each address and group before
+    /// comments are removed and encoded-words are decoded. `raw_message` is
+    /// the data the offsets of `header` refer to, usually
+    /// [`Message::raw_message`].
+    ///
+    /// Returns `None` if the value of `header` is not an address.
+    pub fn address_raw_text<'x>(
+        &self,
+        raw_message: impl IntoByteSlice<'x>,
+        header: &Header<'_>,
+    ) -> Option<AddressRawText<'x>> {
+        if !matches!(header.value, HeaderValue::Address(_)) {
+            return None;
+        }
+        let mut stream = MessageStream::new(
+            raw_message
+                .into_byte_slice()
+                .get(header.offset_start..header.offset_end)?,
+        );
+        stream.set_options(self);
+        Some(stream.parse_address_raw_text().1)
+    }
+
+    /// Parses only the part addressed by an IMAP body part specifier such as
+    /// `2` or `2.1`, see [`Message::part_by_imap_path`]. The multipart
+    /// structure is walked using the boundaries and only the body of the
+    /// requested part is decoded, sibling parts are skipped.
+    ///
+    /// Offsets of the returned part are relative to `raw_message`. The body of
+    /// a multipart part is returned undecoded as [`PartType::Binary`]. Returns
+    /// `None` if the part does not exist.
+    pub fn parse_part<'x>(
+        &self,
+        raw_message: impl IntoByteSlice<'x>,
+        imap_path: &str,
+    ) -> Option<MessagePart<'x>> {
+        let raw_message = raw_message.into_byte_slice();
+        let path = imap_path
+            .split('.')
+            .map(|num| num.parse::<usize>().ok().filter(|&num| num > 0))
+            .collect::<Option<Vec<_>>>()?;
+
+        // Skip the envelope line of a message extracted from an mbox file
+        let mut offset_header = envelope_line_len(raw_message).unwrap_or(0);
+        let mut boundary: Option<Vec<u8>> = None;
+        let mut is_message = true;
+        let mut in_digest = false;
+        let mut path = path.into_iter().peekable();
+
+        while let Some(&num) = path.peek() {
+            let mut stream = MessageStream::new(raw_message);
+            stream.skip_bytes(offset_header);
+            let mut headers = Vec::new();
+            if !stream.parse_headers(self, &mut headers) && headers.is_empty() {
+                return None;
+            }
+
+            let content_type = headers
+                .header_value(&HeaderName::ContentType)
+                .and_then(|c| c.as_content_type());
+            let (is_multipart, _, _, mime_type) = mime_type(
+                content_type,
+                if in_digest {
+                    &MimeType::MultipartDigest
+                } else {
+                    &MimeType::Message
+                },
+                self.content_type_policy,
+            );
+
+            if is_multipart {
+                // Skip to the requested sub-part
+                let part_boundary = content_type?.attribute("boundary")?.as_bytes();
+                for _ in 0..num {
+                    if stream.seek_delimiter(part_boundary).is_none() || stream.is_multipart_end() {
+                        return None;
+                    }
+                }
+                offset_header = stream.offset();
+                boundary = Some(part_boundary.to_vec());
+                in_digest = mime_type == MimeType::MultipartDigest;
+                is_message = false;
+                path.next();
+            } else if mime_type == MimeType::Message && !is_message {
+                let is_encoded = matches!(
+                    self.transfer_encoding(&headers),
+                    Some(HeaderValue::Text(encoding))
+                        if encoding.eq_ignore_ascii_case("base64")
+                            || encoding.eq_ignore_ascii_case("quoted-printable")
+                );
+                if !is_encoded {
+                    // The numbering continues into the encapsulated message
+                    offset_header = stream.offset();
+                    in_digest = false;
+                    is_message = true;
+                } else {
+                    // Encoded messages can only be navigated once decoded
+                    return self
+                        .parse(raw_message)?
+                        .part_by_imap_path(imap_path)
+                        .cloned();
+                }
+            } else if is_message && num == 1 {
+                is_message = false;
+                path.next();
+            } else {
+                return None;
+            }
+        }
+
+        // Parse the requested part up to the boundary that closes it
+        let mut stream = MessageStream::new(raw_message);
+        stream.skip_bytes(offset_header);
+        let offset_end = boundary
+            .and_then(|boundary| stream.seek_delimiter(&boundary))
+            .unwrap_or(raw_message.len());
+        let raw_part = &raw_message[offset_header..offset_end];
+        let mut part = self
+            .parse_(
+                raw_part,
+                MAX_NESTED_ENCODED,
+                0,
+                false,
+                self.limits.max_parts,
+                Message::new(),
+            )?
+            .parts
+            .into_iter()
+            .next()?;
+
+        if part.is_multipart() {
+            part.body = PartType::Binary(raw_part[part.offset_body..].into());
+        } else if in_digest
+            && part
+                .headers
+                .header_value(&HeaderName::ContentType)
+                .is_none()
+        {
+            // Parts of a digest default to message/rfc822
+            if let Some(message) = self.parse(&raw_part[part.offset_body..]) {
+                part.body = PartType::Message(message);
+            }
+        }
+        part.offset_header += offset_header;
+        part.offset_body += offset_header;
+        part.offset_end += offset_header;
+        for header in &mut part.headers {
+            header.offset_field += offset_header;
+            header.offset_start += offset_header;
+            header.offset_end += offset_header;
+        }
+
+        Some(part)
     }
 
     fn parse_<'x>(
         &self,
         raw_message: &'x [u8],
         depth: usize,
+        nesting: usize,
         skip_body: bool,
+        max_parts: usize,
+        mut message: Message<'x>,
     ) -> Option<Message<'x>> {
         let mut stream = MessageStream::new(raw_message);
+        stream.max_decoded_len = self.limits.max_part_size;
 
-        let mut message = Message::new();
+        // Skip the envelope line of a message extracted from an mbox file
+        if let Some(len) = envelope_line_len(raw_message) {
+            stream.skip_bytes(len);
+        }
 
         let mut state = MessageParserState::new();
         let mut state_stack = Vec::with_capacity(4);
 
-        let mut part_headers = Vec::new();
+        // Header lists left by `Message::recycle` are reused for the new parts
+        let mut header_pool = std::mem::take(&mut message.header_pool.0);
+        let mut part_headers = header_pool.pop().unwrap_or_default();
+        let mut parts_count = 0;
+        let mut is_truncated = false;
 
         'outer: loop {
+            if parts_count >= max_parts {
+                // Part limit reached, keep the remaining contents as a single part
+                if stream.offset() < raw_message.len() && !skip_body {
+                    state.sub_part_ids.push(message.parts.len());
+                    message.parts.push(MessagePart {
+                        headers: Vec::new(),
+                        encoding: Encoding::None,
+                        is_encoding_problem: true,
+                        body: PartType::Binary(raw_message[stream.offset()..].into()),
+                        offset_header: stream.offset(),
+                        offset_body: stream.offset(),
+                        offset_end: raw_message.len(),
+                    });
+                    stream.seek_end();
+                    is_truncated = true;
+                }
+                break;
+            }
+
             // Parse headers
             state.offset_header = stream.offset();
             if !stream.parse_headers(self, &mut part_headers) {
@@ -160,9 +478,19 @@ impl MessageParser {
                 .and_then(|c| c.as_content_type());
 
             let (is_multipart, mut is_inline, mut is_text, mut mime_type) =
-                mime_type(content_type, &state.mime_type);
+                mime_type(content_type, &state.mime_type, self.content_type_policy);
 
-            if is_multipart {
+            // Container parts are not expanded past the nesting limit
+            let is_too_deep = (is_multipart || mime_type == MimeType::Message)
+                && nesting + state_stack.len() >= self.limits.max_nesting_depth;
+            if is_too_deep {
+                is_truncated = true;
+            }
+
+            let mut is_missing_boundary = false;
+            if is_multipart && !is_too_deep {
+                // Boundaries are matched using the exact attribute value, including any
+                // spaces enclosed in quotes, as in `boundary=" b "`.
                 if let Some(mime_boundary) = content_type.and_then(|f| f.attribute("boundary")) {
                     if stream.seek_next_part(mime_boundary.as_bytes()) {
                         let part_id = message.parts.len();
@@ -179,8 +507,12 @@ impl MessageParser {
                             ..Default::default()
                         };
                         //add_missing_type(&mut part_header, "text".into(), "plain".into());
+                        parts_count += 1;
                         message.parts.push(MessagePart {
-                            headers: std::mem::take(&mut part_headers),
+                            headers: std::mem::replace(
+                                &mut part_headers,
+                                header_pool.pop().unwrap_or_default(),
+                            ),
                             offset_header: state.offset_header,
                             offset_body: state.offset_body,
                             offset_end: 0,
@@ -192,15 +524,22 @@ impl MessageParser {
                         state = new_state;
                         stream.skip_crlf();
                         continue;
+                    } else if self.content_type_policy == ContentTypePolicy::Structural {
+                        (_, is_inline, is_text, mime_type) =
+                            self::mime_type(None, &state.mime_type, self.content_type_policy);
                     } else {
                         mime_type = MimeType::TextOther;
                         is_text = true;
                     }
+                } else {
+                    // The body cannot be split without a boundary, it is kept as
+                    // a single binary part flagged as an encoding problem
+                    is_missing_boundary = true;
                 }
             }
 
-            let (mut encoding, decode_fnc): (Encoding, DecodeFnc<'_>) = match part_headers
-                .header_value(&HeaderName::ContentTransferEncoding)
+            let (mut encoding, decode_fnc): (Encoding, DecodeFnc<'_>) = match self
+                .transfer_encoding(&part_headers)
             {
                 Some(HeaderValue::Text(encoding)) if encoding.eq_ignore_ascii_case("base64") => {
                     (Encoding::Base64, MessageStream::decode_base64_mime)
@@ -216,7 +555,7 @@ impl MessageParser {
                 _ => (Encoding::None, MessageStream::mime_part),
             };
 
-            if mime_type == MimeType::Message && encoding == Encoding::None {
+            if mime_type == MimeType::Message && encoding == Encoding::None && !is_too_deep {
                 let new_state = MessageParserState {
                     mime_type: MimeType::Message,
                     mime_boundary: state.mime_boundary.take(),
@@ -226,8 +565,12 @@ impl MessageParser {
                     ..Default::default()
                 };
                 message.attachments.push(message.parts.len());
+                parts_count += 1;
                 message.parts.push(MessagePart {
-                    headers: std::mem::take(&mut part_headers),
+                    headers: std::mem::replace(
+                        &mut part_headers,
+                        header_pool.pop().unwrap_or_default(),
+                    ),
                     encoding,
                     is_encoding_problem: false,
                     offset_header: state.offset_header,
@@ -241,6 +584,7 @@ impl MessageParser {
                 continue;
             }
 
+            stream.is_decoded_truncated = false;
             let (offset_end, mut bytes) = decode_fnc(
                 &mut stream,
                 state.mime_boundary.as_deref().unwrap_or(&b""[..]),
@@ -265,18 +609,31 @@ impl MessageParser {
             } else {
                 state.offset_end = offset_end;
             }
+            is_encoding_problem |= is_missing_boundary;
+
+            // Encoded contents are cut by the decoders, unencoded ones are borrowed
+            if stream.is_decoded_truncated && encoding != Encoding::None {
+                is_truncated = true;
+            } else if bytes.len() > self.limits.max_part_size {
+                bytes = match bytes {
+                    Cow::Borrowed(bytes) => bytes[..self.limits.max_part_size].into(),
+                    Cow::Owned(mut bytes) => {
+                        bytes.truncate(self.limits.max_part_size);
+                        bytes.into()
+                    }
+                };
+                is_truncated = true;
+            }
 
             let body_part = if mime_type != MimeType::Message {
                 let is_inline = is_inline
                     && part_headers
                         .header_value(&HeaderName::ContentDisposition)
-                        .map_or(true, |d| {
-                            !d.as_content_type().map_or(false, |ct| ct.is_attachment())
-                        })
+                        .is_none_or(|d| !d.as_content_type().is_some_and(|ct| ct.is_attachment()))
                     && (state.parts == 1
                         || state.mime_type != MimeType::MultipartRelated
                             && (mime_type == MimeType::Inline
-                                || content_type.map_or(true, |c| !c.has_attribute("name"))));
+                                || content_type.is_none_or(|c| !c.has_attribute("name"))));
 
                 let (add_to_html, add_to_text) =
                     if let MimeType::MultipartAlternative = state.mime_type {
@@ -350,18 +707,31 @@ impl MessageParser {
             } else {
                 message.attachments.push(message.parts.len());
 
-                if depth != 0 {
-                    if let Some(nested_message) = self.parse_(bytes.as_ref(), depth - 1, false) {
+                if is_too_deep {
+                    PartType::Binary(bytes)
+                } else if depth != 0 {
+                    if let Some(nested_message) = self.parse_(
+                        bytes.as_ref(),
+                        depth - 1,
+                        nesting + state_stack.len() + 1,
+                        false,
+                        max_parts.saturating_sub(parts_count + 1),
+                        Message::new(),
+                    ) {
+                        is_truncated |= nested_message.is_truncated;
                         PartType::Message(Message {
                             html_body: nested_message.html_body,
                             text_body: nested_message.text_body,
                             attachments: nested_message.attachments,
+                            inline_parts: nested_message.inline_parts,
                             parts: nested_message
                                 .parts
                                 .into_iter()
                                 .map(|p| p.into_owned())
                                 .collect(),
+                            is_truncated: nested_message.is_truncated,
                             raw_message: bytes.into_owned().into(),
+                            header_pool: HeaderPool::default(),
                         })
                     } else {
                         is_encoding_problem = true;
@@ -374,8 +744,12 @@ impl MessageParser {
             };
 
             // Add part
+            parts_count += 1;
             message.parts.push(MessagePart {
-                headers: std::mem::take(&mut part_headers),
+                headers: std::mem::replace(
+                    &mut part_headers,
+                    header_pool.pop().unwrap_or_default(),
+                ),
                 encoding,
                 is_encoding_problem,
                 body: body_part,
@@ -509,9 +883,11 @@ impl MessageParser {
         }
 
         message.raw_message = raw_message.into();
+        message.is_truncated = is_truncated;
 
         if !message.is_empty() {
             message.parts[0].offset_end = message.raw_message.len();
+            message.split_inline_parts();
             Some(message)
         } else if !part_headers.is_empty() {
             // Message without a body
@@ -520,7 +896,7 @@ impl MessageParser {
                 encoding: Encoding::None,
                 is_encoding_problem: true,
                 body: PartType::Text("".into()),
-                offset_header: 0,
+                offset_header: state.offset_header,
                 offset_body: message.raw_message.len(),
                 offset_end: message.raw_message.len(),
             });
@@ -531,6 +907,57 @@ impl MessageParser {
     }
 }
 
+impl MessageParser {
+    /// Returns the Content-Transfer-Encoding selected by the configured
+    /// [`TransferEncodingPolicy`] when a part has several
+    /// Content-Transfer-Encoding header fields.
+    pub(crate) fn transfer_encoding<'y, 'x>(
+        &self,
+        headers: &'y [Header<'x>],
+    ) -> Option<&'y HeaderValue<'x>> {
+        let mut values = headers
+            .iter()
+            .filter(|h| h.name == HeaderName::ContentTransferEncoding)
+            .map(|h| &h.value);
+        let first = values.next();
+        let mut last = first;
+        let mut has_conflict = false;
+        for value in values {
+            has_conflict |= !value
+                .as_text()
+                .zip(first.and_then(|v| v.as_text()))
+                .is_some_and(|(a, b)| a.trim().eq_ignore_ascii_case(b.trim()));
+            last = Some(value);
+        }
+
+        match self.transfer_encoding_policy {
+            TransferEncodingPolicy::First => first,
+            TransferEncodingPolicy::Last => last,
+            TransferEncodingPolicy::Ignore if has_conflict => None,
+            TransferEncodingPolicy::Ignore => first,
+        }
+    }
+}
+
+/// Clears `vec` and returns its allocation as an empty vector of `U`. Used to
+/// reuse vectors whose element types only differ in their lifetime, a new
+/// vector is returned if the layouts of `T` and `U` do not match.
+fn recycle_vec<T, U>(mut vec: Vec<T>) -> Vec<U> {
+    if std::mem::size_of::<T>() != std::mem::size_of::<U>()
+        || std::mem::align_of::<T>() != std::mem::align_of::<U>()
+    {
+        return Vec::new();
+    }
+
+    vec.clear();
+    let mut vec = std::mem::ManuallyDrop::new(vec);
+    let (ptr, capacity) = (vec.as_mut_ptr(), vec.capacity());
+
+    // SAFETY: the vector is empty and its buffer was allocated by the global
+    // allocator for `capacity` elements with the same size and alignment as `U`.
+    unsafe { Vec::from_raw_parts(ptr as *mut U, 0, capacity) }
+}
+
 impl<'x> Message<'x> {
     fn new() -> Message<'x> {
         Message {
@@ -538,10 +965,51 @@ impl<'x> Message<'x> {
         }
     }
 
+    /// Returns an empty message that keeps the allocated capacity of the part,
+    /// header and body lists, for use with [`MessageParser::parse_into`]. The returned
+    /// message is not bound to the lifetime of the original raw message.
+    pub fn recycle<'y>(self) -> Message<'y> {
+        let mut parts = self.parts;
+        let mut header_pool = Vec::with_capacity(self.header_pool.0.len() + parts.len());
+        header_pool.extend(self.header_pool.0.into_iter().map(recycle_vec));
+        header_pool.extend(
+            parts
+                .iter_mut()
+                .map(|part| recycle_vec(std::mem::take(&mut part.headers))),
+        );
+        let mut html_body = self.html_body;
+        html_body.clear();
+        let mut text_body = self.text_body;
+        text_body.clear();
+        let mut attachments = self.attachments;
+        attachments.clear();
+        let mut inline_parts = self.inline_parts;
+        inline_parts.clear();
+
+        Message {
+            html_body,
+            text_body,
+            attachments,
+            inline_parts,
+            parts: recycle_vec(parts),
+            raw_message: Cow::Borrowed(b""),
+            is_truncated: false,
+            header_pool: HeaderPool(header_pool),
+        }
+    }
+
     /// Returns `false` if at least one header field was successfully parsed.
     pub fn is_empty(&self) -> bool {
         self.parts.is_empty()
     }
+
+    /// Returns `true` if parts were left unparsed or their contents were cut
+    /// because one of the limits set with [`MessageParser::with_max_parts`],
+    /// [`MessageParser::with_max_nesting_depth`] or
+    /// [`MessageParser::with_max_part_size`] was reached.
+    pub fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
 }
 
 pub trait IntoByteSlice<'x> {
@@ -580,9 +1048,15 @@ impl<'x> IntoByteSlice<'x> for &'x Vec<u8> {
 
 #[cfg(test)]
 mod tests {
-    use std::{fs, path::PathBuf};
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
 
-    use crate::MessageParser;
+    use crate::{
+        ContentTypePolicy, Encoding, HeaderName, Message, MessageParser, MimeHeaders, ParserLimits,
+        PartType, TransferEncodingPolicy,
+    };
 
     #[test]
     fn parse_full_messages() {
@@ -596,7 +1070,7 @@ mod tests {
 
             for file_name in fs::read_dir(&test_dir).unwrap() {
                 let mut file_name = file_name.unwrap().path();
-                if file_name.extension().map_or(false, |e| e == "eml") {
+                if file_name.extension().is_some_and(|e| e == "eml") {
                     let raw_original = fs::read(&file_name).unwrap();
                     tests_run += 1;
 
@@ -644,6 +1118,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_parts_in_chunks() {
+        fn check_parts(message: &Message<'_>, file_name: &Path, encoded_parts: &mut usize) {
+            for (part_id, part) in message.parts.iter().enumerate() {
+                if part.encoding != Encoding::None && !part.is_text() {
+                    *encoded_parts += 1;
+                }
+                let mut buf = Vec::new();
+                part.decode_to(&message.raw_message, &mut buf).unwrap();
+                assert_eq!(
+                    buf,
+                    part.contents(),
+                    "failed for part {part_id} of {}",
+                    file_name.display()
+                );
+                if let PartType::Message(nested) = &part.body {
+                    check_parts(nested, file_name, encoded_parts);
+                }
+            }
+        }
+
+        let mut encoded_parts = 0;
+        for test_suite in ["rfc", "legacy", "thirdparty", "malformed"] {
+            let test_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("resources")
+                .join("eml")
+                .join(test_suite);
+
+            for file_name in fs::read_dir(&test_dir).unwrap() {
+                let file_name = file_name.unwrap().path();
+                if file_name.extension().is_some_and(|e| e == "eml") {
+                    let raw_original = fs::read(&file_name).unwrap();
+                    for raw_message in [strip_crlf(&raw_original), add_crlf(&raw_original)] {
+                        let message = MessageParser::default().parse(&raw_message).unwrap();
+                        check_parts(&message, &file_name, &mut encoded_parts);
+                    }
+                }
+            }
+        }
+        assert!(
+            encoded_parts > 100,
+            "only {encoded_parts} encoded parts found"
+        );
+    }
+
+    #[test]
+    fn recycle_reuses_header_lists() {
+        let parser = MessageParser::default();
+        let input = concat!(
+            "Subject: test\n",
+            "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+            "--a\n",
+            "Content-Type: text/plain\n\n",
+            "one\n",
+            "--a\n",
+            "Content-Type: text/html\n\n",
+            "<p>two</p>\n",
+            "--a--\n",
+        );
+
+        let message = parser.parse(input).unwrap().recycle();
+        let pool = message
+            .header_pool
+            .0
+            .iter()
+            .map(|headers| (headers.as_ptr(), headers.capacity()))
+            .collect::<Vec<_>>();
+        assert_eq!(pool.len(), 3);
+        assert!(pool.iter().all(|(_, capacity)| *capacity > 0));
+
+        let mut message = message;
+        assert!(parser.parse_into(input, &mut message));
+        assert!(message.header_pool.0.is_empty());
+        for part in &message.parts {
+            assert!(pool.contains(&(part.headers.as_ptr(), part.headers.capacity())));
+        }
+    }
+
     fn add_crlf(bytes: &[u8]) -> Vec<u8> {
         let mut result = Vec::with_capacity(bytes.len());
         let mut last_ch = 0;
@@ -668,4 +1220,604 @@ mod tests {
 
         result
     }
+
+    #[test]
+    fn address_raw_text() {
+        let input =
+            "From: \"Art\" <art@vandelay.com> (Vandelay)\nTo: a@b.c,  d@e.f\nSubject: a\n\nbody";
+
+        let parser = MessageParser::default();
+        let message = parser.parse(input).unwrap();
+        let header = |name: HeaderName<'_>| {
+            message
+                .headers()
+                .iter()
+                .find(|header| header.name == name)
+                .unwrap()
+        };
+        let raw_text = |name: HeaderName<'_>| parser.address_raw_text(input, header(name)).unwrap();
+        assert_eq!(
+            raw_text(HeaderName::From).address(0),
+            Some("\"Art\" <art@vandelay.com> (Vandelay)")
+        );
+        let to = raw_text(HeaderName::To);
+        assert_eq!(
+            (0..3).map(|index| to.address(index)).collect::<Vec<_>>(),
+            vec![Some("a@b.c"), Some("d@e.f"), None]
+        );
+
+        assert_eq!(
+            parser.address_raw_text(input, header(HeaderName::Subject)),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_part_headers() {
+        let part = concat!(
+            "Content-Type: image/png; name=\"logo.png\"\r\n",
+            "Content-Disposition: inline; filename=\"logo.png\"\r\n",
+            "Content-Transfer-Encoding: base64\r\n",
+            "Content-ID: <logo@example.org>\r\n",
+            "\r\n",
+            "iVBORw0KGgo=\r\n"
+        );
+
+        let headers = MessageParser::default().parse_part_headers(part).unwrap();
+        assert!(headers.is_content_type("image", "png"));
+        assert_eq!(headers.content_disposition().unwrap().ctype(), "inline");
+        assert_eq!(headers.content_transfer_encoding(), Some("base64"));
+        assert_eq!(headers.content_id(), Some("logo@example.org"));
+        assert_eq!(headers.attachment_name(), Some("logo.png"));
+        assert_eq!(&part[headers.offset_body..], "iVBORw0KGgo=\r\n");
+
+        let headers = MessageParser::default()
+            .parse_part_headers("\r\nbody")
+            .unwrap();
+        assert_eq!(headers.headers, vec![]);
+        assert_eq!(headers.offset_body, 2);
+
+        assert_eq!(MessageParser::default().parse_part_headers(""), None);
+    }
+
+    #[test]
+    fn duplicate_transfer_encoding() {
+        let input = concat!(
+            "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+            "--a\n",
+            "Content-Transfer-Encoding: base64\n",
+            "Content-Transfer-Encoding: quoted-printable\n\n",
+            "aGVsbG8=3D\n",
+            "--a\n",
+            "Content-Transfer-Encoding: base64\n",
+            "Content-Transfer-Encoding: BASE64\n\n",
+            "aGVsbG8=\n",
+            "--a\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "aGVsbG8=\n",
+            "--a--\n",
+        );
+
+        for (policy, expected) in [
+            (TransferEncodingPolicy::First, "hello"),
+            (TransferEncodingPolicy::Last, "aGVsbG8="),
+            (TransferEncodingPolicy::Ignore, "aGVsbG8=3D"),
+        ] {
+            let message = MessageParser::default()
+                .with_transfer_encoding_policy(policy)
+                .parse(input)
+                .unwrap();
+            assert_eq!(message.body_text(0).unwrap(), expected, "{policy:?}");
+            assert_eq!(message.body_text(1).unwrap(), "hello", "{policy:?}");
+            assert_eq!(
+                message.parts[1..]
+                    .iter()
+                    .map(|part| (
+                        part.has_duplicate_transfer_encoding(),
+                        part.is_encoding_problem
+                    ))
+                    .collect::<Vec<_>>(),
+                [(true, false), (true, false), (false, false)],
+                "{policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn content_type_policy() {
+        let input = concat!(
+            "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+            "--mixed\n",
+            "Content-Type: text; charset=utf-8\n\n",
+            "No subtype\n",
+            "--mixed\n",
+            "Content-Type: multipart/alternative; boundary=\"missing\"\n\n",
+            "Boundary not found\n",
+            "--mixed\n",
+            "Content-Type: multipart/digest; boundary=\"digest\"\n\n",
+            "--digest\n",
+            "Content-Type: message/\n\n",
+            "Subject: Digest entry\n\n",
+            "Digest body\n",
+            "--digest--\n",
+            "--mixed--\n",
+        );
+
+        // The explicit Content-Type wins: parts are attachments of their declared type
+        let message = MessageParser::default().parse(input).unwrap();
+        assert_eq!(message.parts.len(), 5);
+        assert!(matches!(message.parts[1].body, PartType::Text(_)));
+        assert_eq!(message.parts[1].content_type().unwrap().ctype(), "text");
+        assert!(matches!(message.parts[2].body, PartType::Text(_)));
+        assert!(matches!(message.parts[4].body, PartType::Binary(_)));
+        assert!(message.text_body.is_empty());
+        assert_eq!(message.attachments, vec![1, 2, 4]);
+
+        // Structural defaults override the malformed Content-Type fields
+        let message = MessageParser::default()
+            .with_content_type_policy(ContentTypePolicy::Structural)
+            .parse(input)
+            .unwrap();
+        assert_eq!(message.parts.len(), 5);
+        assert_eq!(message.body_text(0).unwrap(), "No subtype");
+        assert_eq!(message.body_text(1).unwrap(), "Boundary not found");
+        assert_eq!(message.text_body, vec![1, 2]);
+        assert_eq!(message.attachments, vec![4]);
+        assert_eq!(
+            message.parts[4].message().unwrap().subject(),
+            Some("Digest entry")
+        );
+
+        // Well-formed parts are not affected by the policy
+        let input = concat!(
+            "Content-Type: multipart/digest; boundary=\"digest\"\n\n",
+            "--digest\n",
+            "Content-Type: text/plain\n\n",
+            "Not a message\n",
+            "--digest--\n",
+        );
+        for policy in [ContentTypePolicy::Explicit, ContentTypePolicy::Structural] {
+            let message = MessageParser::default()
+                .with_content_type_policy(policy)
+                .parse(input)
+                .unwrap();
+            assert_eq!(message.body_text(0).unwrap(), "Not a message", "{policy:?}");
+            assert!(message.attachments.is_empty(), "{policy:?}");
+        }
+    }
+
+    #[test]
+    fn mixed_alternative_bodies() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: multipart/alternative; boundary=\"alt\"\n\n",
+                "--alt\n",
+                "Content-Type: text/plain\n\n",
+                "Plain body\n",
+                "--alt\n",
+                "Content-Type: multipart/related; boundary=\"rel\"\n\n",
+                "--rel\n",
+                "Content-Type: text/html\n\n",
+                "<p>HTML body</p>\n",
+                "--rel\n",
+                "Content-Type: image/png\n",
+                "Content-ID: <logo>\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "iVBORw0KGgo=\n",
+                "--rel--\n",
+                "--alt--\n",
+                "--mixed\n",
+                "Content-Type: application/pdf; name=\"report.pdf\"\n",
+                "Content-Disposition: attachment; filename=\"report.pdf\"\n\n",
+                "%PDF\n",
+                "--mixed--\n",
+            ))
+            .unwrap();
+
+        assert_eq!(message.text_body, vec![2]);
+        assert_eq!(message.html_body, vec![4]);
+        assert_eq!(message.attachments, vec![5, 6]);
+        assert_eq!(message.body_text(0).unwrap(), "Plain body");
+        assert_eq!(message.body_html(0).unwrap(), "<p>HTML body</p>");
+        assert_eq!(
+            message.attachment(1).unwrap().attachment_name(),
+            Some("report.pdf")
+        );
+    }
+
+    #[test]
+    fn max_parts() {
+        let mut input = "Content-Type: multipart/mixed; boundary=\"a\"\n\n".to_string();
+        for i in 0..1000 {
+            input.push_str(&format!(
+                "--a\nContent-Type: application/octet-stream\n\npart {i}\n"
+            ));
+        }
+        input.push_str("--a--\n");
+
+        let message = MessageParser::default().parse(&input).unwrap();
+        assert_eq!(message.parts.len(), 1001);
+        assert!(!message.is_truncated());
+
+        let message = MessageParser::default()
+            .with_max_parts(10)
+            .parse(&input)
+            .unwrap();
+        assert!(message.is_truncated());
+        assert_eq!(message.parts.len(), 11);
+        assert_eq!(message.attachments().count(), 9);
+        let blob = &message.parts[10];
+        assert!(blob.is_encoding_problem);
+        assert!(blob
+            .contents()
+            .starts_with(b"Content-Type: application/octet-stream\n\npart 9\n"));
+        assert!(blob.contents().ends_with(b"part 999\n--a--\n"));
+        assert_eq!(
+            message.parts[0].sub_parts().unwrap(),
+            (1..=10).collect::<Vec<_>>()
+        );
+
+        let message = MessageParser::default()
+            .with_max_parts(1001)
+            .parse(&input)
+            .unwrap();
+        assert!(!message.is_truncated());
+        assert_eq!(message.parts.len(), 1001);
+    }
+
+    #[test]
+    fn boundary_with_spaces() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\" b \"\n\n",
+                "--b\n",
+                "Content-Type: text/plain\n\n",
+                "wrong\n",
+                "-- b \n",
+                "Content-Type: text/plain\n\n",
+                "first\n",
+                "-- b \n",
+                "Content-Type: text/plain\n\n",
+                "second\n",
+                "-- b --\n",
+            ))
+            .unwrap();
+        assert_eq!(
+            message.content_type().unwrap().attribute("boundary"),
+            Some(" b ")
+        );
+        assert_eq!(message.text_body, vec![1, 2]);
+        assert_eq!(message.body_text(0).unwrap(), "first");
+        assert_eq!(message.body_text(1).unwrap(), "second");
+    }
+
+    #[test]
+    fn encoded_message_rfc822() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Subject: Forwarded\n",
+                "Content-Type: multipart/mixed; boundary=\"b\"\n\n",
+                "--b\n",
+                "Content-Type: text/plain\n\n",
+                "See attached.\n",
+                "--b\n",
+                "Content-Type: message/rfc822\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "RnJvbTogaW5uZXJAZXhhbXBsZS5jb20NClN1YmplY3Q6IElubmVyIHN1YmplY3QNCkNvbnRlbnQt\n",
+                "VHlwZTogdGV4dC9wbGFpbg0KDQpJbm5lciBib2R5DQo=\n",
+                "--b--\n",
+            ))
+            .unwrap();
+
+        let part = message.attachment(0).unwrap();
+        assert_eq!(part.encoding, Encoding::Base64);
+        assert!(!part.is_encoding_problem);
+
+        let inner = part.message().unwrap();
+        assert_eq!(inner.subject(), Some("Inner subject"));
+        assert_eq!(
+            inner.from().unwrap().first().unwrap().address(),
+            Some("inner@example.com")
+        );
+        assert_eq!(inner.body_text(0).as_deref(), Some("Inner body\r\n"));
+    }
+
+    #[test]
+    fn multipart_without_boundary() {
+        let body = concat!(
+            "--foo\n",
+            "Content-Type: text/plain\n\n",
+            "hello\n",
+            "--foo--\n"
+        );
+        let input = format!("Content-Type: multipart/mixed\nSubject: test\n\n{body}");
+        let message = MessageParser::default().parse(input.as_bytes()).unwrap();
+        assert_eq!(message.parts.len(), 1);
+        assert!(message.parts[0].is_encoding_problem);
+        assert_eq!(message.parts[0].contents(), body.as_bytes());
+        assert!(matches!(message.parts[0].body, PartType::Binary(_)));
+        assert_eq!(message.attachments, vec![0]);
+
+        // Nested parts are flagged as well
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: multipart/alternative\n\n",
+                "body text\n",
+                "--a--\n",
+            ))
+            .unwrap();
+        assert_eq!(message.parts.len(), 2);
+        assert!(!message.parts[0].is_encoding_problem);
+        assert!(message.parts[1].is_encoding_problem);
+        assert_eq!(message.parts[1].contents(), b"body text");
+    }
+
+    #[test]
+    fn parse_part() {
+        let nested = concat!(
+            "Subject: Nested\n",
+            "Content-Type: multipart/alternative; boundary=\"nested\"\n\n",
+            "--nested\n",
+            "Content-Type: text/plain\n\n",
+            "Nested text\n",
+            "--nested\n",
+            "Content-Type: text/html\n\n",
+            "<p>Nested html</p>\n",
+            "--nested--\n",
+        );
+        let input = format!(
+            concat!(
+                "From sender@example.com Mon Jan  2 15:04:05 2006\n",
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: text/plain; charset=iso-8859-1\n",
+                "Content-Transfer-Encoding: quoted-printable\n\n",
+                "Caf=E9\n",
+                "--mixed\n",
+                "Content-Type: multipart/mixed; boundary=\"mixed-2\"\n\n",
+                "--mixed-2\n",
+                "Content-Type: application/octet-stream\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "aGVsbG8=\n",
+                "--mixed-2\n",
+                "Content-Type: message/rfc822\n\n",
+                "{}",
+                "--mixed-2--\n",
+                "--mixed\n",
+                "Content-Type: multipart/digest; boundary=\"digest\"\n\n",
+                "--digest\n\n",
+                "Subject: Digest entry\n\n",
+                "Digest body\n",
+                "--digest--\n",
+                "--mixed\n",
+                "Content-Type: message/rfc822\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "{}\n",
+                "--mixed--\n",
+            ),
+            nested, "U3ViamVjdDogRW5jb2RlZAoKRW5jb2RlZCBib2R5Cg=="
+        );
+
+        let parser = MessageParser::default();
+        let message = parser.parse(&input).unwrap();
+
+        for path in [
+            "1", "2", "2.1", "2.2", "2.2.1", "2.2.2", "3", "3.1", "3.1.1", "4", "4.1",
+        ] {
+            let expected = message.part_by_imap_path(path).unwrap();
+            let part = parser.parse_part(&input, path).unwrap();
+            assert_eq!(part.headers, expected.headers, "failed for {path}");
+            assert_eq!(
+                part.offset_header, expected.offset_header,
+                "failed for {path}"
+            );
+            assert_eq!(part.offset_body, expected.offset_body, "failed for {path}");
+            if !expected.is_multipart() {
+                assert_eq!(part.contents(), expected.contents(), "failed for {path}");
+            }
+        }
+
+        let part = parser.parse_part(&input, "1").unwrap();
+        assert_eq!(part.text_contents(), Some("Café"));
+        assert_eq!(
+            parser.parse_part(&input, "2.1").unwrap().contents(),
+            b"hello"
+        );
+        assert_eq!(
+            parser.parse_part(&input, "2.2.2").unwrap().text_contents(),
+            Some("<p>Nested html</p>")
+        );
+        assert_eq!(
+            parser
+                .parse_part(&input, "2.2")
+                .unwrap()
+                .message()
+                .unwrap()
+                .subject(),
+            Some("Nested")
+        );
+        assert_eq!(
+            parser
+                .parse_part(&input, "3.1")
+                .unwrap()
+                .message()
+                .unwrap()
+                .subject(),
+            Some("Digest entry")
+        );
+        assert_eq!(
+            parser.parse_part(&input, "4.1").unwrap().text_contents(),
+            Some("Encoded body\n")
+        );
+
+        // Multipart parts are returned undecoded
+        let part = parser.parse_part(&input, "2.2.1").unwrap();
+        assert_eq!(part.text_contents(), Some("Nested text"));
+        let part = parser.parse_part(&input, "3").unwrap();
+        assert!(matches!(part.body, PartType::Binary(_)));
+        assert!(part.contents().starts_with(b"--digest\n"));
+
+        for path in ["", "0", "5", "1.1", "2.3", "2.2.3", "x", "1..2"] {
+            assert!(
+                parser.parse_part(&input, path).is_none(),
+                "failed for {path:?}"
+            );
+        }
+
+        // Body of a single part message
+        let part = parser
+            .parse_part("Subject: test\n\nHello, world\n", "1")
+            .unwrap();
+        assert_eq!(part.text_contents(), Some("Hello, world\n"));
+        assert!(parser
+            .parse_part("Subject: test\n\nHello, world\n", "2")
+            .is_none());
+    }
+
+    #[test]
+    fn max_nesting_depth() {
+        let mut input = String::new();
+        for i in 0..100 {
+            input.push_str(&format!(
+                "Content-Type: multipart/mixed; boundary=\"b{i}x\"\n\n--b{i}x\n"
+            ));
+        }
+        input.push_str("Content-Type: text/plain\n\nDeepest\n");
+        for i in (0..100).rev() {
+            input.push_str(&format!("--b{i}x--\n"));
+        }
+
+        let message = MessageParser::default().parse(&input).unwrap();
+        assert!(!message.is_truncated());
+        assert_eq!(
+            message.walk_parts().map(|(depth, _)| depth).max(),
+            Some(100)
+        );
+        assert_eq!(
+            message.parts.last().unwrap().text_contents(),
+            Some("Deepest")
+        );
+
+        let message = MessageParser::default()
+            .with_max_nesting_depth(3)
+            .parse(&input)
+            .unwrap();
+        assert!(message.is_truncated());
+        assert_eq!(
+            message
+                .walk_parts()
+                .map(|(depth, part)| (depth, part.is_multipart()))
+                .collect::<Vec<_>>(),
+            [(0, true), (1, true), (2, true), (3, false)]
+        );
+        let blob = &message.parts[3];
+        assert!(blob.is_binary());
+        assert!(blob
+            .contents()
+            .starts_with(b"--b3x\nContent-Type: multipart/mixed; boundary=\"b4x\"\n"));
+
+        // Nested messages count towards the nesting depth
+        let input = concat!(
+            "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+            "--mixed\n",
+            "Content-Type: message/rfc822\n\n",
+            "Subject: Nested\n\n",
+            "Nested body\n",
+            "--mixed\n",
+            "Content-Type: message/rfc822\n",
+            "Content-Transfer-Encoding: base64\n\n",
+            "U3ViamVjdDogRW5jb2RlZAoKRW5jb2RlZCBib2R5Cg==\n",
+            "--mixed--\n",
+        );
+        let message = MessageParser::default()
+            .with_max_nesting_depth(2)
+            .parse(input)
+            .unwrap();
+        assert!(!message.is_truncated());
+        assert_eq!(
+            message
+                .walk_parts()
+                .map(|(depth, _)| depth)
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 1, 2]
+        );
+
+        let message = MessageParser::default()
+            .with_max_nesting_depth(1)
+            .parse(input)
+            .unwrap();
+        assert!(message.is_truncated());
+        assert_eq!(
+            message
+                .walk_parts()
+                .map(|(depth, part)| (depth, part.is_binary()))
+                .collect::<Vec<_>>(),
+            [(0, false), (1, true), (1, true)]
+        );
+        assert_eq!(
+            message.parts[1].contents(),
+            b"Subject: Nested\n\nNested body"
+        );
+        assert_eq!(
+            message.parts[2].contents(),
+            b"Subject: Encoded\n\nEncoded body\n"
+        );
+    }
+
+    #[test]
+    fn max_part_size() {
+        let input = format!(
+            concat!(
+                "Content-Type: multipart/mixed; boundary=\"mixed\"\n\n",
+                "--mixed\n",
+                "Content-Type: text/plain\n\n",
+                "Short text\n",
+                "--mixed\n",
+                "Content-Type: application/octet-stream\n",
+                "Content-Transfer-Encoding: base64\n\n",
+                "{}\n",
+                "--mixed--\n",
+            ),
+            "AAAA\n".repeat(100_000)
+        );
+
+        let message = MessageParser::default().parse(&input).unwrap();
+        assert!(!message.is_truncated());
+        assert_eq!(message.parts[2].len(), 300_000);
+
+        let message = MessageParser::default()
+            .with_max_part_size(1024)
+            .parse(&input)
+            .unwrap();
+        assert!(message.is_truncated());
+        assert_eq!(message.parts[1].text_contents(), Some("Short text"));
+        assert_eq!(message.parts[2].contents(), &[0u8; 1024][..]);
+
+        let message = MessageParser::default()
+            .with_max_part_size(5)
+            .parse(&input)
+            .unwrap();
+        assert!(message.is_truncated());
+        assert_eq!(message.parts[1].text_contents(), Some("Short"));
+        assert_eq!(message.parts[2].len(), 5);
+
+        // All limits can be set at once
+        let parser = MessageParser::default().with_limits(ParserLimits {
+            max_parts: 2,
+            max_part_size: 5,
+            ..Default::default()
+        });
+        let message = parser.parse(&input).unwrap();
+        assert!(message.is_truncated());
+        assert_eq!(message.parts.len(), 3);
+        assert_eq!(message.parts[1].text_contents(), Some("Short"));
+        assert_eq!(
+            MessageParser::default().with_limits(ParserLimits::default()),
+            MessageParser::default()
+        );
+    }
 }
@@ -58,6 +58,20 @@ impl<'x> MessageStream<'x> {
         None
     }
 
+    // Seeks the next delimiter line of `boundary`, skipping longer boundaries
+    // that start with it, and returns the end offset of the preceding part
+    pub fn seek_delimiter(&mut self, boundary: &[u8]) -> Option<usize> {
+        loop {
+            let offset_end = self.seek_next_part_offset(boundary)?;
+            let remaining = &self.data[self.offset()..];
+            if remaining.starts_with(b"--")
+                || matches!(remaining.first(), None | Some(b'\r' | b'\n' | b' ' | b'\t'))
+            {
+                return Some(offset_end);
+            }
+        }
+    }
+
     pub fn mime_part(&mut self, boundary: &[u8]) -> (usize, Cow<'x, [u8]>) {
         let mut last_ch = b'\n';
         let mut before_last_ch = 0;
@@ -11,17 +11,31 @@
 
 use std::{iter::Peekable, ops::Range, slice::Iter};
 
+use crate::EncodedWord;
+
+pub mod events;
 pub mod fields;
 pub mod header;
 pub mod message;
 pub mod mime;
 pub mod preview;
+pub mod vcard;
 
 pub struct MessageStream<'x> {
     data: &'x [u8],
     iter: Peekable<Iter<'x, u8>>,
     pos: usize,
     restore_pos: usize,
+    pub(crate) encoded_words: Option<Vec<EncodedWord<'x>>>,
+    pub(crate) comment_display_names: bool,
+    pub(crate) lenient_address_names: bool,
+    pub(crate) lenient_attribute_values: bool,
+    pub(crate) max_attribute_name_len: usize,
+    pub(crate) max_attribute_value_len: usize,
+    pub(crate) max_attribute_continuations: usize,
+    // Limit of the MIME part decoders and whether the last decoded part reached it
+    pub(crate) max_decoded_len: usize,
+    pub(crate) is_decoded_truncated: bool,
 }
 
 impl<'x> MessageStream<'x> {
@@ -31,6 +45,15 @@ impl<'x> MessageStream<'x> {
             iter: data.iter().peekable(),
             pos: 0,
             restore_pos: 0,
+            encoded_words: None,
+            comment_display_names: false,
+            lenient_address_names: false,
+            lenient_attribute_values: false,
+            max_attribute_name_len: usize::MAX,
+            max_attribute_value_len: usize::MAX,
+            max_attribute_continuations: fields::content_type::MAX_ATTRIBUTE_CONTINUATIONS,
+            max_decoded_len: usize::MAX,
+            is_decoded_truncated: false,
         }
     }
 
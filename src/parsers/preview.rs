@@ -100,6 +100,89 @@ pub fn truncate_html<'x>(html: Cow<'_, str>, mut max_len: usize) -> Cow<'x, str>
     }
 }
 
+// Collapses whitespace and truncates the text to `max_chars` characters,
+// cutting at a word boundary when possible and ending with "..." if cut.
+pub fn preview_words(text: &str, max_chars: usize) -> String {
+    let mut result = String::with_capacity(std::cmp::min(text.len(), max_chars * 4));
+    let mut num_chars = 0;
+
+    for word in text.split_whitespace() {
+        if num_chars > 0 {
+            result.push(' ');
+            num_chars += 1;
+        }
+        result.push_str(word);
+        num_chars += word.chars().count();
+        if num_chars > max_chars {
+            break;
+        }
+    }
+
+    if num_chars <= max_chars {
+        return result;
+    }
+
+    let add_dots = max_chars > 6;
+    let max_chars = if add_dots { max_chars - 3 } else { max_chars };
+    let cut_pos = result
+        .char_indices()
+        .nth(max_chars)
+        .map_or(result.len(), |(pos, _)| pos);
+    let cut_pos = if result[cut_pos..].starts_with(' ') {
+        cut_pos
+    } else {
+        result[..cut_pos].rfind(' ').unwrap_or(cut_pos)
+    };
+    result.truncate(cut_pos);
+    if add_dots {
+        result.push_str("...");
+    }
+    result
+}
+
+// Joins the soft line breaks of RFC 3676 format=flowed text, removing
+// space-stuffing and, when `delsp` is set, the space preceding each soft break.
+pub fn unflow_text(text: &str, delsp: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut flowed_depth = None;
+
+    for line in text.lines() {
+        let depth = line.bytes().take_while(|&ch| ch == b'>').count();
+        let content = &line[depth..];
+        let content = content.strip_prefix(' ').unwrap_or(content);
+        let is_flowed = content.ends_with(' ') && content != "-- ";
+
+        if flowed_depth != Some(depth) {
+            if flowed_depth.is_some() {
+                result.push('\n');
+            }
+            if depth > 0 {
+                result.push_str(&line[..depth]);
+                result.push(' ');
+            }
+        }
+
+        if is_flowed {
+            result.push_str(if delsp {
+                &content[..content.len() - 1]
+            } else {
+                content
+            });
+            flowed_depth = Some(depth);
+        } else {
+            result.push_str(content);
+            result.push('\n');
+            flowed_depth = None;
+        }
+    }
+
+    if flowed_depth.is_some() {
+        result.push('\n');
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -137,6 +220,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn word_preview() {
+        for (text, max_chars, expected) in [
+            ("hello   world\n\n", 20, "hello world"),
+            ("hello world", 11, "hello world"),
+            ("hello wonderful world", 16, "hello..."),
+            ("hello wonderful world", 18, "hello wonderful..."),
+            ("incomprehensibilities", 12, "incompreh..."),
+            ("día de año nuevo", 13, "día de año..."),
+            ("día de año nuevo", 12, "día de..."),
+            ("長沮桀溺耦而耕孔子過之", 9, "長沮桀溺耦而..."),
+            ("hello world", 4, "hell"),
+            ("hello", 0, ""),
+            ("", 10, ""),
+        ] {
+            assert_eq!(
+                super::preview_words(text, max_chars),
+                expected,
+                "failed for {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn flowed_text() {
+        for (text, delsp, expected) in [
+            (
+                "This is a \r\nflowed paragraph.\r\nFixed line\r\n",
+                false,
+                "This is a flowed paragraph.\nFixed line\n",
+            ),
+            ("Split wo \nrd\n", true, "Split word\n"),
+            (" From the start \n line\n", false, "From the start line\n"),
+            (
+                "> quoted \n> text\n>> deeper \n>> quote\nreply\n",
+                false,
+                "> quoted text\n>> deeper quote\nreply\n",
+            ),
+            ("-- \nSignature\n", false, "-- \nSignature\n"),
+            ("Trailing soft break ", false, "Trailing soft break \n"),
+        ] {
+            assert_eq!(
+                super::unflow_text(text, delsp),
+                expected,
+                "failed for {text:?}"
+            );
+        }
+    }
+
     #[test]
     fn html_truncate() {
         for (html, expected_result) in [
@@ -0,0 +1,324 @@
+/*
+ * Copyright Stalwart Labs Ltd. See the COPYING
+ * file at the top-level directory of this distribution.
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use crate::{
+    decoders::{
+        base64::base64_decode, charsets::map::charset_decoder,
+        quoted_printable::quoted_printable_decode,
+    },
+    MessagePart, MimeHeaders, VCard,
+};
+
+impl VCard {
+    /// Parses the `FN`, `N`, `EMAIL`, `TEL`, `ORG` and, if `decode_photo` is
+    /// set, `PHOTO` properties of the first card in `text`. Values encoded as
+    /// quoted-printable or with a vCard 2.1 `CHARSET` parameter are decoded.
+    ///
+    /// Returns `None` if no `BEGIN:VCARD` line is found.
+    pub fn parse(text: &str, decode_photo: bool) -> Option<VCard> {
+        let mut lines = unfold_lines(text).into_iter();
+        lines.find(|line| line.trim().eq_ignore_ascii_case("begin:vcard"))?;
+
+        let mut card = VCard::default();
+        for line in lines {
+            let Some((name, params, value)) = split_property(&line) else {
+                continue;
+            };
+            let encoding = param(&params, "ENCODING");
+            let is_qp = encoding.is_some_and(|e| e.eq_ignore_ascii_case("quoted-printable"))
+                || params
+                    .iter()
+                    .any(|(p, v)| v.is_none() && p.eq_ignore_ascii_case("quoted-printable"));
+            let text_value = || {
+                let bytes = if is_qp {
+                    quoted_printable_decode(value.as_bytes())
+                        .unwrap_or_else(|| value.as_bytes().to_vec())
+                } else {
+                    value.as_bytes().to_vec()
+                };
+                match param(&params, "CHARSET").and_then(|c| charset_decoder(c.as_bytes())) {
+                    Some(decoder) => decoder(&bytes),
+                    None => String::from_utf8(bytes)
+                        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()),
+                }
+            };
+
+            match name.to_ascii_uppercase().as_str() {
+                "END" if value.trim().eq_ignore_ascii_case("vcard") => break,
+                "FN" if card.full_name.is_none() => {
+                    card.full_name = Some(unescape(&text_value()));
+                }
+                "N" if card.name.is_empty() => {
+                    card.name = split_unescaped(&text_value(), ';');
+                }
+                "EMAIL" => card.emails.push(unescape(&text_value())),
+                "TEL" => card
+                    .phones
+                    .push(unescape(text_value().trim_start_matches("tel:"))),
+                "ORG" if card.organization.is_none() => {
+                    card.organization = Some(split_unescaped(&text_value(), ';').join(";"));
+                }
+                "PHOTO" if decode_photo && card.photo.is_none() => {
+                    card.photo = if let Some((_, data)) = value
+                        .strip_prefix("data:")
+                        .and_then(|uri| uri.split_once(";base64,"))
+                    {
+                        // vCard 4.0 data URI
+                        base64_decode(data.as_bytes())
+                    } else if encoding.is_some_and(|e| {
+                        e.eq_ignore_ascii_case("b") || e.eq_ignore_ascii_case("base64")
+                    }) || params
+                        .iter()
+                        .any(|(p, v)| v.is_none() && p.eq_ignore_ascii_case("base64"))
+                    {
+                        base64_decode(value.as_bytes())
+                    } else {
+                        // External URIs are not fetched
+                        None
+                    };
+                }
+                _ => (),
+            }
+        }
+
+        Some(card)
+    }
+}
+
+impl MessagePart<'_> {
+    /// Parses the part as a vCard if its type is `text/vcard`, `text/x-vcard`
+    /// or `text/directory`, see [`VCard::parse`].
+    pub fn vcard(&self, decode_photo: bool) -> Option<VCard> {
+        let ct = self.content_type()?;
+        if ct.ctype().eq_ignore_ascii_case("text")
+            && ct.subtype().is_some_and(|st| {
+                st.eq_ignore_ascii_case("vcard")
+                    || st.eq_ignore_ascii_case("x-vcard")
+                    || st.eq_ignore_ascii_case("directory")
+            })
+        {
+            VCard::parse(self.text_contents()?, decode_photo)
+        } else {
+            None
+        }
+    }
+}
+
+// Joins folded lines, as well as vCard 2.1 quoted-printable soft line breaks
+fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut is_soft_break = false;
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match lines.last_mut() {
+            Some(last) if is_soft_break => last.push_str(line),
+            Some(last) if line.starts_with([' ', '\t']) => last.push_str(&line[1..]),
+            _ => lines.push(line.to_string()),
+        }
+        is_soft_break = line.ends_with('=')
+            && lines.last().is_some_and(|last| {
+                last.split_once(':')
+                    .is_some_and(|(name, _)| name.to_ascii_uppercase().contains("QUOTED-PRINTABLE"))
+            });
+        if is_soft_break {
+            if let Some(last) = lines.last_mut() {
+                last.pop();
+            }
+        }
+    }
+    lines
+}
+
+type Params<'x> = Vec<(&'x str, Option<&'x str>)>;
+
+// Splits `group.NAME;PARAM=VALUE;PARAM:value` into its name, parameters and value
+fn split_property(line: &str) -> Option<(&str, Params<'_>, &str)> {
+    let mut in_quote = false;
+    let colon = line.char_indices().find_map(|(pos, ch)| match ch {
+        '"' => {
+            in_quote = !in_quote;
+            None
+        }
+        ':' if !in_quote => Some(pos),
+        _ => None,
+    })?;
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?;
+    let name = name.rsplit_once('.').map_or(name, |(_, name)| name).trim();
+    let params = parts
+        .map(|param| match param.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+            None => (param.trim(), None),
+        })
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+fn param<'x>(params: &[(&str, Option<&'x str>)], name: &str) -> Option<&'x str> {
+    params
+        .iter()
+        .find(|(param, _)| param.eq_ignore_ascii_case(name))
+        .and_then(|(_, value)| *value)
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => result.push('\n'),
+                Some(ch) => result.push(ch),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result.trim().to_string()
+}
+
+fn split_unescaped(value: &str, separator: char) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut is_escaped = false;
+    for (pos, ch) in value.char_indices() {
+        if is_escaped {
+            is_escaped = false;
+        } else if ch == '\\' {
+            is_escaped = true;
+        } else if ch == separator {
+            result.push(unescape(&value[start..pos]));
+            start = pos + ch.len_utf8();
+        }
+    }
+    result.push(unescape(&value[start..]));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MessageParser, VCard};
+
+    #[test]
+    fn parse_vcard() {
+        for (input, expected) in [
+            (
+                concat!(
+                    "BEGIN:VCARD\r\n",
+                    "VERSION:4.0\r\n",
+                    "FN:Dr. John Doe\\, Jr.\r\n",
+                    "N:Doe;John;Philip\\;Paul;Dr.;Jr.\r\n",
+                    "EMAIL;TYPE=work:john.doe@\r\n",
+                    " example.com\r\n",
+                    "EMAIL;PREF=1:jdoe@example.org\r\n",
+                    "TEL;VALUE=uri;TYPE=\"voice,home\":tel:+1-555-555-5555\r\n",
+                    "item1.ORG:Example\\, Inc.;Research\r\n",
+                    "PHOTO:data:image/png;base64,iVBORw0KGgo=\r\n",
+                    "END:VCARD\r\n",
+                    "BEGIN:VCARD\r\n",
+                    "FN:Second Card\r\n",
+                    "END:VCARD\r\n",
+                ),
+                VCard {
+                    full_name: Some("Dr. John Doe, Jr.".into()),
+                    name: vec![
+                        "Doe".into(),
+                        "John".into(),
+                        "Philip;Paul".into(),
+                        "Dr.".into(),
+                        "Jr.".into(),
+                    ],
+                    emails: vec!["john.doe@example.com".into(), "jdoe@example.org".into()],
+                    phones: vec!["+1-555-555-5555".into()],
+                    organization: Some("Example, Inc.;Research".into()),
+                    photo: Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+                },
+            ),
+            (
+                concat!(
+                    "BEGIN:VCARD\n",
+                    "VERSION:3.0\n",
+                    "FN:Jane Smith\n",
+                    "TEL;TYPE=WORK,VOICE:(111) 555-1212\n",
+                    "PHOTO;ENCODING=b;TYPE=JPEG:/9j/4AAQ\n",
+                    "END:VCARD\n",
+                ),
+                VCard {
+                    full_name: Some("Jane Smith".into()),
+                    phones: vec!["(111) 555-1212".into()],
+                    photo: Some(b"\xff\xd8\xff\xe0\x00\x10".to_vec()),
+                    ..Default::default()
+                },
+            ),
+            (
+                concat!(
+                    "BEGIN:VCARD\n",
+                    "VERSION:2.1\n",
+                    "N;CHARSET=ISO-8859-1;ENCODING=QUOTED-PRINTABLE:M=FCller;J=FCrgen\n",
+                    "FN;ENCODING=QUOTED-PRINTABLE;CHARSET=UTF-8:J=C3=BCrgen M=C3=BC=\n",
+                    "ller\n",
+                    "TEL;WORK;VOICE:+49 30 1234567\n",
+                    "EMAIL;INTERNET:juergen@example.de\n",
+                    "PHOTO;JPEG;BASE64:\n",
+                    "  /9j/4AAQ\n",
+                    "END:VCARD\n",
+                ),
+                VCard {
+                    full_name: Some("Jürgen Müller".into()),
+                    name: vec!["Müller".into(), "Jürgen".into()],
+                    emails: vec!["juergen@example.de".into()],
+                    phones: vec!["+49 30 1234567".into()],
+                    photo: Some(b"\xff\xd8\xff\xe0\x00\x10".to_vec()),
+                    ..Default::default()
+                },
+            ),
+        ] {
+            assert_eq!(VCard::parse(input, true).unwrap(), expected, "{input:?}");
+            assert_eq!(
+                VCard::parse(input, false).unwrap(),
+                VCard {
+                    photo: None,
+                    ..expected
+                },
+                "{input:?}"
+            );
+        }
+
+        assert_eq!(VCard::parse("FN:No card\n", true), None);
+    }
+
+    #[test]
+    fn vcard_part() {
+        let message = MessageParser::default()
+            .parse(concat!(
+                "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+                "--a\n",
+                "Content-Type: text/plain\n\n",
+                "See attached contact\n",
+                "--a\n",
+                "Content-Type: text/x-vcard; name=\"jdoe.vcf\"\n",
+                "Content-Disposition: attachment; filename=\"jdoe.vcf\"\n\n",
+                "BEGIN:VCARD\n",
+                "VERSION:3.0\n",
+                "FN:John Doe\n",
+                "EMAIL:jdoe@example.org\n",
+                "END:VCARD\n",
+                "--a--\n",
+            ))
+            .unwrap();
+
+        assert_eq!(message.parts[1].vcard(false), None);
+        let card = message.attachment(0).unwrap().vcard(false).unwrap();
+        assert_eq!(card.full_name.as_deref(), Some("John Doe"));
+        assert_eq!(card.emails, vec!["jdoe@example.org".to_string()]);
+    }
+}
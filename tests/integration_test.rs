@@ -155,3 +155,161 @@ R0lGODlhAQABAIAAAAAAAP///yH5BAEAAAAALAAAAAABAAEAAAIBRAA7
         "Book about ☕ tables.gif"
     );
 }
+
+#[test]
+fn test_reordered_mime_headers() {
+    let ordered = concat!(
+        "MIME-Version: 1.0\n",
+        "Subject: ordered\n",
+        "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+        "--a\n",
+        "Content-Type: text/plain; charset=iso-8859-1\n",
+        "Content-Transfer-Encoding: quoted-printable\n\n",
+        "caf=E9\n",
+        "--a\n",
+        "Content-Type: message/rfc822\n",
+        "Content-Disposition: attachment\n\n",
+        "MIME-Version: 1.0\n",
+        "Content-Type: multipart/alternative; boundary=\"b\"\n\n",
+        "--b\n",
+        "Content-Type: text/html\n",
+        "Content-Transfer-Encoding: base64\n\n",
+        "PHA+aGk8L3A+\n",
+        "--b--\n",
+        "--a--\n",
+    );
+    let reordered = concat!(
+        "Content-Type: multipart/mixed; boundary=\"a\"\n",
+        "Subject: reordered\n",
+        "MIME-Version: 1.0\n\n",
+        "--a\n",
+        "Content-Transfer-Encoding: quoted-printable\n",
+        "Content-Type: text/plain; charset=iso-8859-1\n\n",
+        "caf=E9\n",
+        "--a\n",
+        "Content-Disposition: attachment\n",
+        "Content-Type: message/rfc822\n\n",
+        "Content-Type: multipart/alternative; boundary=\"b\"\n",
+        "MIME-Version: 1.0\n\n",
+        "--b\n",
+        "Content-Transfer-Encoding: base64\n",
+        "Content-Type: text/html\n\n",
+        "PHA+aGk8L3A+\n",
+        "--b--\n",
+        "--a--\n",
+    );
+
+    fn structure(message: &Message) -> Vec<String> {
+        message
+            .parts
+            .iter()
+            .map(|part| {
+                let ct = part.content_type().unwrap();
+                format!(
+                    "{}/{} {:?} {:?}",
+                    ct.ctype(),
+                    ct.subtype().unwrap_or_default(),
+                    ct.attributes(),
+                    match &part.body {
+                        PartType::Text(text) => text.to_string(),
+                        PartType::Message(message) => structure(message).join(" | "),
+                        PartType::Multipart(parts) => format!("{parts:?}"),
+                        _ => String::new(),
+                    }
+                )
+            })
+            .collect()
+    }
+
+    let ordered = MessageParser::default().parse(ordered).unwrap();
+    let reordered = MessageParser::default().parse(reordered).unwrap();
+    assert_eq!(structure(&ordered), structure(&reordered));
+    assert_eq!(reordered.body_text(0).unwrap(), "café");
+    let nested = reordered.attachment(0).unwrap().message().unwrap();
+    assert_eq!(
+        nested.content_type().unwrap().subtype(),
+        Some("alternative")
+    );
+    assert_eq!(nested.body_html(0).unwrap(), "<p>hi</p>");
+}
+
+#[test]
+fn test_nul_bytes() {
+    let input = concat!(
+        "From: \"John\0Doe\" <john@example.org>\n",
+        "Subject: Hello\0world\n",
+        "X-Custom: a\0b\n",
+        "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+        "--a\n",
+        "Content-Type: text/plain\n\n",
+        "before\0after\n",
+        "--a\n",
+        "Content-Type: application/octet-stream\n\n",
+        "\0\0\0binary\0\n",
+        "--a\n",
+        "Content-Type: text/plain\n",
+        "Content-Transfer-Encoding: base64\n\n",
+        "bnVsAGJ5dGU=\n",
+        "--a--\n",
+    )
+    .as_bytes();
+    let message = MessageParser::default().parse(input).unwrap();
+
+    assert_eq!(message.subject(), Some("Hello\0world"));
+    assert_eq!(
+        message.from().unwrap().first().unwrap().name(),
+        Some("John\0Doe")
+    );
+    assert_eq!(message.header_raw("X-Custom"), Some(" a\0b\n"));
+    assert_eq!(message.parts.len(), 4);
+    assert_eq!(message.body_text(0).unwrap(), "before\0after");
+    assert_eq!(message.parts[2].contents(), b"\0\0\0binary\0");
+    assert_eq!(message.body_text(1).unwrap(), "nul\0byte");
+    assert_eq!(message.raw_message(), input);
+
+    // Invalid UTF-8 next to a NUL byte only replaces the invalid sequence
+    let message = MessageParser::default()
+        .parse(&b"Subject: a\0\xffb\n\nc\0\xfed\n"[..])
+        .unwrap();
+    assert_eq!(message.subject(), Some("a\0\u{fffd}b"));
+    assert_eq!(message.body_text(0).unwrap(), "c\0\u{fffd}d\n");
+}
+
+#[test]
+fn test_parse_into() {
+    let parser = MessageParser::default();
+    let inputs = [
+        concat!(
+            "Subject: first\n",
+            "Content-Type: multipart/mixed; boundary=\"a\"\n\n",
+            "--a\n",
+            "Content-Type: text/plain\n\n",
+            "one\n",
+            "--a\n",
+            "Content-Type: application/octet-stream\n\n",
+            "two\n",
+            "--a\n",
+            "Content-Type: text/html\n\n",
+            "<p>three</p>\n",
+            "--a--\n",
+        )
+        .to_string(),
+        "Subject: second\n\nbody".to_string(),
+    ];
+
+    let mut message = Message::default();
+    let mut capacity = 0;
+    for input in &inputs {
+        let mut reused = message.recycle();
+        assert!(reused.parts.is_empty() && reused.parts.capacity() >= capacity);
+        assert!(parser.parse_into(input, &mut reused));
+        assert_eq!(reused, parser.parse(input).unwrap());
+        capacity = reused.parts.capacity();
+        message = reused.recycle();
+    }
+    assert!(capacity >= 4);
+
+    let mut message = parser.parse(&inputs[0]).unwrap();
+    assert!(!parser.parse_into("", &mut message));
+    assert!(message.is_empty());
+}